@@ -643,6 +643,7 @@ mod tests {
             pitch_variance: 0,
             data_offset: 0,
             string_size: 0,
+            sound_length: 0.0,
         });
         let symbol = tlk.interner.get_or_intern("Khelgar Ironfist");
         tlk.string_cache.insert(
@@ -13,6 +13,7 @@ pub struct AppConfigResponse {
     pub backup_count: u32,
     pub auto_close_on_launch: bool,
     pub show_launch_dialog: bool,
+    pub refresh_stale_icons: bool,
 }
 
 #[tauri::command]
@@ -32,6 +33,7 @@ fn build_response(config: &crate::config::AppConfig) -> AppConfigResponse {
         backup_count: config.backup_count,
         auto_close_on_launch: config.auto_close_on_launch,
         show_launch_dialog: config.show_launch_dialog,
+        refresh_stale_icons: config.refresh_stale_icons,
     }
 }
 
@@ -44,6 +46,7 @@ pub struct AppConfigUpdate {
     pub backup_count: Option<u32>,
     pub auto_close_on_launch: Option<bool>,
     pub show_launch_dialog: Option<bool>,
+    pub refresh_stale_icons: Option<bool>,
 }
 
 #[tauri::command]
@@ -76,6 +79,9 @@ pub async fn update_app_config(
     if let Some(show_dialog) = updates.show_launch_dialog {
         config.show_launch_dialog = show_dialog;
     }
+    if let Some(refresh_icons) = updates.refresh_stale_icons {
+        config.refresh_stale_icons = refresh_icons;
+    }
 
     config.save().map_err(|e| CommandError::OperationFailed {
         operation: "save_app_config".to_string(),
@@ -1,9 +1,12 @@
+use std::collections::HashMap;
+
 use image::{ImageBuffer, RgbaImage};
 use serde::{Deserialize, Serialize};
 use tauri::State;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 use crate::services::model_loader::{self, ModelData};
+use crate::services::resource_manager::sniff_icon_format;
 use crate::state::AppState;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -69,43 +72,57 @@ pub fn get_texture_bytes(
 
 #[tauri::command]
 pub fn get_icon_png(state: State<'_, AppState>, name: String) -> Result<String, String> {
+    if state.config.read().refresh_stale_icons {
+        refresh_stale_icons(&state);
+    }
     let rm = state.resource_manager.blocking_read();
 
-    // 1. Check indexed icon files (upscaled DDS, workshop overrides)
-    if let Some(icon_path) = rm.get_icon_path(&name) {
-        let path: &std::path::Path = &icon_path;
-        let bytes = std::fs::read(path).map_err(|e| format!("Failed to read icon {name}: {e}"))?;
-
-        let ext = path
-            .extension()
-            .and_then(|e| e.to_str())
-            .unwrap_or("dds")
-            .to_lowercase();
-
-        let png_bytes = if ext == "tga" {
-            decode_tga_to_png(&bytes)
-                .map_err(|e| format!("Failed to decode TGA icon {name}: {e}"))?
-        } else {
-            decode_dds_to_png(&bytes)
-                .map_err(|e| format!("Failed to decode DDS icon {name}: {e}"))?
-        };
-        return encode_png_data_url(&png_bytes);
-    }
+    // Module HAKs, icon folders (upscaled DDS, workshop), then override/zips
+    let bytes = rm
+        .get_icon_bytes(&name)
+        .ok_or_else(|| format!("Icon not found: {name}"))?;
+    let png_bytes =
+        decode_icon_to_png(&bytes).map_err(|e| format!("Failed to decode icon {name}: {e}"))?;
+    encode_png_data_url(&png_bytes)
+}
 
-    // 2. Fallback to get_resource_bytes (HAKs, override, zips)
-    if let Ok(dds_bytes) = rm.get_resource_bytes(&name, "dds") {
-        let png_bytes = decode_dds_to_png(&dds_bytes)
-            .map_err(|e| format!("Failed to decode icon {name}: {e}"))?;
-        return encode_png_data_url(&png_bytes);
-    }
+/// `get_icon_png` for many icons in one call, keyed by lowercase resref.
+/// Icons that don't resolve or fail to decode are left out.
+#[tauri::command]
+pub fn get_icons_png_batch(
+    state: State<'_, AppState>,
+    names: Vec<String>,
+) -> Result<HashMap<String, String>, String> {
+    use rayon::prelude::*;
 
-    if let Ok(tga_bytes) = rm.get_resource_bytes(&name, "tga") {
-        let png_bytes = decode_tga_to_png(&tga_bytes)
-            .map_err(|e| format!("Failed to decode TGA icon {name}: {e}"))?;
-        return encode_png_data_url(&png_bytes);
+    if state.config.read().refresh_stale_icons {
+        refresh_stale_icons(&state);
     }
+    let rm = state.resource_manager.blocking_read();
 
-    Err(format!("Icon not found: {name}"))
+    Ok(rm
+        .get_icons_batch(&names)
+        .into_par_iter()
+        .filter_map(|(name, bytes)| match decode_icon_to_png(&bytes) {
+            Ok(png_bytes) => encode_png_data_url(&png_bytes).ok().map(|url| (name, url)),
+            Err(e) => {
+                debug!("Failed to decode icon {}: {}", name, e);
+                None
+            }
+        })
+        .collect())
+}
+
+/// Re-index the icon folders if they changed since the last scan, so icons
+/// added while the editor is open show up.
+fn refresh_stale_icons(state: &AppState) {
+    if !state.resource_manager.blocking_read().icons_stale() {
+        return;
+    }
+    let mut rm = state.resource_manager.blocking_write();
+    if let Err(e) = tauri::async_runtime::block_on(rm.refresh_icons_if_stale()) {
+        warn!("Failed to rescan icon directories: {}", e);
+    }
 }
 
 fn encode_png_data_url(png_bytes: &[u8]) -> Result<String, String> {
@@ -114,6 +131,26 @@ fn encode_png_data_url(png_bytes: &[u8]) -> Result<String, String> {
     Ok(format!("data:image/png;base64,{b64}"))
 }
 
+/// Pick the decoder from the icon's contents rather than its name, since
+/// upscaled icon sets ship DDS data under `.tga` names.
+fn decode_icon_to_png(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    match sniff_icon_format(bytes) {
+        Some("dds") => decode_dds_to_png(bytes),
+        Some("tga") => decode_tga_to_png(bytes),
+        Some(_) => decode_image_to_png(bytes),
+        // No signature: most likely an old-style TGA
+        None => decode_tga_to_png(bytes).or_else(|_| decode_image_to_png(bytes)),
+    }
+}
+
+fn decode_image_to_png(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let img = image::load_from_memory(bytes).map_err(|e| format!("Image decode failed: {e}"))?;
+    let mut png_buf = std::io::Cursor::new(Vec::new());
+    img.write_to(&mut png_buf, image::ImageFormat::Png)
+        .map_err(|e| format!("PNG encode failed: {e}"))?;
+    Ok(png_buf.into_inner())
+}
+
 fn decode_tga_to_png(tga_bytes: &[u8]) -> Result<Vec<u8>, String> {
     let img = image::load_from_memory_with_format(tga_bytes, image::ImageFormat::Tga)
         .map_err(|e| format!("TGA decode failed: {e}"))?;
@@ -163,3 +200,52 @@ pub fn list_available_models(state: State<'_, AppState>) -> Result<Vec<ModelEntr
     *cache = Some(result.clone());
     Ok(result)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 4x4 legacy-DXT1 DDS of solid red, padded past the decoder's minimum size.
+    fn dxt1_red() -> Vec<u8> {
+        let mut d = vec![0u8; 160];
+        d[0..4].copy_from_slice(b"DDS ");
+        d[4..8].copy_from_slice(&124u32.to_le_bytes());
+        d[12..16].copy_from_slice(&4u32.to_le_bytes());
+        d[16..20].copy_from_slice(&4u32.to_le_bytes());
+        d[80..84].copy_from_slice(&0x4u32.to_le_bytes());
+        d[84..88].copy_from_slice(b"DXT1");
+        d[128..130].copy_from_slice(&0xF800u16.to_le_bytes());
+        d[130..132].copy_from_slice(&0xF800u16.to_le_bytes());
+        d
+    }
+
+    #[test]
+    fn test_decode_icon_sniffs_dds_saved_as_tga() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ife_upscaled.tga");
+        std::fs::write(&path, dxt1_red()).unwrap();
+
+        let png = decode_icon_to_png(&std::fs::read(&path).unwrap()).expect("decode as DDS");
+        let img = image::load_from_memory_with_format(&png, image::ImageFormat::Png).unwrap();
+        assert_eq!((img.width(), img.height()), (4, 4));
+        assert_eq!(img.to_rgba8().get_pixel(0, 0).0, [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_decode_icon_falls_back_to_signatureless_tga_and_png() {
+        let mut tga = vec![0u8; 18];
+        tga[2] = 2; // uncompressed true-color
+        tga[12] = 1; // 1x1
+        tga[14] = 1;
+        tga[16] = 24;
+        tga.extend_from_slice(&[0, 0, 255]); // BGR red
+        assert_eq!(sniff_icon_format(&tga), None);
+        let png = decode_icon_to_png(&tga).expect("decode as TGA");
+
+        // A PNG under any name is re-encoded through `image`
+        assert_eq!(sniff_icon_format(&png), Some("png"));
+        let reencoded = decode_icon_to_png(&png).expect("decode as PNG");
+        let img = image::load_from_memory(&reencoded).unwrap();
+        assert_eq!(img.to_rgba8().get_pixel(0, 0).0, [255, 0, 0, 255]);
+    }
+}
@@ -3,6 +3,7 @@ use std::path::PathBuf;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AppConfig {
     pub theme: String,
     pub language: String,
@@ -14,6 +15,8 @@ pub struct AppConfig {
     pub last_save_path: Option<PathBuf>,
     pub recent_saves: Vec<PathBuf>,
     pub max_recent_saves: usize,
+    /// Rescan icon folders when they change on disk; off skips the mtime check.
+    pub refresh_stale_icons: bool,
 }
 
 impl Default for AppConfig {
@@ -29,6 +32,7 @@ impl Default for AppConfig {
             last_save_path: None,
             recent_saves: Vec::new(),
             max_recent_saves: 10,
+            refresh_stale_icons: true,
         }
     }
 }
@@ -679,6 +679,11 @@ impl NWN2Paths {
     pub fn set_game_folder_for_test(&mut self, path: std::path::PathBuf) {
         self.game_folder = Some(path);
     }
+
+    #[cfg(test)]
+    pub fn set_documents_folder_for_test(&mut self, path: std::path::PathBuf) {
+        self.documents_folder = Some(path);
+    }
 }
 
 #[cfg(all(target_os = "linux", not(windows)))]
@@ -305,6 +305,59 @@ pub async fn validate_nwn2_installation(path: String) -> Result<bool, String> {
     Ok(true)
 }
 
+/// WebP encoding mode for images sent to the frontend.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WebpQuality {
+    Lossless,
+    /// Lossy encoding at the given quality, 0.0 to 100.0.
+    Lossy(f32),
+}
+
+impl Default for WebpQuality {
+    fn default() -> Self {
+        // Good balance of quality and size for save thumbnails
+        Self::Lossy(85.0)
+    }
+}
+
+/// Output settings for [`convert_image_data`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ImageConversionConfig {
+    /// Bounding box edge in pixels; the image is scaled to fit, keeping its aspect ratio.
+    /// `None` keeps the source dimensions.
+    pub icon_size: Option<u32>,
+    pub quality: WebpQuality,
+}
+
+/// Resize a decoded image per `config` and encode it as WebP.
+pub fn convert_image_data(
+    image: image::DynamicImage,
+    config: &ImageConversionConfig,
+) -> Result<Vec<u8>, String> {
+    let image = match config.icon_size {
+        Some(size) if image.width() != size || image.height() != size => {
+            image.thumbnail(size, size)
+        }
+        _ => image,
+    };
+
+    // The webp encoder only accepts 8-bit RGB/RGBA buffers
+    let image = match image {
+        image::DynamicImage::ImageRgb8(_) | image::DynamicImage::ImageRgba8(_) => image,
+        other => image::DynamicImage::ImageRgba8(other.to_rgba8()),
+    };
+    let encoder = webp::Encoder::from_image(&image).map_err(|e| {
+        log::error!("Failed to create WebP encoder: {e}");
+        "Failed to create WebP encoder from image.".to_string()
+    })?;
+
+    let webp_memory = match config.quality {
+        WebpQuality::Lossless => encoder.encode_lossless(),
+        WebpQuality::Lossy(quality) => encoder.encode(quality.clamp(0.0, 100.0)),
+    };
+    Ok(webp_memory.to_vec())
+}
+
 #[tauri::command]
 pub async fn get_save_thumbnail(thumbnail_path: String) -> Result<String, String> {
     log::info!("[Rust] Starting thumbnail conversion process for: {thumbnail_path}");
@@ -332,15 +385,7 @@ pub async fn get_save_thumbnail(thumbnail_path: String) -> Result<String, String
         dynamic_image.height()
     );
 
-    // Convert to WebP with quality control using webp crate
-    let encoder = webp::Encoder::from_image(&dynamic_image).map_err(|e| {
-        log::error!("Failed to create WebP encoder: {e}");
-        "Failed to create WebP encoder from image.".to_string()
-    })?;
-
-    // Encode with quality setting of 85.0 (out of 100) for good balance of quality and size
-    let webp_memory = encoder.encode(85.0);
-    let webp_data = webp_memory.to_vec();
+    let webp_data = convert_image_data(dynamic_image, &ImageConversionConfig::default())?;
 
     log::debug!(
         "[Rust] Successfully converted thumbnail to WebP ({} bytes)",
@@ -1018,4 +1063,37 @@ mod tests {
             tmp.path().join("screen.tga")
         );
     }
+
+    #[test]
+    fn test_convert_image_data_resizes_to_configured_icon_size() {
+        let source = image::DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(
+            64,
+            64,
+            image::Rgba([200, 40, 40, 255]),
+        ));
+        let config = ImageConversionConfig {
+            icon_size: Some(32),
+            quality: WebpQuality::Lossy(75.0),
+        };
+
+        let webp_data = convert_image_data(source, &config).unwrap();
+        let decoded = image::load_from_memory(&webp_data).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (32, 32));
+    }
+
+    #[test]
+    fn test_convert_image_data_lossless_round_trips_pixels() {
+        let source = image::RgbaImage::from_fn(16, 16, |x, y| {
+            image::Rgba([(x * 16) as u8, (y * 16) as u8, 128, 255])
+        });
+        let config = ImageConversionConfig {
+            icon_size: None,
+            quality: WebpQuality::Lossless,
+        };
+
+        let webp_data =
+            convert_image_data(image::DynamicImage::ImageRgba8(source.clone()), &config).unwrap();
+        let decoded = image::load_from_memory(&webp_data).unwrap().to_rgba8();
+        assert_eq!(decoded, source);
+    }
 }
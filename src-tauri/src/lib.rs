@@ -363,6 +363,7 @@ pub fn run() {
             crate::commands::models::load_model,
             crate::commands::models::get_texture_bytes,
             crate::commands::models::get_icon_png,
+            crate::commands::models::get_icons_png_batch,
             crate::commands::models::list_available_models,
         ])
         .run(tauri::generate_context!())
@@ -0,0 +1,73 @@
+//! Parallel multi-file loading shared by the per-format
+//! `load_multiple_files` helpers.
+
+use std::path::{Path, PathBuf};
+
+use ahash::AHashMap;
+use rayon::prelude::*;
+
+/// Per-file outcome of a batch load. Results are keyed on the path exactly
+/// as given, so two files whose names are not valid UTF-8 never collide on
+/// the same lossy string.
+#[derive(Debug)]
+pub struct BatchLoadResult<T, E> {
+    pub loaded: AHashMap<PathBuf, T>,
+    pub errors: AHashMap<PathBuf, E>,
+}
+
+impl<T, E> Default for BatchLoadResult<T, E> {
+    fn default() -> Self {
+        Self {
+            loaded: AHashMap::new(),
+            errors: AHashMap::new(),
+        }
+    }
+}
+
+impl<T, E> BatchLoadResult<T, E> {
+    pub fn insert(&mut self, path: PathBuf, outcome: Result<T, E>) {
+        match outcome {
+            Ok(parser) => {
+                self.loaded.insert(path, parser);
+            }
+            Err(e) => {
+                self.errors.insert(path, e);
+            }
+        }
+    }
+}
+
+/// Run `load` on every path. Each file loads independently: a corrupt file
+/// lands in `errors` while the rest still appear in `loaded`. `max_threads`
+/// bounds the Rayon pool used (`None` uses the global pool); a single file,
+/// or `Some(1)`, loads on the calling thread.
+pub fn load_multiple<P, T, E, F>(
+    paths: &[P],
+    max_threads: Option<usize>,
+    load: F,
+) -> BatchLoadResult<T, E>
+where
+    P: AsRef<Path> + Sync,
+    T: Send,
+    E: Send,
+    F: Fn(&Path) -> Result<T, E> + Sync,
+{
+    let load = |path: &P| (path.as_ref().to_path_buf(), load(path.as_ref()));
+
+    let outcomes: Vec<(PathBuf, Result<T, E>)> = if paths.len() <= 1 || max_threads == Some(1) {
+        paths.iter().map(load).collect()
+    } else {
+        let load_all = || paths.par_iter().map(load).collect();
+        match max_threads.and_then(|n| rayon::ThreadPoolBuilder::new().num_threads(n).build().ok())
+        {
+            Some(pool) => pool.install(load_all),
+            None => load_all(),
+        }
+    };
+
+    let mut result = BatchLoadResult::default();
+    for (path, outcome) in outcomes {
+        result.insert(path, outcome);
+    }
+    result
+}
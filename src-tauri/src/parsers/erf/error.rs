@@ -24,6 +24,12 @@ pub enum ErfError {
     #[error("Invalid resource name: contains non-ASCII characters")]
     InvalidResourceName,
 
+    #[error("Resource '{name}' is already in the archive")]
+    DuplicateResource { name: String },
+
+    #[error("Resource name '{name}' is longer than {max} characters")]
+    ResourceNameTooLong { name: String, max: usize },
+
     #[error("Security violation: {message}")]
     SecurityViolation { message: String },
 
@@ -3,9 +3,10 @@ pub mod parser;
 pub mod types;
 
 pub use error::{ErfError, ErfResult};
-pub use parser::ErfParser;
+pub use parser::{ErfParser, erf_description};
 pub use types::SecurityLimits;
 pub use types::{
-    ErfBuilder, ErfHeader, ErfResource, ErfStatistics, ErfType, ErfVersion, FileMetadata, KeyEntry,
-    ResourceEntry, extension_to_resource_type, resource_type_to_extension,
+    ErfBuilder, ErfHeader, ErfIntegrityIssue, ErfResource, ErfResourceInfo, ErfStatistics, ErfType,
+    ErfVersion, ErfWarning, FileMetadata, KeyEntry, ResourceEntry, extension_to_resource_type,
+    resource_type_name, resource_type_to_extension,
 };
@@ -1,16 +1,20 @@
 use super::error::{ErfError, ErfResult};
 use super::types::{
-    ErfHeader, ErfResource, ErfStatistics, ErfType, ErfVersion, FileMetadata, KeyEntry,
-    ResourceEntry, SecurityLimits, resource_type_to_extension,
+    ErfHeader, ErfIntegrityIssue, ErfResource, ErfResourceInfo, ErfStatistics, ErfType, ErfVersion,
+    ErfWarning, FileMetadata, KeyEntry, ResourceEntry, SecurityLimits, extension_to_resource_type,
+    resource_type_name, resource_type_to_extension,
 };
+use crate::parsers::tlk::TLKParser;
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use encoding_rs::WINDOWS_1252;
 use indexmap::IndexMap;
 use lasso::Rodeo;
 use memmap2::Mmap;
-use std::collections::HashMap;
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io::{BufWriter, Cursor, Read, Seek, SeekFrom, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
 pub struct ErfParser {
@@ -22,8 +26,11 @@ pub struct ErfParser {
     pub security_limits: SecurityLimits,
     pub stats: ErfStatistics,
     pub metadata: Option<FileMetadata>,
+    description: Vec<(u32, String)>,
     mmap: Option<Mmap>,
     file_data: Option<Vec<u8>>,
+    lenient_signature: bool,
+    warnings: Vec<ErfWarning>,
 }
 
 impl Default for ErfParser {
@@ -49,8 +56,11 @@ impl ErfParser {
                 parse_time_ms: 0,
             },
             metadata: None,
+            description: Vec::new(),
             mmap: None,
             file_data: None,
+            lenient_signature: false,
+            warnings: Vec::new(),
         }
     }
 
@@ -59,6 +69,19 @@ impl ErfParser {
         self
     }
 
+    /// Parse archives whose signature isn't `ERF`/`HAK`/`MOD`, recording an
+    /// `ErfWarning` and leaving `erf_type` unset instead of failing. Set
+    /// `erf_type` before writing such an archive back out.
+    pub fn with_lenient_signature(mut self, lenient: bool) -> Self {
+        self.lenient_signature = lenient;
+        self
+    }
+
+    /// Problems tolerated during the last parse.
+    pub fn warnings(&self) -> &[ErfWarning] {
+        &self.warnings
+    }
+
     pub fn read<P: AsRef<Path>>(&mut self, path: P) -> ErfResult<()> {
         let start = Instant::now();
         let path = path.as_ref();
@@ -81,6 +104,7 @@ impl ErfParser {
 
         if let Some(header) = self.header.clone() {
             self.validate_header(&header, file_size)?;
+            self.parse_localized_strings(&mut cursor, &header)?;
 
             // Parse key and resource lists
             let keys = self.parse_key_list(&mut cursor, &header)?;
@@ -139,6 +163,7 @@ impl ErfParser {
 
         if let Some(header) = self.header.clone() {
             self.validate_header(&header, file_size)?;
+            self.parse_localized_strings(&mut cursor, &header)?;
 
             let keys = self.parse_key_list(&mut cursor, &header)?;
             let resources = self.parse_resource_list(&mut cursor, &header)?;
@@ -160,12 +185,15 @@ impl ErfParser {
         let mut sig = [0u8; 4];
         reader.read_exact(&mut sig)?;
 
-        self.erf_type =
-            Some(
-                ErfType::from_signature(&sig).ok_or_else(|| ErfError::InvalidSignature {
-                    found: String::from_utf8_lossy(&sig).into_owned(),
-                })?,
-            );
+        self.warnings.clear();
+        self.erf_type = ErfType::from_signature(&sig);
+        if self.erf_type.is_none() {
+            let found = String::from_utf8_lossy(&sig).into_owned();
+            if !self.lenient_signature {
+                return Err(ErfError::InvalidSignature { found });
+            }
+            self.warnings.push(ErfWarning::UnknownSignature { found });
+        }
 
         let mut ver = [0u8; 4];
         reader.read_exact(&mut ver)?;
@@ -210,18 +238,54 @@ impl ErfParser {
             });
         }
 
-        if header.offset_to_key_list as usize > file_size {
-            return Err(ErfError::InvalidOffset {
-                offset: header.offset_to_key_list as usize,
-                file_size,
-            });
+        let entry_count = header.entry_count as usize;
+        let key_size = self.version.map_or(0, |v| v.key_entry_size());
+
+        let key_list_size = checked_table_size(entry_count, key_size, file_size)?;
+        checked_end(header.offset_to_key_list as usize, key_list_size, file_size)?;
+        checked_end(
+            header.offset_to_localized_string as usize,
+            header.localized_string_size as usize,
+            file_size,
+        )?;
+
+        let resource_list_size = checked_table_size(entry_count, RESOURCE_ENTRY_SIZE, file_size)?;
+        checked_end(
+            header.offset_to_resource_list as usize,
+            resource_list_size,
+            file_size,
+        )?;
+
+        Ok(())
+    }
+
+    fn parse_localized_strings<R: Read + Seek>(
+        &mut self,
+        reader: &mut R,
+        header: &ErfHeader,
+    ) -> ErfResult<()> {
+        self.description.clear();
+        if header.language_count == 0 {
+            return Ok(());
         }
 
-        if header.offset_to_resource_list as usize > file_size {
-            return Err(ErfError::InvalidOffset {
-                offset: header.offset_to_resource_list as usize,
-                file_size,
-            });
+        reader.seek(SeekFrom::Start(u64::from(
+            header.offset_to_localized_string,
+        )))?;
+
+        let mut remaining = header.localized_string_size as usize;
+        for _ in 0..header.language_count {
+            let language_id = reader.read_u32::<LittleEndian>()?;
+            let size = reader.read_u32::<LittleEndian>()? as usize;
+
+            remaining = remaining
+                .checked_sub(LOCALIZED_STRING_HEADER_SIZE + size)
+                .ok_or_else(|| ErfError::corrupted_data("Localized string overruns its block"))?;
+
+            let mut bytes = vec![0u8; size];
+            reader.read_exact(&mut bytes)?;
+            let (text, _, _) = WINDOWS_1252.decode(&bytes);
+            self.description.push((language_id, text.into_owned()));
         }
 
         Ok(())
@@ -345,6 +409,28 @@ impl ErfParser {
         Ok(())
     }
 
+    fn find_resource(&self, name: &str) -> Option<&ErfResource> {
+        self.resources.get(&name.to_lowercase())
+    }
+
+    /// Probe for a resource (e.g. `module.ifo`) without listing or extracting.
+    pub fn contains(&self, name: &str) -> bool {
+        self.find_resource(name).is_some()
+    }
+
+    pub fn resource_type_of(&self, name: &str) -> Option<u16> {
+        self.find_resource(name).map(|res| res.key.resource_type)
+    }
+
+    pub fn resource_size(&self, name: &str) -> Option<u32> {
+        self.find_resource(name).map(|res| res.entry.size)
+    }
+
+    /// `Some(true)` for a zero-length entry, `None` if the resource doesn't exist.
+    pub fn is_empty_resource(&self, name: &str) -> Option<bool> {
+        self.find_resource(name).map(|res| res.entry.size == 0)
+    }
+
     pub fn list_resources(&self, resource_type: Option<u16>) -> Vec<(String, u32, u16)> {
         self.resources
             .iter()
@@ -353,6 +439,31 @@ impl ErfParser {
             .collect()
     }
 
+    /// Names of the resources whose type matches a file extension such as
+    /// `"2da"` or `".uti"`. Unknown extensions match nothing.
+    pub fn list_by_extension(&self, ext: &str) -> Vec<String> {
+        let Some(resource_type) = extension_to_resource_type(ext.trim_start_matches('.')) else {
+            return Vec::new();
+        };
+        self.list_resources(Some(resource_type))
+            .into_iter()
+            .map(|(name, _, _)| name)
+            .collect()
+    }
+
+    /// `list_resources` with each entry's readable type name.
+    pub fn list_resources_detailed(&self, resource_type: Option<u16>) -> Vec<ErfResourceInfo> {
+        self.list_resources(resource_type)
+            .into_iter()
+            .map(|(name, size, resource_type)| ErfResourceInfo {
+                name,
+                size,
+                resource_type,
+                type_name: resource_type_name(resource_type),
+            })
+            .collect()
+    }
+
     pub fn extract_resource(&mut self, name: &str) -> ErfResult<Vec<u8>> {
         let name_lower = name.to_lowercase();
 
@@ -375,6 +486,15 @@ impl ErfParser {
             return Ok(cached_data.clone());
         }
 
+        // Zero-length entries are valid empty files; don't let an `offset == 0`
+        // placeholder send us reading from the data source at all
+        if entry.size == 0 {
+            if let Some(resource) = self.resources.get_mut(&name_lower) {
+                resource.data = Some(Vec::new());
+            }
+            return Ok(Vec::new());
+        }
+
         // Extract data
         let data = if let Some(mmap) = &self.mmap {
             self.extract_from_mmap(mmap, &entry)?
@@ -393,31 +513,14 @@ impl ErfParser {
     }
 
     fn extract_from_mmap(&self, mmap: &Mmap, entry: &ResourceEntry) -> ErfResult<Vec<u8>> {
-        let offset = entry.offset as usize;
-        let size = entry.size as usize;
-
-        if offset + size > mmap.len() {
-            return Err(ErfError::InvalidOffset {
-                offset: offset + size,
-                file_size: mmap.len(),
-            });
-        }
-
-        Ok(mmap[offset..offset + size].to_vec())
+        self.extract_from_bytes(&mmap[..], entry)
     }
 
     fn extract_from_bytes(&self, data: &[u8], entry: &ResourceEntry) -> ErfResult<Vec<u8>> {
         let offset = entry.offset as usize;
-        let size = entry.size as usize;
+        let end = checked_end(offset, entry.size as usize, data.len())?;
 
-        if offset + size > data.len() {
-            return Err(ErfError::InvalidOffset {
-                offset: offset + size,
-                file_size: data.len(),
-            });
-        }
-
-        Ok(data[offset..offset + size].to_vec())
+        Ok(data[offset..end].to_vec())
     }
 
     pub fn extract_all_by_type(
@@ -449,6 +552,182 @@ impl ErfParser {
         Ok(extracted)
     }
 
+    /// Extract several resources at once, copying slices out of the mapped
+    /// file in parallel. Unlike `extract_resource` this takes `&self` and never
+    /// populates the per-resource `data` cache; already-cached resources are
+    /// returned from the cache. Results keep the order of `names`.
+    pub fn extract_many(&self, names: &[String]) -> ErfResult<Vec<(String, Vec<u8>)>> {
+        names
+            .par_iter()
+            .map(|name| {
+                let resource = self
+                    .find_resource(name)
+                    .ok_or_else(|| ErfError::ResourceNotFound { name: name.clone() })?;
+                Ok((name.clone(), self.read_resource(resource)?))
+            })
+            .collect()
+    }
+
+    /// Parallel counterpart of `extract_all_by_type`: resources are read via
+    /// `extract_many` and written concurrently. The `data` cache is left
+    /// untouched.
+    pub fn extract_all_by_type_parallel(
+        &self,
+        resource_type: u16,
+        output_dir: &Path,
+    ) -> ErfResult<Vec<String>> {
+        std::fs::create_dir_all(output_dir)?;
+
+        let names: Vec<String> = self
+            .resources
+            .iter()
+            .filter(|(_, res)| res.key.resource_type == resource_type)
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        self.extract_many(&names)?
+            .into_par_iter()
+            .map(|(name, data)| {
+                let output_path = output_dir.join(&name);
+                std::fs::write(&output_path, data)?;
+                Ok(output_path.to_string_lossy().into_owned())
+            })
+            .collect()
+    }
+
+    /// Borrow a resource's bytes straight from the mapped file (or the buffer
+    /// given to `parse_from_bytes`) without copying or caching them. Resources
+    /// added or updated in memory borrow their pending data instead.
+    pub fn resource_slice(&self, name: &str) -> ErfResult<&[u8]> {
+        let resource = self
+            .find_resource(name)
+            .ok_or_else(|| ErfError::ResourceNotFound {
+                name: name.to_string(),
+            })?;
+        self.resource_bytes(resource)
+    }
+
+    fn read_resource(&self, resource: &ErfResource) -> ErfResult<Vec<u8>> {
+        self.resource_bytes(resource).map(<[u8]>::to_vec)
+    }
+
+    fn resource_bytes<'a>(&'a self, resource: &'a ErfResource) -> ErfResult<&'a [u8]> {
+        if let Some(data) = &resource.data {
+            return Ok(data);
+        }
+        if resource.entry.size == 0 {
+            return Ok(&[]);
+        }
+
+        let data = self
+            .source_data()
+            .ok_or_else(|| ErfError::corrupted_data("No data source available"))?;
+        let offset = resource.entry.offset as usize;
+        let end = checked_end(offset, resource.entry.size as usize, data.len())?;
+        Ok(&data[offset..end])
+    }
+
+    fn source_data(&self) -> Option<&[u8]> {
+        self.mmap.as_deref().or(self.file_data.as_deref())
+    }
+
+    /// Check the archive's on-disk tables for problems the parser tolerates:
+    /// resources running past the end of the file, overlapping resource data,
+    /// an `entry_count` that disagrees with the resource map, and duplicate
+    /// names (which collapse to one entry when parsed). Every problem found
+    /// is reported rather than stopping at the first.
+    pub fn validate(&self) -> ErfResult<Vec<ErfIntegrityIssue>> {
+        let data = self
+            .source_data()
+            .ok_or_else(|| ErfError::corrupted_data("No data source available"))?;
+        let header = self
+            .header
+            .as_ref()
+            .ok_or_else(|| ErfError::corrupted_data("Missing header"))?;
+        let version = self
+            .version
+            .ok_or_else(|| ErfError::corrupted_data("Missing version"))?;
+
+        let mut issues = Vec::new();
+        let file_size = data.len();
+        let entry_count = header.entry_count as usize;
+
+        if entry_count != self.resources.len() {
+            issues.push(ErfIntegrityIssue::EntryCountMismatch {
+                header: header.entry_count,
+                actual: self.resources.len(),
+            });
+        }
+
+        // Re-read the raw tables: the resource map has already merged
+        // duplicate names, so it can't be trusted for this
+        let key_size = version.key_entry_size();
+        let name_length = version.max_resource_name_length();
+        let key_start = header.offset_to_key_list as usize;
+        let key_list_size = checked_table_size(entry_count, key_size, file_size)?;
+        let key_end = checked_end(key_start, key_list_size, file_size)?;
+        let list_start = header.offset_to_resource_list as usize;
+        let resource_list_size = checked_table_size(entry_count, RESOURCE_ENTRY_SIZE, file_size)?;
+        let list_end = checked_end(list_start, resource_list_size, file_size)?;
+
+        let mut seen = HashSet::new();
+        let mut extents = Vec::with_capacity(entry_count);
+        let keys = data[key_start..key_end].chunks_exact(key_size);
+        let entries = data[list_start..list_end].chunks_exact(RESOURCE_ENTRY_SIZE);
+
+        for (key, entry) in keys.zip(entries) {
+            let name_bytes = &key[..name_length];
+            let name_end = name_bytes
+                .iter()
+                .position(|&b| b == 0)
+                .unwrap_or(name_length);
+            let resource_type = u16::from_le_bytes([key[name_length + 4], key[name_length + 5]]);
+            let name = format!(
+                "{}.{}",
+                String::from_utf8_lossy(&name_bytes[..name_end]),
+                resource_type_to_extension(resource_type)
+            )
+            .to_lowercase();
+
+            let offset = u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]);
+            let size = u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]);
+
+            if !seen.insert(name.clone()) {
+                issues.push(ErfIntegrityIssue::DuplicateName { name: name.clone() });
+            }
+
+            if checked_end(offset as usize, size as usize, file_size).is_err() {
+                issues.push(ErfIntegrityIssue::ResourceOutOfBounds {
+                    name,
+                    offset,
+                    size,
+                    file_size,
+                });
+            } else if size > 0 {
+                extents.push((offset as usize, offset as usize + size as usize, name));
+            }
+        }
+
+        extents.sort_by_key(|&(offset, _, _)| offset);
+        let mut furthest: Option<&(usize, usize, String)> = None;
+        for extent in &extents {
+            if let Some(previous) = furthest {
+                if extent.0 < previous.1 {
+                    issues.push(ErfIntegrityIssue::OverlappingResources {
+                        first: previous.2.clone(),
+                        second: extent.2.clone(),
+                    });
+                }
+                if extent.1 <= previous.1 {
+                    continue;
+                }
+            }
+            furthest = Some(extent);
+        }
+
+        Ok(issues)
+    }
+
     pub fn extract_all_2da(&mut self, output_dir: &Path) -> ErfResult<Vec<String>> {
         self.extract_all_by_type(2017, output_dir) // 2017 is the 2DA resource type
     }
@@ -476,15 +755,14 @@ impl ErfParser {
         }
     }
 
+    /// Add a new resource. Names are case-insensitive, so a name already in
+    /// the archive (e.g. `ICON.TGA` after `Icon.tga`) is rejected with
+    /// `DuplicateResource`; use `update_resource` to replace contents.
     pub fn add_resource(&mut self, name: &str, resource_type: u16, data: Vec<u8>) -> ErfResult<()> {
         let version = self.version.unwrap_or(ErfVersion::V11);
         let max_name_len = version.max_resource_name_length();
 
-        let (base_name, ext) = if let Some(dot_pos) = name.rfind('.') {
-            (&name[..dot_pos], &name[dot_pos + 1..])
-        } else {
-            (name, "")
-        };
+        let base_name = name.rfind('.').map_or(name, |dot_pos| &name[..dot_pos]);
 
         if base_name.len() > max_name_len {
             return Err(ErfError::InvalidResourceName);
@@ -494,15 +772,10 @@ impl ErfParser {
             return Err(ErfError::InvalidResourceName);
         }
 
-        let full_name = if ext.is_empty() {
-            format!(
-                "{}.{}",
-                base_name,
-                resource_type_to_extension(resource_type)
-            )
-        } else {
-            name.to_string()
-        };
+        let full_name = resource_key(name, resource_type);
+        if self.resources.contains_key(&full_name) {
+            return Err(ErfError::DuplicateResource { name: full_name });
+        }
 
         let key = KeyEntry {
             resource_name: base_name.to_string(),
@@ -522,7 +795,7 @@ impl ErfParser {
             data: Some(data),
         };
 
-        self.resources.insert(full_name.to_lowercase(), resource);
+        self.resources.insert(full_name, resource);
 
         if let Some(header) = &mut self.header {
             header.entry_count = self.resources.len() as u32;
@@ -556,6 +829,56 @@ impl ErfParser {
         }
     }
 
+    /// Localized description strings as `(language_id, text)` pairs.
+    pub fn description(&self) -> &[(u32, String)] {
+        &self.description
+    }
+
+    /// Replace the localized description strings written to the archive's
+    /// localized string block. Text is stored as Windows-1252.
+    pub fn set_description(&mut self, strings: Vec<(u32, String)>) {
+        self.description = strings;
+        let block_size = self.localized_string_bytes().len();
+        if let Some(header) = &mut self.header {
+            header.language_count = self.description.len() as u32;
+            header.localized_string_size = block_size as u32;
+        }
+    }
+
+    /// Re-tag the archive as `target` so the next `to_bytes` writes that
+    /// version's key layout. Fails without changing anything if a resource
+    /// name is too long for `target` (16 characters for V1.0).
+    pub fn convert_version(&mut self, target: ErfVersion) -> ErfResult<()> {
+        let max = target.max_resource_name_length();
+        if let Some(resource) = self
+            .resources
+            .values()
+            .find(|resource| resource.key.resource_name.len() > max)
+        {
+            return Err(ErfError::ResourceNameTooLong {
+                name: resource.key.resource_name.clone(),
+                max,
+            });
+        }
+
+        self.version = Some(target);
+        if let Some(header) = &mut self.header {
+            header.version = String::from_utf8_lossy(target.version_bytes()).into_owned();
+        }
+        Ok(())
+    }
+
+    fn localized_string_bytes(&self) -> Vec<u8> {
+        let mut block = Vec::new();
+        for (language_id, text) in &self.description {
+            let (bytes, _, _) = WINDOWS_1252.encode(text);
+            block.extend_from_slice(&language_id.to_le_bytes());
+            block.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+            block.extend_from_slice(&bytes);
+        }
+        block
+    }
+
     pub fn to_bytes(&self) -> ErfResult<Vec<u8>> {
         let version = self
             .version
@@ -564,43 +887,102 @@ impl ErfParser {
             .erf_type
             .ok_or_else(|| ErfError::corrupted_data("No ERF type set"))?;
 
+        let localized_strings = self.localized_string_bytes();
+        let localized_size = localized_strings.len() as u32;
+
         let mut output = Vec::new();
 
-        self.write_header_bytes(&mut output, version, erf_type)?;
+        self.write_header_bytes(&mut output, version, erf_type, localized_size)?;
+        output.extend_from_slice(&localized_strings);
         self.write_keys_bytes(&mut output, version)?;
-        self.write_resource_list_bytes(&mut output)?;
+        self.write_resource_list_bytes(&mut output, localized_size)?;
         self.write_resource_data_bytes(&mut output)?;
 
         Ok(output)
     }
 
+    /// Write the archive through a sibling `.tmp` file renamed into place.
+    /// The parser is left as it was and keeps reading from its own source,
+    /// so this is also a "save as". Use `save` to write back over the file
+    /// the parser was read from.
     pub fn write<P: AsRef<Path>>(&self, path: P) -> ErfResult<()> {
+        let path = path.as_ref();
         let data = self.to_bytes()?;
-        let file = File::create(path)?;
-        let mut writer = BufWriter::new(file);
-        writer.write_all(&data)?;
-        writer.flush()?;
+        let temp_path = write_temp_file(path, &data)?;
+        std::fs::rename(&temp_path, path).map_err(|e| {
+            let _ = std::fs::remove_file(&temp_path);
+            e.into()
+        })
+    }
+
+    /// Like `write`, but when `path` is the file this parser was read from,
+    /// the parser then switches to the written archive, since untouched
+    /// resources now live at different offsets. The new state is parsed
+    /// from the written bytes before anything is replaced, so on error the
+    /// parser and the file on disk are both unchanged.
+    pub fn save<P: AsRef<Path>>(&mut self, path: P) -> ErfResult<()> {
+        let path = path.as_ref();
+        if !self.is_source(path) {
+            return self.write(path);
+        }
+
+        let data = self.to_bytes()?;
+        let mut reloaded = Self::new()
+            .with_limits(self.security_limits.clone())
+            .with_lenient_signature(self.lenient_signature);
+        reloaded.parse_from_bytes(&data)?;
+        reloaded.metadata = self.metadata.clone().map(|metadata| FileMetadata {
+            file_size: data.len(),
+            ..metadata
+        });
+        let temp_path = write_temp_file(path, &data)?;
+
+        // Windows won't replace a file that is still mapped; the source is
+        // untouched if the rename fails, so it can be mapped again
+        let unmapped = cfg!(windows) && self.mmap.take().is_some();
+        if let Err(e) = std::fs::rename(&temp_path, path) {
+            let _ = std::fs::remove_file(&temp_path);
+            if unmapped {
+                self.mmap = Some(unsafe { Mmap::map(&File::open(path)?)? });
+            }
+            return Err(e.into());
+        }
+
+        *self = reloaded;
         Ok(())
     }
 
+    /// Whether `path` names the file this parser was read from.
+    fn is_source(&self, path: &Path) -> bool {
+        let Some(metadata) = &self.metadata else {
+            return false;
+        };
+        let source = Path::new(&metadata.file_path);
+        match (source.canonicalize(), path.canonicalize()) {
+            (Ok(source), Ok(target)) => source == target,
+            _ => source == path,
+        }
+    }
+
     fn write_header_bytes(
         &self,
         output: &mut Vec<u8>,
         version: ErfVersion,
         erf_type: ErfType,
+        localized_size: u32,
     ) -> ErfResult<()> {
         let key_size = version.key_entry_size();
         let resource_count = self.resources.len();
 
         let header_size = 160u32;
-        let offset_to_keys = header_size;
+        let offset_to_keys = header_size + localized_size;
         let offset_to_resources = offset_to_keys + (resource_count as u32 * key_size as u32);
 
         output.extend_from_slice(erf_type.signature());
         output.extend_from_slice(version.version_bytes());
 
-        output.write_u32::<LittleEndian>(0)?;
-        output.write_u32::<LittleEndian>(0)?;
+        output.write_u32::<LittleEndian>(self.description.len() as u32)?;
+        output.write_u32::<LittleEndian>(localized_size)?;
         output.write_u32::<LittleEndian>(resource_count as u32)?;
         output.write_u32::<LittleEndian>(header_size)?;
         output.write_u32::<LittleEndian>(offset_to_keys)?;
@@ -638,7 +1020,11 @@ impl ErfParser {
         Ok(())
     }
 
-    fn write_resource_list_bytes(&self, output: &mut Vec<u8>) -> ErfResult<()> {
+    fn write_resource_list_bytes(
+        &self,
+        output: &mut Vec<u8>,
+        localized_size: u32,
+    ) -> ErfResult<()> {
         let version = self
             .version
             .ok_or_else(|| ErfError::corrupted_data("No version set"))?;
@@ -648,7 +1034,7 @@ impl ErfParser {
         let header_size = 160u32;
         let keys_size = (resource_count * key_size) as u32;
         let resource_list_size = (resource_count * 8) as u32;
-        let mut data_offset = header_size + keys_size + resource_list_size;
+        let mut data_offset = header_size + localized_size + keys_size + resource_list_size;
 
         for resource in self.resources.values() {
             output.write_u32::<LittleEndian>(data_offset)?;
@@ -659,15 +1045,23 @@ impl ErfParser {
         Ok(())
     }
 
+    /// Resources that were never extracted are copied straight from the
+    /// original file, so a parsed archive can be edited and rewritten without
+    /// calling `load_all_resources` first.
     fn write_resource_data_bytes(&self, output: &mut Vec<u8>) -> ErfResult<()> {
         for resource in self.resources.values() {
             if let Some(data) = &resource.data {
                 output.extend_from_slice(data);
-            } else {
-                return Err(ErfError::corrupted_data(format!(
-                    "Resource '{}' has no data loaded",
-                    resource.key.resource_name
-                )));
+            } else if resource.entry.size != 0 {
+                let source = self.source_data().ok_or_else(|| {
+                    ErfError::corrupted_data(format!(
+                        "Resource '{}' has no data loaded",
+                        resource.key.resource_name
+                    ))
+                })?;
+                let offset = resource.entry.offset as usize;
+                let end = checked_end(offset, resource.entry.size as usize, source.len())?;
+                output.extend_from_slice(&source[offset..end]);
             }
         }
 
@@ -705,8 +1099,11 @@ impl ErfParser {
                 parse_time_ms: 0,
             },
             metadata: None,
+            description: Vec::new(),
             mmap: None,
             file_data: None,
+            lenient_signature: false,
+            warnings: Vec::new(),
         }
     }
 
@@ -718,3 +1115,70 @@ impl ErfParser {
         Ok(())
     }
 }
+
+const RESOURCE_ENTRY_SIZE: usize = 8;
+const LOCALIZED_STRING_HEADER_SIZE: usize = 8;
+
+/// Write `data` to a `.tmp` sibling of `path`, removing it again on failure.
+fn write_temp_file(path: &Path, data: &[u8]) -> ErfResult<PathBuf> {
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    let temp_path = PathBuf::from(temp_path);
+    let written = File::create(&temp_path).and_then(|file| {
+        let mut writer = BufWriter::new(file);
+        writer.write_all(data)?;
+        writer.flush()
+    });
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(e.into());
+    }
+    Ok(temp_path)
+}
+
+/// Lookup key for a resource added as `name`: lowercased, with the
+/// extension for `resource_type` appended when `name` has none.
+pub(crate) fn resource_key(name: &str, resource_type: u16) -> String {
+    let (base_name, ext) = match name.rfind('.') {
+        Some(dot_pos) => (&name[..dot_pos], &name[dot_pos + 1..]),
+        None => (name, ""),
+    };
+    if ext.is_empty() {
+        format!("{base_name}.{}", resource_type_to_extension(resource_type)).to_lowercase()
+    } else {
+        name.to_lowercase()
+    }
+}
+
+/// End of `offset..offset + size`, rejecting ranges that overflow or run past
+/// `file_size`. Header values come from untrusted files, so never add them raw.
+fn checked_end(offset: usize, size: usize, file_size: usize) -> ErfResult<usize> {
+    match offset.checked_add(size) {
+        Some(end) if end <= file_size => Ok(end),
+        end => Err(ErfError::InvalidOffset {
+            offset: end.unwrap_or(usize::MAX),
+            file_size,
+        }),
+    }
+}
+
+/// Byte size of a table of `count` entries of `entry_size` bytes, rejecting
+/// a header-supplied count large enough to overflow.
+fn checked_table_size(count: usize, entry_size: usize, file_size: usize) -> ErfResult<usize> {
+    count
+        .checked_mul(entry_size)
+        .ok_or(ErfError::InvalidOffset {
+            offset: usize::MAX,
+            file_size,
+        })
+}
+
+/// Resolve the archive's `description_str_ref` against the dialog TLK.
+/// Returns `None` for the unset sentinel or a StrRef the TLK can't resolve.
+pub fn erf_description(erf: &ErfParser, tlk: &mut TLKParser) -> Option<String> {
+    let str_ref = erf.header.as_ref()?.description_str_ref;
+    if str_ref == 0xFFFF_FFFF {
+        return None;
+    }
+    tlk.get_string(str_ref as usize).ok().flatten()
+}
@@ -1,3 +1,5 @@
+use super::error::ErfResult;
+use super::parser::resource_key;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -57,11 +59,17 @@ pub enum ErfType {
 }
 
 impl ErfType {
+    /// Trailing spaces and NULs are ignored, so `HAK\0` and `HAK ` both
+    /// match.
     pub fn from_signature(sig: &[u8; 4]) -> Option<Self> {
-        match sig {
-            b"ERF " => Some(ErfType::ERF),
-            b"HAK " => Some(ErfType::HAK),
-            b"MOD " => Some(ErfType::MOD),
+        let end = sig
+            .iter()
+            .rposition(|&b| b != b' ' && b != 0)
+            .map_or(0, |last| last + 1);
+        match &sig[..end] {
+            b"ERF" => Some(ErfType::ERF),
+            b"HAK" => Some(ErfType::HAK),
+            b"MOD" => Some(ErfType::MOD),
             _ => None,
         }
     }
@@ -129,6 +137,45 @@ pub struct ErfResource {
     pub data: Option<Vec<u8>>, // Lazy-loaded
 }
 
+/// One `ErfParser::list_resources_detailed` row.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ErfResourceInfo {
+    pub name: String,
+    pub size: u32,
+    pub resource_type: u16,
+    /// `resource_type_name` of `resource_type`
+    pub type_name: &'static str,
+}
+
+/// A header problem tolerated by a parser built with
+/// `with_lenient_signature` instead of failing the parse.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ErfWarning {
+    UnknownSignature { found: String },
+}
+
+/// A structural problem found by `ErfParser::validate`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ErfIntegrityIssue {
+    ResourceOutOfBounds {
+        name: String,
+        offset: u32,
+        size: u32,
+        file_size: usize,
+    },
+    OverlappingResources {
+        first: String,
+        second: String,
+    },
+    EntryCountMismatch {
+        header: u32,
+        actual: usize,
+    },
+    DuplicateName {
+        name: String,
+    },
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ErfStatistics {
     pub total_resources: usize,
@@ -286,6 +333,71 @@ pub fn resource_type_to_extension(resource_type: u16) -> &'static str {
     }
 }
 
+/// Display name for the common NWN2 resource types, e.g. 2017 is `"2DA"`.
+/// Unlisted codes give `"unknown"`.
+pub fn resource_type_name(resource_type: u16) -> &'static str {
+    match resource_type {
+        1 => "BMP",
+        3 => "TGA",
+        4 => "WAV",
+        6 => "PLT",
+        7 => "INI",
+        10 => "TXT",
+        2002 => "MDL",
+        2009 => "NSS",
+        2010 => "NCS",
+        2011 => "MOD",
+        2012 => "ARE",
+        2013 => "SET",
+        2014 => "IFO",
+        2015 => "BIC",
+        2016 => "WOK",
+        2017 => "2DA",
+        2018 => "TLK",
+        2022 => "TXI",
+        2023 => "GIT",
+        2025 => "UTI",
+        2027 => "UTC",
+        2029 => "DLG",
+        2030 => "ITP",
+        2032 => "UTT",
+        2033 => "DDS",
+        2035 => "UTS",
+        2036 => "LTR",
+        2037 => "GFF",
+        2038 => "FAC",
+        2040 => "UTE",
+        2042 => "UTD",
+        2044 => "UTP",
+        2047 => "GUI",
+        2051 => "UTM",
+        2056 => "JRL",
+        2057 => "SAV",
+        2058 => "UTW",
+        2060 => "SSF",
+        2061 => "HAK",
+        2064 => "NDB",
+        2073 => "ULT",
+        2074 => "SEF",
+        2075 => "PFX",
+        2076 => "CAM",
+        2078 => "BFX",
+        2079 => "UPE",
+        2080 => "ROS",
+        2081 => "RST",
+        2082 => "IFX",
+        2089 => "XML",
+        3005 => "MDB",
+        3006 => "MDA",
+        3007 => "SPT",
+        3008 => "GR2",
+        3011 => "JPG",
+        3016 => "OGG",
+        3020 => "WDB",
+        _ => "unknown",
+    }
+}
+
 pub fn extension_to_resource_type(ext: &str) -> Option<u16> {
     let ext_lower = ext.to_lowercase();
     match ext_lower.as_str() {
@@ -353,12 +465,7 @@ impl ErfBuilder {
     }
 
     pub fn add_resource(mut self, name: &str, data: Vec<u8>) -> Self {
-        let resource_type = if let Some(dot_pos) = name.rfind('.') {
-            let ext = &name[dot_pos + 1..];
-            extension_to_resource_type(ext).unwrap_or(2037)
-        } else {
-            2037
-        };
+        let resource_type = builder_resource_type(name);
         self.resources.push((name.to_string(), resource_type, data));
         self
     }
@@ -368,10 +475,39 @@ impl ErfBuilder {
         self
     }
 
+    /// Whether a resource already added would collide with `name` in the
+    /// built archive. Names compare case-insensitively, and a name without
+    /// an extension is typed the way `add_resource` would type it.
+    pub fn has_resource(&self, name: &str) -> bool {
+        let key = resource_key(name, builder_resource_type(name));
+        self.resources
+            .iter()
+            .any(|(existing, resource_type, _)| resource_key(existing, *resource_type) == key)
+    }
+
+    /// Like `build`, but stops at the first resource the archive rejects,
+    /// such as a second name that differs from an earlier one only in case.
+    pub fn try_build(self) -> ErfResult<super::parser::ErfParser> {
+        let (mut parser, resources) = self.into_parts();
+        for (name, resource_type, data) in resources {
+            parser.add_resource(&name, resource_type, data)?;
+        }
+        Ok(parser)
+    }
+
+    /// Build the archive, skipping resources it rejects: a name that
+    /// collides with an earlier one keeps the first resource. Use `try_build`
+    /// to have collisions reported instead.
     pub fn build(self) -> super::parser::ErfParser {
-        use super::parser::ErfParser;
+        let (mut parser, resources) = self.into_parts();
+        for (name, resource_type, data) in resources {
+            let _ = parser.add_resource(&name, resource_type, data);
+        }
+        parser
+    }
 
-        let mut parser = ErfParser::new_archive(self.erf_type, self.version);
+    fn into_parts(self) -> (super::parser::ErfParser, Vec<(String, u16, Vec<u8>)>) {
+        let mut parser = super::parser::ErfParser::new_archive(self.erf_type, self.version);
 
         if let Some(header) = &mut parser.header {
             header.build_year = self.build_year;
@@ -379,10 +515,12 @@ impl ErfBuilder {
             header.description_str_ref = self.description_str_ref;
         }
 
-        for (name, resource_type, data) in self.resources {
-            let _ = parser.add_resource(&name, resource_type, data);
-        }
-
-        parser
+        (parser, self.resources)
     }
 }
+
+fn builder_resource_type(name: &str) -> u16 {
+    name.rfind('.')
+        .and_then(|dot_pos| extension_to_resource_type(&name[dot_pos + 1..]))
+        .unwrap_or(2037)
+}
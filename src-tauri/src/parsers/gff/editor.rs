@@ -0,0 +1,130 @@
+use std::sync::Arc;
+
+use indexmap::IndexMap;
+
+use super::error::GffError;
+use super::helpers::replace_value;
+use super::parser::GffParser;
+use super::types::GffValue;
+
+/// Overlay of field edits on top of a parsed GFF, for "read, change a few
+/// fields, write" workflows. Edits are only recorded until `build()`, which
+/// applies them in order, so a later edit to the same path wins. Only the
+/// structs along edited paths are read; everything else stays lazy until
+/// `GffWriter` encodes it.
+///
+/// Each edit must keep the field's existing type; with `coerce`, integer
+/// values are converted to the field's existing width instead (see
+/// `replace_value`).
+pub struct GffEditor {
+    parser: Arc<GffParser>,
+    edits: Vec<(String, GffValue<'static>)>,
+    coerce: bool,
+}
+
+impl GffEditor {
+    pub fn new(parser: Arc<GffParser>) -> Self {
+        Self {
+            parser,
+            edits: Vec::new(),
+            coerce: false,
+        }
+    }
+
+    /// Convert integer edits to the existing field width rather than
+    /// rejecting a type change.
+    #[must_use]
+    pub fn coerce(mut self, coerce: bool) -> Self {
+        self.coerce = coerce;
+        self
+    }
+
+    /// Record a replacement for the field at a `get_value`-style path. The
+    /// path and type are checked when the edits are applied by `build()`.
+    pub fn set(&mut self, path: impl Into<String>, value: GffValue<'static>) -> &mut Self {
+        self.edits.push((path.into(), value));
+        self
+    }
+
+    /// Edits recorded so far, in the order they will be applied.
+    pub fn edits(&self) -> &[(String, GffValue<'static>)] {
+        &self.edits
+    }
+
+    pub fn parser(&self) -> &Arc<GffParser> {
+        &self.parser
+    }
+
+    /// The merged value tree, struct IDs included, ready for `GffWriter`.
+    /// Structs and lists off the edited paths are left as lazy values.
+    pub fn build(&self) -> Result<IndexMap<String, GffValue<'static>>, GffError> {
+        let mut tree = read_shallow(&self.parser, 0, self.parser.get_struct_id(0)?)?;
+        for (path, value) in &self.edits {
+            open_path(&mut tree, path)?;
+            replace_value(&mut tree, path, value.clone(), self.coerce)?;
+        }
+        Ok(tree)
+    }
+}
+
+/// One struct's fields plus its `__struct_id__`, with nested structs and
+/// lists left lazy.
+fn read_shallow(
+    parser: &Arc<GffParser>,
+    struct_index: u32,
+    struct_id: u32,
+) -> Result<IndexMap<String, GffValue<'static>>, GffError> {
+    let mut fields: IndexMap<String, GffValue<'static>> = parser
+        .read_struct_fields(struct_index)?
+        .into_iter()
+        .map(|(label, value)| (label, value.into_owned()))
+        .collect();
+    fields.insert("__struct_id__".to_string(), GffValue::Dword(struct_id));
+    Ok(fields)
+}
+
+/// Replace the lazy structs and lists leading to the last segment of `path`
+/// with owned, shallowly read copies so `replace_value` can reach it. Stops
+/// quietly where the path stops resolving; `replace_value` reports that.
+fn open_path(tree: &mut IndexMap<String, GffValue<'static>>, path: &str) -> Result<(), GffError> {
+    let mut parts: Vec<&str> = path.split('/').collect();
+    parts.pop();
+
+    let mut current = tree;
+    let mut parts = parts.into_iter();
+    while let Some(part) = parts.next() {
+        let Some(slot) = current.get_mut(part) else {
+            return Ok(());
+        };
+        match slot {
+            GffValue::Struct(lazy) => {
+                let fields = read_shallow(&lazy.parser, lazy.struct_index, lazy.struct_id)?;
+                *slot = GffValue::StructOwned(Box::new(fields));
+            }
+            GffValue::List(items) => {
+                let items = items
+                    .iter()
+                    .map(|lazy| read_shallow(&lazy.parser, lazy.struct_index, lazy.struct_id))
+                    .collect::<Result<_, _>>()?;
+                *slot = GffValue::ListOwned(items);
+            }
+            _ => {}
+        }
+
+        current = match slot {
+            GffValue::StructOwned(map) => map.as_mut(),
+            GffValue::ListOwned(items) => {
+                let item = parts
+                    .next()
+                    .and_then(|idx| idx.parse::<usize>().ok())
+                    .and_then(|idx| items.get_mut(idx));
+                match item {
+                    Some(item) => item,
+                    None => return Ok(()),
+                }
+            }
+            _ => return Ok(()),
+        };
+    }
+    Ok(())
+}
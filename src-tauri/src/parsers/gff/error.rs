@@ -1,3 +1,4 @@
+use super::validator::ValidationIssue;
 use crate::define_parser_error;
 
 define_parser_error! {
@@ -10,5 +11,10 @@ define_parser_error! {
         FieldNotFound(String) => "Field not found: {0}",
         UnsupportedFieldType(u32) => "Unsupported field type: {0}",
         BufferOverflow(String) => "Buffer overflow: {0}",
+        ValueOutOfRange(String) => "Value out of range: {0}",
+        TypeMismatch(String) => "Type mismatch: {0}",
+        StructCycle(u32) => "Struct {0} is nested inside itself",
+        NestingTooDeep(usize) => "Structs nested deeper than {0} levels",
+        ValidationFailed(Vec<ValidationIssue>) => "Required fields failed validation: {0:?}",
     }
 }
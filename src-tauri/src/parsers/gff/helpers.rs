@@ -5,7 +5,7 @@
 //! helpers read the existing type for `key` and emit the same variant when
 //! writing, falling back to a sensible default for new fields.
 
-use super::GffValue;
+use super::{GffError, GffFieldType, GffValue};
 use indexmap::IndexMap;
 
 /// Insert a signed-integer value, preserving the existing GFF type variant.
@@ -64,6 +64,132 @@ pub fn insert_bool_preserving_type(
     fields.insert(key.to_string(), new_value);
 }
 
+/// Build an integer value stored at exactly the width of GFF type `type_id`
+/// (0 = Byte .. 7 = Int64). Errors for non-integer types or values that
+/// don't fit.
+pub fn integer_with_type(value: i64, type_id: u32) -> Result<GffValue<'static>, GffError> {
+    let out_of_range =
+        || GffError::ValueOutOfRange(format!("{value} does not fit in GFF type {type_id}"));
+    let field_type =
+        GffFieldType::from_id(type_id).ok_or(GffError::UnsupportedFieldType(type_id))?;
+
+    Ok(match field_type {
+        GffFieldType::Byte => GffValue::Byte(u8::try_from(value).map_err(|_| out_of_range())?),
+        GffFieldType::Char => {
+            let byte = u8::try_from(value).map_err(|_| out_of_range())?;
+            GffValue::Char(char::from(byte))
+        }
+        GffFieldType::Word => GffValue::Word(u16::try_from(value).map_err(|_| out_of_range())?),
+        GffFieldType::Short => GffValue::Short(i16::try_from(value).map_err(|_| out_of_range())?),
+        GffFieldType::Dword => GffValue::Dword(u32::try_from(value).map_err(|_| out_of_range())?),
+        GffFieldType::Int => GffValue::Int(i32::try_from(value).map_err(|_| out_of_range())?),
+        GffFieldType::Dword64 => {
+            GffValue::Dword64(u64::try_from(value).map_err(|_| out_of_range())?)
+        }
+        GffFieldType::Int64 => GffValue::Int64(value),
+        _ => return Err(GffError::UnsupportedFieldType(type_id)),
+    })
+}
+
+/// Set an integer field at a slash path (`"Equip_ItemList/0/StackSize"`) in an
+/// owned document, pinned to GFF type `type_id` regardless of what was there.
+/// Intermediate structs/list entries must already exist.
+pub fn set_typed(
+    fields: &mut IndexMap<String, GffValue<'static>>,
+    path: &str,
+    value: i64,
+    type_id: u32,
+) -> Result<(), GffError> {
+    let new_value = integer_with_type(value, type_id)?;
+    let (parent, label) = parent_struct_mut(fields, path)?;
+    parent.insert(label.to_string(), new_value);
+    Ok(())
+}
+
+/// Replace the existing field at a slash path with `value`. Type changes are
+/// rejected with `TypeMismatch`; with `coerce`, an integer value is instead
+/// converted to the field's existing integer width.
+pub fn replace_value(
+    fields: &mut IndexMap<String, GffValue<'static>>,
+    path: &str,
+    value: GffValue<'static>,
+    coerce: bool,
+) -> Result<(), GffError> {
+    let (parent, label) = parent_struct_mut(fields, path)?;
+    let slot = parent
+        .get_mut(label)
+        .ok_or_else(|| GffError::FieldNotFound(path.to_string()))?;
+
+    let expected = slot.field_type();
+    let found = value.field_type();
+    *slot = if expected == found {
+        value
+    } else if coerce
+        && integer_value(slot).is_some()
+        && let Some(int) = integer_value(&value)
+    {
+        integer_with_type(int, expected as u32)?
+    } else {
+        return Err(GffError::TypeMismatch(format!(
+            "{path} is {expected:?}, not {found:?}"
+        )));
+    };
+    Ok(())
+}
+
+/// Walk all but the last segment of a slash path, returning the struct that
+/// holds the final label. Intermediate structs/list entries must exist.
+fn parent_struct_mut<'a, 'p>(
+    fields: &'a mut IndexMap<String, GffValue<'static>>,
+    path: &'p str,
+) -> Result<(&'a mut IndexMap<String, GffValue<'static>>, &'p str), GffError> {
+    let mut parts: Vec<&str> = path.split('/').collect();
+    let Some(label) = parts.pop().filter(|l| !l.is_empty()) else {
+        return Err(GffError::FieldNotFound("(empty path)".into()));
+    };
+
+    let mut current = fields;
+    let mut parts = parts.into_iter();
+    while let Some(part) = parts.next() {
+        current = match current.get_mut(part) {
+            Some(GffValue::StructOwned(map)) => map.as_mut(),
+            Some(GffValue::ListOwned(items)) => {
+                let idx = parts.next().ok_or_else(|| {
+                    GffError::FieldNotFound(format!("Missing list index after: {part}"))
+                })?;
+                let idx: usize = idx
+                    .parse()
+                    .map_err(|_| GffError::FieldNotFound(format!("Invalid list index: {idx}")))?;
+                items.get_mut(idx).ok_or_else(|| {
+                    GffError::FieldNotFound(format!("List index out of bounds: {idx}"))
+                })?
+            }
+            Some(_) => {
+                return Err(GffError::FieldNotFound(format!(
+                    "Cannot traverse into non-structural field: {part}"
+                )));
+            }
+            None => return Err(GffError::FieldNotFound(part.to_string())),
+        };
+    }
+
+    Ok((current, label))
+}
+
+fn integer_value(v: &GffValue<'_>) -> Option<i64> {
+    match *v {
+        GffValue::Byte(n) => Some(i64::from(n)),
+        GffValue::Char(c) => Some(i64::from(c as u32)),
+        GffValue::Word(n) => Some(i64::from(n)),
+        GffValue::Short(n) => Some(i64::from(n)),
+        GffValue::Dword(n) => Some(i64::from(n)),
+        GffValue::Int(n) => Some(i64::from(n)),
+        GffValue::Dword64(n) => i64::try_from(n).ok(),
+        GffValue::Int64(n) => Some(n),
+        _ => None,
+    }
+}
+
 /// Name of the `GffValue` variant for diagnostics and assertions.
 pub fn variant_name(v: &GffValue<'_>) -> &'static str {
     match v {
@@ -86,6 +212,33 @@ pub fn variant_name(v: &GffValue<'_>) -> &'static str {
     }
 }
 
+/// Longest ResRef the game accepts, in characters.
+pub const RESREF_MAX_LEN: usize = 32;
+
+/// Lowercase a ResRef and cut it to `RESREF_MAX_LEN` characters, the form
+/// the game expects. With `strict`, non-ASCII characters are an error instead
+/// of being passed through to the Windows-1252 encoder.
+pub fn normalize_resref(resref: &str, strict: bool) -> Result<String, GffError> {
+    if strict {
+        require_ascii_resref(resref)?;
+    }
+    Ok(resref
+        .chars()
+        .take(RESREF_MAX_LEN)
+        .collect::<String>()
+        .to_lowercase())
+}
+
+pub(crate) fn require_ascii_resref(resref: &str) -> Result<(), GffError> {
+    if resref.is_ascii() {
+        Ok(())
+    } else {
+        Err(GffError::ValueOutOfRange(format!(
+            "ResRef {resref:?} contains non-ASCII characters"
+        )))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -157,4 +310,31 @@ mod tests {
         insert_i32_preserving_type(&mut f, "AsString", 9);
         assert!(matches!(f.get("AsString"), Some(GffValue::Int(9))));
     }
+
+    #[test]
+    fn set_typed_pins_word_width_through_write() {
+        use crate::parsers::gff::{GffParser, GffWriter};
+
+        let mut item: IndexMap<String, GffValue<'static>> = IndexMap::new();
+        item.insert("StackSize".to_string(), GffValue::Int(1));
+        let mut f = make_fields();
+        f.insert("ItemList".to_string(), GffValue::ListOwned(vec![item]));
+
+        set_typed(&mut f, "AsInt", 5, GffFieldType::Word as u32).unwrap();
+        set_typed(&mut f, "ItemList/0/StackSize", 10, 2).unwrap();
+        assert!(set_typed(&mut f, "AsByte", 70_000, 2).is_err());
+        assert!(set_typed(&mut f, "ItemList/3/StackSize", 1, 2).is_err());
+        assert!(set_typed(&mut f, "AsWord", 1, 10).is_err());
+
+        let bytes = GffWriter::new("BIC ", "V3.2").write(f).expect("write");
+        let parser = GffParser::from_bytes(bytes).expect("parse");
+        let root = parser.read_struct_fields(0).expect("root");
+
+        let as_int = root.get("AsInt").unwrap();
+        assert_eq!(as_int.field_type() as u32, 2);
+        assert!(matches!(as_int, GffValue::Word(5)));
+
+        let stack = parser.get_value("ItemList/0/StackSize").expect("nested");
+        assert!(matches!(stack, GffValue::Word(10)));
+    }
 }
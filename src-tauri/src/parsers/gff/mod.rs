@@ -1,16 +1,23 @@
+pub mod editor;
 pub mod error;
 pub mod helpers;
 mod merge;
 pub mod parser;
 pub mod types;
+pub mod validator;
 pub mod writer;
 
+pub use editor::GffEditor;
 pub use error::GffError;
 pub use helpers::{
-    insert_bool_preserving_type, insert_i32_preserving_type, insert_u32_preserving_type,
+    RESREF_MAX_LEN, insert_bool_preserving_type, insert_i32_preserving_type,
+    insert_u32_preserving_type, integer_with_type, normalize_resref, replace_value, set_typed,
     variant_name,
 };
 pub use merge::merge_fields_into_gff;
-pub use parser::GffParser;
-pub use types::{GffFieldType, GffValue, LazyStruct, LocalizedString, LocalizedSubstring};
+pub use parser::{BatchLoadResult, GffParser, load_multiple_files};
+pub use types::{
+    GffFieldType, GffValue, GffWarning, LazyStruct, LocalizedString, LocalizedSubstring,
+};
+pub use validator::{GffValidator, ValidationIssue};
 pub use writer::GffWriter;
@@ -1,22 +1,33 @@
 use std::borrow::Cow;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fs::File;
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
 
 use byteorder::{ByteOrder, LittleEndian};
-use encoding_rs::WINDOWS_1252;
+use encoding_rs::{Encoding, WINDOWS_1252};
 use indexmap::IndexMap;
 use memmap2::Mmap;
 use tracing::{debug, instrument, trace, warn};
 
 use super::error::GffError;
-use super::types::{GffValue, LazyStruct, LocalizedString, LocalizedSubstring};
+use super::helpers::replace_value;
+use super::types::{GffValue, GffWarning, LazyStruct, LocalizedString, LocalizedSubstring};
+use crate::parsers::batch;
+use crate::parsers::tlk::TLKParser;
 
 const HEADER_SIZE: usize = 56;
 const LABEL_SIZE: usize = 16;
 const FIELD_SIZE: usize = 12;
 const STRUCT_SIZE: usize = 12;
 
+/// Default nesting cap for `GffParser::find_structs_by_id`.
+pub const DEFAULT_SEARCH_DEPTH: usize = 64;
+
+/// Deepest struct nesting `GffParser::to_value_tree` materializes.
+pub const MAX_TREE_DEPTH: usize = 256;
+
 enum DataSource {
     Mmap(Mmap),
     Bytes(Vec<u8>),
@@ -66,11 +77,25 @@ pub struct GffParser {
     _field_indices_len: u32,
     list_indices_offset: usize,
     list_indices_len: u32,
+
+    skip_unknown_fields: AtomicBool,
+    warnings: RwLock<Vec<GffWarning>>,
+    encoding: &'static Encoding,
 }
 
 impl GffParser {
-    #[instrument(name = "GffParser::new", skip_all, fields(path = ?path.as_ref()))]
     pub fn new<P: AsRef<Path>>(path: P) -> Result<Arc<Self>, GffError> {
+        Self::new_with_encoding(path, WINDOWS_1252)
+    }
+
+    /// Like `new`, decoding String, ResRef and LocString content with
+    /// `encoding` instead of the game's Windows-1252 (e.g. `UTF_8` for files
+    /// authored by community tools). `GffWriter` still encodes Windows-1252.
+    #[instrument(name = "GffParser::new", skip_all, fields(path = ?path.as_ref()))]
+    pub fn new_with_encoding<P: AsRef<Path>>(
+        path: P,
+        encoding: &'static Encoding,
+    ) -> Result<Arc<Self>, GffError> {
         trace!("Opening GFF file");
         let file = File::open(path)?;
         let mmap = unsafe { Mmap::map(&file)? };
@@ -79,7 +104,7 @@ impl GffParser {
 
         let data = Arc::new(DataSource::Mmap(mmap));
 
-        let parser = Self::parse_header(data)?;
+        let parser = Self::parse_header(data, encoding)?;
         debug!(
             "GFF file parsed: type={}, version={}, structs={}, fields={}",
             parser.file_type, parser.file_version, parser.struct_count, parser.field_count
@@ -88,12 +113,20 @@ impl GffParser {
     }
 
     pub fn from_bytes(bytes: Vec<u8>) -> Result<Arc<Self>, GffError> {
+        Self::from_bytes_with_encoding(bytes, WINDOWS_1252)
+    }
+
+    /// `from_bytes` with a string content encoding; see `new_with_encoding`.
+    pub fn from_bytes_with_encoding(
+        bytes: Vec<u8>,
+        encoding: &'static Encoding,
+    ) -> Result<Arc<Self>, GffError> {
         let data = Arc::new(DataSource::Bytes(bytes));
-        let parser = Self::parse_header(data)?;
+        let parser = Self::parse_header(data, encoding)?;
         Ok(Arc::new(parser))
     }
 
-    fn parse_header(data: Arc<DataSource>) -> Result<Self, GffError> {
+    fn parse_header(data: Arc<DataSource>, encoding: &'static Encoding) -> Result<Self, GffError> {
         let slice = data.as_slice();
         if data.len() < HEADER_SIZE {
             return Err(GffError::InvalidHeader("File too small".to_string()));
@@ -112,6 +145,51 @@ impl GffParser {
         let list_indices_offset = LittleEndian::read_u32(&slice[48..52]) as usize;
         let list_indices_len = LittleEndian::read_u32(&slice[52..56]);
 
+        let file_len = data.len();
+        let sections = [
+            (
+                "Struct array",
+                struct_offset,
+                struct_count as usize,
+                STRUCT_SIZE,
+            ),
+            (
+                "Field array",
+                field_offset,
+                field_count as usize,
+                FIELD_SIZE,
+            ),
+            (
+                "Label array",
+                label_offset,
+                label_count as usize,
+                LABEL_SIZE,
+            ),
+            ("Field data", field_data_offset, field_data_len as usize, 1),
+            (
+                "Field indices",
+                field_indices_offset,
+                field_indices_len as usize,
+                1,
+            ),
+            (
+                "List indices",
+                list_indices_offset,
+                list_indices_len as usize,
+                1,
+            ),
+        ];
+        for (name, offset, count, entry_size) in sections {
+            let end = count
+                .checked_mul(entry_size)
+                .and_then(|size| offset.checked_add(size));
+            if end.is_none_or(|end| end > file_len) {
+                return Err(GffError::InvalidHeader(format!(
+                    "{name} at offset {offset} with {count} x {entry_size} bytes exceeds file size {file_len}"
+                )));
+            }
+        }
+
         let file_type_bytes = &slice[0..4];
         let file_ver_bytes = &slice[4..8];
         let file_type = String::from_utf8_lossy(file_type_bytes).to_string();
@@ -133,6 +211,9 @@ impl GffParser {
             _field_indices_len: field_indices_len,
             list_indices_offset,
             list_indices_len,
+            skip_unknown_fields: AtomicBool::new(false),
+            warnings: RwLock::new(Vec::new()),
+            encoding,
         })
     }
 
@@ -193,7 +274,7 @@ impl GffParser {
         Ok(map)
     }
 
-    fn read_field<'a>(
+    pub(crate) fn read_field<'a>(
         self: &Arc<Self>,
         field_index: u32,
     ) -> Result<(String, GffValue<'a>), GffError> {
@@ -230,12 +311,55 @@ impl GffParser {
             13 => GffValue::Void(self.read_void(data_or_offset)?),
             14 => GffValue::Struct(self.create_lazy_struct(data_or_offset)?),
             15 => GffValue::List(self.read_list(data_or_offset)?),
+            _ if self.skips_unknown_fields() => {
+                self.record_unknown_field(field_index, &label, field_type_u32);
+                GffValue::Void(Cow::Owned(Vec::new()))
+            }
             _ => return Err(GffError::UnsupportedFieldType(field_type_u32)),
         };
 
         Ok((label, value))
     }
 
+    /// Lenient mode: fields with a type id outside 0-15 read as an empty
+    /// `Void` placeholder and are reported by `warnings()` instead of failing
+    /// the whole struct. Off by default.
+    pub fn set_skip_unknown_fields(&self, skip: bool) {
+        self.skip_unknown_fields.store(skip, Ordering::Relaxed);
+    }
+
+    pub fn skips_unknown_fields(&self) -> bool {
+        self.skip_unknown_fields.load(Ordering::Relaxed)
+    }
+
+    /// The encoding String, ResRef and LocString content is decoded with.
+    pub fn encoding(&self) -> &'static Encoding {
+        self.encoding
+    }
+
+    /// Unknown fields skipped so far in lenient mode, one per field entry.
+    pub fn warnings(&self) -> Vec<GffWarning> {
+        self.warnings
+            .read()
+            .map(|warnings| warnings.clone())
+            .unwrap_or_default()
+    }
+
+    fn record_unknown_field(&self, field_index: u32, label: &str, field_type: u32) {
+        let Ok(mut warnings) = self.warnings.write() else {
+            return;
+        };
+        if warnings.iter().any(|w| w.field_index == field_index) {
+            return;
+        }
+        warn!("Skipping field '{label}' with unknown GFF type {field_type}");
+        warnings.push(GffWarning {
+            field_index,
+            label: label.to_string(),
+            field_type,
+        });
+    }
+
     fn get_data_slice(&self, offset: u32, len: usize) -> Result<&[u8], GffError> {
         let start = self.field_data_offset + offset as usize;
         let end = start + len;
@@ -264,16 +388,19 @@ impl GffParser {
         let len_slice = self.get_data_slice(offset, 4)?;
         let len = LittleEndian::read_u32(len_slice) as usize;
         let str_slice = self.get_data_slice(offset + 4, len)?;
-        let (cow, _, _) = WINDOWS_1252.decode(str_slice);
-        Ok(Cow::Owned(cow.into_owned()))
+        Ok(self.decode_text(str_slice))
     }
 
     fn read_resref<'a>(&self, offset: u32) -> Result<Cow<'a, str>, GffError> {
         let len_slice = self.get_data_slice(offset, 1)?;
         let len = len_slice[0] as usize;
         let str_slice = self.get_data_slice(offset + 1, len)?;
-        let (cow, _, _) = WINDOWS_1252.decode(str_slice);
-        Ok(Cow::Owned(cow.into_owned()))
+        Ok(self.decode_text(str_slice))
+    }
+
+    fn decode_text<'a>(&self, bytes: &[u8]) -> Cow<'a, str> {
+        let (cow, _, _) = self.encoding.decode(bytes);
+        Cow::Owned(cow.into_owned())
     }
 
     fn read_void<'a>(&self, offset: u32) -> Result<Cow<'a, [u8]>, GffError> {
@@ -297,10 +424,8 @@ impl GffParser {
             let len = LittleEndian::read_u32(&sub_header[4..8]);
 
             let str_slice = self.get_data_slice(current_offset + 8, len as usize)?;
-            let (cow, _, _) = WINDOWS_1252.decode(str_slice);
-
             substrings.push(LocalizedSubstring {
-                string: Cow::Owned(cow.into_owned()),
+                string: self.decode_text(str_slice),
                 language: id / 2,
                 gender: id % 2,
             });
@@ -361,37 +486,67 @@ impl GffParser {
     }
 
     pub fn get_value<'a>(self: &Arc<Self>, path: &str) -> Result<GffValue<'a>, GffError> {
-        let parts: Vec<&str> = path.split('/').collect();
-        if parts.is_empty() {
-            return Err(GffError::FieldNotFound("(empty path)".into()));
+        let mut current = None;
+        for part in path.split('/') {
+            current = Some(self.step_into(current, part)?);
         }
-        let mut current_value = self.read_field_by_label(0, parts[0])?;
+        current.ok_or_else(|| GffError::FieldNotFound("(empty path)".into()))
+    }
 
-        for part in &parts[1..] {
-            match current_value {
-                GffValue::Struct(lazy) => {
-                    current_value = self.read_field_by_label(lazy.struct_index, part)?;
-                }
-                GffValue::List(list) => {
-                    let idx: usize = part.parse().map_err(|_| {
-                        GffError::FieldNotFound(format!("Invalid list index: {part}"))
-                    })?;
-                    if idx >= list.len() {
-                        return Err(GffError::FieldNotFound(format!(
-                            "List index out of bounds: {idx}"
-                        )));
+    /// Resolve several `get_value`-style paths at once. Structs and lists
+    /// reached along the way are remembered, so sibling paths such as
+    /// `CombatInfo/ArmorClass` and `CombatInfo/BaseAttackBonus` walk the
+    /// shared prefix only once. Paths that fail to resolve yield `None`.
+    pub fn get_fields<'a>(self: &Arc<Self>, paths: &[&str]) -> Vec<Option<GffValue<'a>>> {
+        let mut containers: HashMap<&str, GffValue<'a>> = HashMap::new();
+
+        paths
+            .iter()
+            .map(|path| {
+                let mut current = None;
+                let mut start = 0;
+                // Intermediate segments: reuse or remember the container
+                for (end, _) in path.match_indices('/') {
+                    let prefix = &path[..end];
+                    if let Some(value) = containers.get(prefix) {
+                        current = Some(value.clone());
+                    } else {
+                        let value = self.step_into(current, &path[start..end]).ok()?;
+                        containers.insert(prefix, value.clone());
+                        current = Some(value);
                     }
-                    current_value = GffValue::Struct(list[idx].clone());
+                    start = end + 1;
                 }
-                _ => {
+                self.step_into(current, &path[start..]).ok()
+            })
+            .collect()
+    }
+
+    /// One `get_value` path segment: a field of the root (`None`) or of a
+    /// struct, or an index into a list.
+    fn step_into<'a>(
+        self: &Arc<Self>,
+        current: Option<GffValue<'a>>,
+        part: &str,
+    ) -> Result<GffValue<'a>, GffError> {
+        match current {
+            None => self.read_field_by_label(0, part),
+            Some(GffValue::Struct(lazy)) => self.read_field_by_label(lazy.struct_index, part),
+            Some(GffValue::List(list)) => {
+                let idx: usize = part
+                    .parse()
+                    .map_err(|_| GffError::FieldNotFound(format!("Invalid list index: {part}")))?;
+                if idx >= list.len() {
                     return Err(GffError::FieldNotFound(format!(
-                        "Cannot traverse into non-structural field: {part}"
+                        "List index out of bounds: {idx}"
                     )));
                 }
+                Ok(GffValue::Struct(list[idx].clone()))
             }
+            Some(_) => Err(GffError::FieldNotFound(format!(
+                "Cannot traverse into non-structural field: {part}"
+            ))),
         }
-
-        Ok(current_value)
     }
 
     pub fn read_field_by_label<'a>(
@@ -433,4 +588,420 @@ impl GffParser {
 
         Err(GffError::FieldNotFound(label_to_find.to_string()))
     }
+
+    /// Fully materialize the root struct as an owned tree `GffWriter` accepts.
+    /// Every struct, the root included, carries its `__struct_id__` so a
+    /// write preserves struct IDs alongside the field types. Any struct that
+    /// fails to read fails the whole call rather than coming back empty.
+    pub fn to_value_tree(
+        self: &Arc<Self>,
+    ) -> Result<IndexMap<String, GffValue<'static>>, GffError> {
+        self.owned_struct(0, &mut Vec::new())
+    }
+
+    /// Fields of `struct_index` plus its `__struct_id__`, with nested structs
+    /// and lists read the same way. `ancestors` holds the struct indices on
+    /// the path from the root, so a struct nested inside itself is reported
+    /// instead of recursing forever.
+    pub(crate) fn owned_struct(
+        self: &Arc<Self>,
+        struct_index: u32,
+        ancestors: &mut Vec<u32>,
+    ) -> Result<IndexMap<String, GffValue<'static>>, GffError> {
+        if ancestors.contains(&struct_index) {
+            return Err(GffError::StructCycle(struct_index));
+        }
+        if ancestors.len() >= MAX_TREE_DEPTH {
+            return Err(GffError::NestingTooDeep(MAX_TREE_DEPTH));
+        }
+
+        ancestors.push(struct_index);
+        let mut tree = IndexMap::new();
+        for (label, value) in self.read_struct_fields(struct_index)? {
+            let value = match value {
+                GffValue::Struct(lazy) => GffValue::StructOwned(Box::new(
+                    self.owned_struct(lazy.struct_index, ancestors)?,
+                )),
+                GffValue::List(items) => GffValue::ListOwned(
+                    items
+                        .iter()
+                        .map(|lazy| self.owned_struct(lazy.struct_index, ancestors))
+                        .collect::<Result<_, _>>()?,
+                ),
+                other => other.into_owned(),
+            };
+            tree.insert(label, value);
+        }
+        ancestors.pop();
+
+        tree.insert(
+            "__struct_id__".to_string(),
+            GffValue::Dword(self.get_struct_id(struct_index)?),
+        );
+        Ok(tree)
+    }
+
+    /// The whole document as JSON, fields in file order. Shapes follow
+    /// `GffValue`'s `Serialize`: numbers and strings are plain JSON values,
+    /// `Char` is a one-character string, a `LocString` is
+    /// `{"string_ref": i32, "substrings": [{"string", "language", "gender"}]}`,
+    /// `Void` is an array of byte values, structs are objects carrying
+    /// `__struct_id__`, and lists are arrays of those objects.
+    pub fn to_json_string(self: &Arc<Self>) -> Result<String, GffError> {
+        let tree = self.to_value_tree()?;
+        serde_json::to_string(&tree).map_err(|e| GffError::Serialization(e.to_string()))
+    }
+
+    /// Materialize the document with the field at a `get_value`-style path
+    /// replaced, ready for `GffWriter`. The value must keep the field's type
+    /// unless `coerce` is set, which converts integers to the existing width.
+    pub fn set_value(
+        self: &Arc<Self>,
+        path: &str,
+        value: GffValue<'static>,
+        coerce: bool,
+    ) -> Result<IndexMap<String, GffValue<'static>>, GffError> {
+        let mut tree = self.to_value_tree()?;
+        replace_value(&mut tree, path, value, coerce)?;
+        Ok(tree)
+    }
+
+    /// The localized string at a `get_value`-style path.
+    pub fn get_locstring(
+        self: &Arc<Self>,
+        path: &str,
+    ) -> Result<LocalizedString<'static>, GffError> {
+        match self.get_value(path)? {
+            GffValue::LocString(locstring) => Ok(locstring),
+            other => Err(GffError::TypeMismatch(format!(
+                "{path} is {:?}, not LocString",
+                other.field_type()
+            ))),
+        }
+    }
+
+    /// Display text for the localized string at `path`: the substring best
+    /// matching `language`/`gender`, else the `string_ref` resolved through
+    /// `tlk`. `None` if the field is missing or neither source has text.
+    pub fn get_locstring_for(
+        self: &Arc<Self>,
+        path: &str,
+        language: u32,
+        gender: u32,
+        tlk: &mut TLKParser,
+    ) -> Option<String> {
+        let locstring = self.get_locstring(path).ok()?;
+        if let Some(text) = locstring.substring_for(language, gender) {
+            return Some(text.to_string());
+        }
+        let str_ref = usize::try_from(locstring.string_ref).ok()?;
+        tlk.get_string(str_ref).ok().flatten()
+    }
+
+    /// Number of entries in the list at `path`, read from the list header
+    /// without touching its elements.
+    pub fn list_len(&self, path: &str) -> Result<usize, GffError> {
+        match self.locate(path)? {
+            PathTarget::List(list_offset) => {
+                let start = self.list_indices_offset + list_offset as usize;
+                Ok(self.read_u32_at(start, "List count")? as usize)
+            }
+            _ => Err(GffError::FieldNotFound(format!("Not a list: {path}"))),
+        }
+    }
+
+    /// Field labels of the struct at `path` (`""` for the root), in file
+    /// order. Only the field and label arrays are read; no values are decoded.
+    pub fn struct_labels(&self, path: &str) -> Result<Vec<String>, GffError> {
+        match self.locate(path)? {
+            PathTarget::Struct(struct_index) => self
+                .struct_field_indices(struct_index)?
+                .into_iter()
+                .map(|field_index| {
+                    let (_, label_index, _) = self.field_header(field_index)?;
+                    Ok(self.get_label(label_index)?.into_owned())
+                })
+                .collect(),
+            _ => Err(GffError::FieldNotFound(format!("Not a struct: {path}"))),
+        }
+    }
+
+    /// Paths of every struct whose struct ID is `struct_id` (`""` for the
+    /// root), breadth-first. Same as `find_structs_by_id_within` with
+    /// `DEFAULT_SEARCH_DEPTH`.
+    pub fn find_structs_by_id(&self, struct_id: u32) -> Vec<String> {
+        self.find_structs_by_id_within(struct_id, DEFAULT_SEARCH_DEPTH)
+    }
+
+    /// Breadth-first search for structs with `struct_id`, descending at most
+    /// `max_depth` struct/list levels below the root. Only the struct, field,
+    /// and list index arrays are read; unreadable structs are skipped. Each
+    /// struct is visited once, at its first (shallowest) path, so malformed
+    /// files whose structs reference each other cannot loop.
+    pub fn find_structs_by_id_within(&self, struct_id: u32, max_depth: usize) -> Vec<String> {
+        let mut matches = Vec::new();
+        let mut queue = VecDeque::from([(String::new(), 0u32, 0usize)]);
+        let mut visited = HashSet::from([0u32]);
+
+        while let Some((path, struct_index, depth)) = queue.pop_front() {
+            match self.get_struct_id(struct_index) {
+                Ok(id) if id == struct_id => matches.push(path.clone()),
+                Ok(_) => {}
+                Err(e) => {
+                    warn!("Skipping unreadable struct {struct_index} at '{path}': {e}");
+                    continue;
+                }
+            }
+            if depth >= max_depth {
+                continue;
+            }
+
+            if let Err(e) =
+                self.queue_children(&path, struct_index, depth + 1, &mut queue, &mut visited)
+            {
+                warn!("Stopped searching struct {struct_index} at '{path}': {e}");
+            }
+        }
+
+        matches
+    }
+
+    fn queue_children(
+        &self,
+        path: &str,
+        struct_index: u32,
+        depth: usize,
+        queue: &mut VecDeque<(String, u32, usize)>,
+        visited: &mut HashSet<u32>,
+    ) -> Result<(), GffError> {
+        for field_index in self.struct_field_indices(struct_index)? {
+            let (field_type, label_index, data) = self.field_header(field_index)?;
+            if field_type != 14 && field_type != 15 {
+                continue;
+            }
+
+            let label = self.get_label(label_index)?;
+            let child_path = if path.is_empty() {
+                label.into_owned()
+            } else {
+                format!("{path}/{label}")
+            };
+
+            if field_type == 14 {
+                if visited.insert(data) {
+                    queue.push_back((child_path, data, depth));
+                }
+                continue;
+            }
+
+            let start = self.list_indices_offset + data as usize;
+            let count = self.read_u32_at(start, "List count")? as usize;
+            for i in 0..count {
+                let item = self.read_u32_at(start + 4 + i * 4, "List items")?;
+                if visited.insert(item) {
+                    queue.push_back((format!("{child_path}/{i}"), item, depth));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolve a `get_value`-style path using only the struct, field, and
+    /// list index arrays.
+    fn locate(&self, path: &str) -> Result<PathTarget, GffError> {
+        let mut target = PathTarget::Struct(0);
+        for part in path.split('/').filter(|part| !part.is_empty()) {
+            target = match target {
+                PathTarget::Struct(struct_index) => {
+                    let (field_type, data) = self.find_field(struct_index, part)?;
+                    match field_type {
+                        14 => PathTarget::Struct(data),
+                        15 => PathTarget::List(data),
+                        _ => PathTarget::Value,
+                    }
+                }
+                PathTarget::List(list_offset) => {
+                    let idx: usize = part.parse().map_err(|_| {
+                        GffError::FieldNotFound(format!("Invalid list index: {part}"))
+                    })?;
+                    let start = self.list_indices_offset + list_offset as usize;
+                    let count = self.read_u32_at(start, "List count")? as usize;
+                    if idx >= count {
+                        return Err(GffError::FieldNotFound(format!(
+                            "List index out of bounds: {idx}"
+                        )));
+                    }
+                    PathTarget::Struct(self.read_u32_at(start + 4 + idx * 4, "List items")?)
+                }
+                PathTarget::Value => {
+                    return Err(GffError::FieldNotFound(format!(
+                        "Cannot traverse into non-structural field: {part}"
+                    )));
+                }
+            };
+        }
+
+        Ok(target)
+    }
+
+    fn find_field(&self, struct_index: u32, label: &str) -> Result<(u32, u32), GffError> {
+        for field_index in self.struct_field_indices(struct_index)? {
+            let (field_type, label_index, data) = self.field_header(field_index)?;
+            if self.get_label(label_index)? == label {
+                return Ok((field_type, data));
+            }
+        }
+        Err(GffError::FieldNotFound(label.to_string()))
+    }
+
+    /// Entry counts of the struct, field and label arrays.
+    pub(crate) fn table_counts(&self) -> (u32, u32, u32) {
+        (self.struct_count, self.field_count, self.label_count)
+    }
+
+    /// `(id, data_or_index, field_count)` of a struct entry.
+    pub(crate) fn struct_header(&self, struct_index: u32) -> Result<(u32, u32, u32), GffError> {
+        if struct_index >= self.struct_count {
+            return Err(GffError::InvalidStructIndex(struct_index));
+        }
+        let offset = self.struct_offset + (struct_index as usize * STRUCT_SIZE);
+        Ok((
+            self.read_u32_at(offset, "Struct array")?,
+            self.read_u32_at(offset + 4, "Struct array")?,
+            self.read_u32_at(offset + 8, "Struct array")?,
+        ))
+    }
+
+    pub(crate) fn struct_field_indices(&self, struct_index: u32) -> Result<Vec<u32>, GffError> {
+        let (_, field_data_or_index, field_count) = self.struct_header(struct_index)?;
+
+        match field_count {
+            0 => Ok(Vec::new()),
+            1 => Ok(vec![field_data_or_index]),
+            _ => {
+                let indices_offset = self.field_indices_offset + field_data_or_index as usize;
+                (0..field_count as usize)
+                    .map(|i| self.read_u32_at(indices_offset + i * 4, "Field indices"))
+                    .collect()
+            }
+        }
+    }
+
+    /// `(type, label_index, data_or_offset)` of a field entry.
+    pub(crate) fn field_header(&self, field_index: u32) -> Result<(u32, u32, u32), GffError> {
+        if field_index >= self.field_count {
+            return Err(GffError::InvalidFieldIndex(field_index));
+        }
+        let offset = self.field_offset + (field_index as usize * FIELD_SIZE);
+        Ok((
+            self.read_u32_at(offset, "Field array")?,
+            self.read_u32_at(offset + 4, "Field array")?,
+            self.read_u32_at(offset + 8, "Field array")?,
+        ))
+    }
+
+    fn read_u32_at(&self, offset: usize, what: &str) -> Result<u32, GffError> {
+        self.data
+            .as_slice()
+            .get(offset..offset + 4)
+            .map(LittleEndian::read_u32)
+            .ok_or_else(|| GffError::BufferOverflow(what.into()))
+    }
+
+    /// Depth-first walk over every field in the file, paired with its
+    /// `get_value`-style path. List elements are yielded as `Struct` values at
+    /// `List/N` before their own fields. Structs are only read as the walk
+    /// reaches them; unreadable structs are skipped. Each struct's fields are
+    /// walked once, at its first path, so malformed files whose structs
+    /// reference each other cannot loop.
+    pub fn walk(self: &Arc<Self>) -> impl Iterator<Item = (String, GffValue<'static>)> {
+        let mut walk = GffWalk {
+            parser: self.clone(),
+            stack: Vec::new(),
+            visited: HashSet::new(),
+        };
+        walk.push_struct(String::new(), 0);
+        walk
+    }
+}
+
+enum PathTarget {
+    Struct(u32),
+    List(u32),
+    Value,
+}
+
+enum WalkFrame {
+    Fields(String, indexmap::map::IntoIter<String, GffValue<'static>>),
+    List(String, std::iter::Enumerate<std::vec::IntoIter<LazyStruct>>),
+}
+
+struct GffWalk {
+    parser: Arc<GffParser>,
+    stack: Vec<WalkFrame>,
+    visited: HashSet<u32>,
+}
+
+impl GffWalk {
+    fn push_struct(&mut self, prefix: String, struct_index: u32) {
+        if !self.visited.insert(struct_index) {
+            warn!("Not re-walking struct {struct_index} at '{prefix}'");
+            return;
+        }
+        match self.parser.read_struct_fields(struct_index) {
+            Ok(fields) => self
+                .stack
+                .push(WalkFrame::Fields(prefix, fields.into_iter())),
+            Err(e) => warn!("Skipping unreadable struct {struct_index} at '{prefix}': {e}"),
+        }
+    }
+}
+
+impl Iterator for GffWalk {
+    type Item = (String, GffValue<'static>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let item = match self.stack.last_mut()? {
+                WalkFrame::Fields(prefix, fields) => fields.next().map(|(label, value)| {
+                    let path = if prefix.is_empty() {
+                        label
+                    } else {
+                        format!("{prefix}/{label}")
+                    };
+                    (path, value)
+                }),
+                WalkFrame::List(prefix, items) => items
+                    .next()
+                    .map(|(i, lazy)| (format!("{prefix}/{i}"), GffValue::Struct(lazy))),
+            };
+
+            let Some((path, value)) = item else {
+                self.stack.pop();
+                continue;
+            };
+
+            match &value {
+                GffValue::Struct(lazy) => self.push_struct(path.clone(), lazy.struct_index),
+                GffValue::List(items) => self.stack.push(WalkFrame::List(
+                    path.clone(),
+                    items.clone().into_iter().enumerate(),
+                )),
+                _ => {}
+            }
+
+            return Some((path, value));
+        }
+    }
+}
+
+pub type BatchLoadResult = batch::BatchLoadResult<Arc<GffParser>, GffError>;
+
+/// Parallel loading of multiple GFF files (e.g. a save folder's `.bic`/`.ros`
+/// companions), one result per file; see `batch::load_multiple`.
+pub fn load_multiple_files<P: AsRef<Path> + Sync>(
+    paths: &[P],
+    max_threads: Option<usize>,
+) -> BatchLoadResult {
+    batch::load_multiple(paths, max_threads, |path| GffParser::new(path))
 }
@@ -27,6 +27,39 @@ pub enum GffFieldType {
     List = 15,
 }
 
+impl GffFieldType {
+    pub fn from_id(type_id: u32) -> Option<Self> {
+        Some(match type_id {
+            0 => Self::Byte,
+            1 => Self::Char,
+            2 => Self::Word,
+            3 => Self::Short,
+            4 => Self::Dword,
+            5 => Self::Int,
+            6 => Self::Dword64,
+            7 => Self::Int64,
+            8 => Self::Float,
+            9 => Self::Double,
+            10 => Self::String,
+            11 => Self::ResRef,
+            12 => Self::LocString,
+            13 => Self::Void,
+            14 => Self::Struct,
+            15 => Self::List,
+            _ => return None,
+        })
+    }
+}
+
+/// A field skipped by a parser in lenient mode because its type id is not a
+/// known GFF field type.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GffWarning {
+    pub field_index: u32,
+    pub label: String,
+    pub field_type: u32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LocalizedSubstring<'a> {
     pub string: Cow<'a, str>,
@@ -40,6 +73,18 @@ pub struct LocalizedString<'a> {
     pub substrings: Vec<LocalizedSubstring<'a>>,
 }
 
+impl LocalizedString<'_> {
+    /// Substring for `language`/`gender`, falling back to the same language
+    /// with either gender. `None` when the language isn't present.
+    pub fn substring_for(&self, language: u32, gender: u32) -> Option<&str> {
+        self.substrings
+            .iter()
+            .find(|sub| sub.language == language && sub.gender == gender)
+            .or_else(|| self.substrings.iter().find(|sub| sub.language == language))
+            .map(|sub| sub.string.as_ref())
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(untagged)]
 pub enum GffValue<'a> {
@@ -265,4 +310,27 @@ impl GffValue<'_> {
             GffValue::ListRef(vec) => GffValue::ListRef(vec),
         }
     }
+    /// On-disk field type this value is stored as.
+    pub fn field_type(&self) -> GffFieldType {
+        match self {
+            GffValue::Byte(_) => GffFieldType::Byte,
+            GffValue::Char(_) => GffFieldType::Char,
+            GffValue::Word(_) => GffFieldType::Word,
+            GffValue::Short(_) => GffFieldType::Short,
+            GffValue::Dword(_) => GffFieldType::Dword,
+            GffValue::Int(_) => GffFieldType::Int,
+            GffValue::Dword64(_) => GffFieldType::Dword64,
+            GffValue::Int64(_) => GffFieldType::Int64,
+            GffValue::Float(_) => GffFieldType::Float,
+            GffValue::Double(_) => GffFieldType::Double,
+            GffValue::String(_) => GffFieldType::String,
+            GffValue::ResRef(_) => GffFieldType::ResRef,
+            GffValue::LocString(_) => GffFieldType::LocString,
+            GffValue::Void(_) => GffFieldType::Void,
+            GffValue::Struct(_) | GffValue::StructOwned(_) | GffValue::StructRef(_) => {
+                GffFieldType::Struct
+            }
+            GffValue::List(_) | GffValue::ListOwned(_) | GffValue::ListRef(_) => GffFieldType::List,
+        }
+    }
 }
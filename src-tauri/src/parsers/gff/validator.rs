@@ -0,0 +1,263 @@
+//! Pre-write safety gate for edited GFF documents.
+//!
+//! The game silently misbehaves (or refuses to load a save) when required
+//! fields like a creature's `FirstName` or `Tag` go missing or change type.
+//! `GffValidator` checks a parsed document, or the owned value tree an edit
+//! produces, against a list of required `(path, type)` entries before it is
+//! written back out. `GffWriter::validator` runs it as part of the write.
+
+use std::sync::Arc;
+
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use super::{GffError, GffFieldType, GffParser, GffValue};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum ValidationIssue {
+    Missing {
+        path: String,
+    },
+    WrongType {
+        path: String,
+        expected: GffFieldType,
+        found: GffFieldType,
+    },
+    Unreadable {
+        path: String,
+        reason: String,
+    },
+}
+
+impl ValidationIssue {
+    pub fn path(&self) -> &str {
+        match self {
+            Self::Missing { path }
+            | Self::WrongType { path, .. }
+            | Self::Unreadable { path, .. } => path,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GffValidator {
+    required: Vec<(String, GffFieldType)>,
+}
+
+impl GffValidator {
+    /// Paths use the same `a/b/0/c` form as [`GffParser::get_value`].
+    pub fn new<I, S>(required: I) -> Self
+    where
+        I: IntoIterator<Item = (S, GffFieldType)>,
+        S: Into<String>,
+    {
+        Self {
+            required: required
+                .into_iter()
+                .map(|(path, ty)| (path.into(), ty))
+                .collect(),
+        }
+    }
+
+    pub fn require(mut self, path: impl Into<String>, expected: GffFieldType) -> Self {
+        self.required.push((path.into(), expected));
+        self
+    }
+
+    pub fn validate(&self, doc: &Arc<GffParser>) -> Vec<ValidationIssue> {
+        let root = doc.read_struct_fields(0).map(|fields| {
+            fields
+                .into_iter()
+                .map(|(label, value)| (label, value.into_owned()))
+                .collect()
+        });
+        match root {
+            Ok(root) => self.validate_tree(&root),
+            Err(e) => self
+                .required
+                .iter()
+                .map(|(path, _)| ValidationIssue::Unreadable {
+                    path: path.clone(),
+                    reason: e.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Check an owned value tree, such as the output of
+    /// `GffParser::set_value` or `GffEditor::build`, without writing it.
+    /// Lazy structs and lists in the tree are read through their parser.
+    pub fn validate_tree(
+        &self,
+        tree: &IndexMap<String, GffValue<'static>>,
+    ) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+
+        for (path, expected) in &self.required {
+            match field_type_at(tree, path) {
+                Ok(found) => {
+                    if found != *expected {
+                        issues.push(ValidationIssue::WrongType {
+                            path: path.clone(),
+                            expected: *expected,
+                            found,
+                        });
+                    }
+                }
+                Err(GffError::FieldNotFound(_)) => {
+                    issues.push(ValidationIssue::Missing { path: path.clone() });
+                }
+                Err(e) => {
+                    issues.push(ValidationIssue::Unreadable {
+                        path: path.clone(),
+                        reason: e.to_string(),
+                    });
+                }
+            }
+        }
+
+        issues
+    }
+}
+
+/// Position reached while resolving a path in a value tree.
+enum Cursor<'t> {
+    Fields(&'t IndexMap<String, GffValue<'static>>),
+    Value(&'t GffValue<'static>),
+    Lazy(GffValue<'static>),
+}
+
+fn field_type_at(
+    tree: &IndexMap<String, GffValue<'static>>,
+    path: &str,
+) -> Result<GffFieldType, GffError> {
+    let mut cursor = Cursor::Fields(tree);
+    for part in path.split('/') {
+        cursor = step(cursor, part)?;
+    }
+    Ok(match cursor {
+        Cursor::Fields(_) => GffFieldType::Struct,
+        Cursor::Value(value) => value.field_type(),
+        Cursor::Lazy(value) => value.field_type(),
+    })
+}
+
+fn step<'t>(cursor: Cursor<'t>, part: &str) -> Result<Cursor<'t>, GffError> {
+    let list_index = |len: usize| {
+        part.parse::<usize>()
+            .ok()
+            .filter(|&idx| idx < len)
+            .ok_or_else(|| GffError::FieldNotFound(format!("Invalid list index: {part}")))
+    };
+
+    match cursor {
+        Cursor::Fields(fields) => fields
+            .get(part)
+            .map(Cursor::Value)
+            .ok_or_else(|| GffError::FieldNotFound(part.to_string())),
+        Cursor::Value(GffValue::StructOwned(fields)) => step(Cursor::Fields(fields), part),
+        Cursor::Value(GffValue::ListOwned(items)) => {
+            Ok(Cursor::Fields(&items[list_index(items.len())?]))
+        }
+        Cursor::Value(value @ (GffValue::Struct(_) | GffValue::List(_))) => {
+            step(Cursor::Lazy(value.clone()), part)
+        }
+        Cursor::Lazy(GffValue::Struct(lazy)) => Ok(Cursor::Lazy(
+            lazy.parser.read_field_by_label(lazy.struct_index, part)?,
+        )),
+        Cursor::Lazy(GffValue::List(items)) => Ok(Cursor::Lazy(GffValue::Struct(
+            items[list_index(items.len())?].clone(),
+        ))),
+        Cursor::Value(_) | Cursor::Lazy(_) => Err(GffError::FieldNotFound(format!(
+            "Cannot traverse into non-structural field: {part}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::gff::{GffValue, GffWriter, LocalizedString};
+    use indexmap::IndexMap;
+    use std::borrow::Cow;
+
+    fn creature_fields() -> IndexMap<String, GffValue<'static>> {
+        let mut root: IndexMap<String, GffValue<'static>> = IndexMap::new();
+        root.insert(
+            "FirstName".into(),
+            GffValue::LocString(LocalizedString {
+                string_ref: -1,
+                substrings: Vec::new(),
+            }),
+        );
+        root.insert("Tag".into(), GffValue::String(Cow::Borrowed("hero")));
+        root.insert("Str".into(), GffValue::Byte(14));
+        root
+    }
+
+    fn validator() -> GffValidator {
+        GffValidator::new([
+            ("FirstName", GffFieldType::LocString),
+            ("Tag", GffFieldType::String),
+            ("Str", GffFieldType::Byte),
+        ])
+    }
+
+    #[test]
+    fn complete_document_has_no_issues() {
+        let bytes = GffWriter::new("BIC ", "V3.2")
+            .write(creature_fields())
+            .expect("write");
+        let doc = GffParser::from_bytes(bytes).expect("parse");
+
+        assert!(validator().validate(&doc).is_empty());
+    }
+
+    #[test]
+    fn edited_tree_is_checked_before_writing() {
+        let bytes = GffWriter::new("BIC ", "V3.2")
+            .write(creature_fields())
+            .expect("write");
+        let doc = GffParser::from_bytes(bytes).expect("parse");
+
+        let tree = doc
+            .set_value("Str", GffValue::Byte(18), false)
+            .expect("set_value");
+        assert!(validator().validate_tree(&tree).is_empty());
+
+        let mut tree = tree;
+        tree.shift_remove("Tag");
+        assert_eq!(
+            validator().validate_tree(&tree),
+            vec![ValidationIssue::Missing { path: "Tag".into() }]
+        );
+        match GffWriter::new("BIC ", "V3.2")
+            .validator(validator())
+            .write(tree)
+        {
+            Err(GffError::ValidationFailed(issues)) => assert_eq!(issues.len(), 1),
+            other => panic!("expected ValidationFailed, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn removed_and_retyped_fields_are_reported() {
+        let mut fields = creature_fields();
+        fields.shift_remove("Tag");
+        fields.insert("Str".into(), GffValue::Int(14));
+        let bytes = GffWriter::new("BIC ", "V3.2").write(fields).expect("write");
+        let doc = GffParser::from_bytes(bytes).expect("parse");
+
+        let issues = validator().validate(&doc);
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0], ValidationIssue::Missing { path: "Tag".into() });
+        assert_eq!(
+            issues[1],
+            ValidationIssue::WrongType {
+                path: "Str".into(),
+                expected: GffFieldType::Byte,
+                found: GffFieldType::Int,
+            }
+        );
+    }
+}
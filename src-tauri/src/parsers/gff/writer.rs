@@ -1,12 +1,16 @@
 use std::borrow::Cow;
 use std::io::{Cursor, Write};
+use std::sync::Arc;
 
 use byteorder::{LittleEndian, WriteBytesExt};
 use encoding_rs::WINDOWS_1252;
 use indexmap::IndexMap;
 
 use super::error::GffError;
+use super::helpers::{RESREF_MAX_LEN, normalize_resref, require_ascii_resref};
+use super::parser::GffParser;
 use super::types::{GffFieldType, GffValue};
+use super::validator::GffValidator;
 
 /// Encode a Rust string (UTF-8 internally) to Windows-1252 bytes for GFF storage.
 /// Returns borrowed bytes on the ASCII fast path; mirrors the parser's
@@ -29,6 +33,9 @@ pub struct GffWriter {
 
     struct_queue: Vec<IndexMap<String, GffValue<'static>>>,
     struct_ids: Vec<u32>,
+    strict_resrefs: bool,
+    normalize_resrefs: bool,
+    validator: Option<GffValidator>,
 }
 
 impl GffWriter {
@@ -44,9 +51,34 @@ impl GffWriter {
             list_indices: Vec::new(),
             struct_queue: Vec::new(),
             struct_ids: Vec::new(),
+            strict_resrefs: false,
+            normalize_resrefs: false,
+            validator: None,
         }
     }
 
+    /// Reject ResRefs with non-ASCII characters instead of writing them.
+    pub fn strict_resrefs(mut self, strict: bool) -> Self {
+        self.strict_resrefs = strict;
+        self
+    }
+
+    /// Lowercase ResRefs and truncate them to `RESREF_MAX_LEN` characters
+    /// (see `normalize_resref`). Off by default, so existing ResRefs are
+    /// written byte for byte; over-long ones are still cut to the format's
+    /// 32 bytes.
+    pub fn normalize_resrefs(mut self, normalize: bool) -> Self {
+        self.normalize_resrefs = normalize;
+        self
+    }
+
+    /// Check the root against `validator` before encoding anything, failing
+    /// with `ValidationFailed` if a required field is missing or retyped.
+    pub fn validator(mut self, validator: GffValidator) -> Self {
+        self.validator = Some(validator);
+        self
+    }
+
     fn reset(&mut self) {
         self.structs.clear();
         self.fields.clear();
@@ -70,6 +102,12 @@ impl GffWriter {
         root: IndexMap<String, GffValue<'static>>,
         root_struct_id: u32,
     ) -> Result<Vec<u8>, GffError> {
+        if let Some(validator) = &self.validator {
+            let issues = validator.validate_tree(&root);
+            if !issues.is_empty() {
+                return Err(GffError::ValidationFailed(issues));
+            }
+        }
         self.reset();
 
         let mut flat_structs: Vec<IndexMap<String, GffValue<'static>>> = Vec::new();
@@ -95,6 +133,59 @@ impl GffWriter {
         self.finalize()
     }
 
+    /// Re-serialize a parsed file with its own file type and version, keeping
+    /// its struct, field and label order. Field data, field indices and list
+    /// indices are re-encoded in the order of their original offsets, so a
+    /// packed file (as the game writes them) comes back byte for byte. Edits
+    /// go through `write` with the tree from `GffParser::to_value_tree`.
+    pub fn rewrite(parser: &Arc<GffParser>) -> Result<Vec<u8>, GffError> {
+        // Unreadable or cyclic structs fail here, before anything is encoded
+        parser.to_value_tree()?;
+
+        let mut writer = GffWriter::new(&parser.file_type, &parser.file_version);
+        let (struct_count, field_count, label_count) = parser.table_counts();
+        for index in 0..label_count {
+            writer.get_label_index(parser.get_label(index)?.into_owned());
+        }
+
+        let headers = (0..field_count)
+            .map(|index| parser.field_header(index))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut order: Vec<u32> = (0..field_count).collect();
+        order.sort_by_key(|&index| headers[index as usize].2);
+        let mut fields = vec![(0, 0, 0); field_count as usize];
+        for index in order {
+            let (label, value) = parser.read_field(index)?;
+            let value = match value {
+                GffValue::Struct(lazy) => GffValue::StructRef(lazy.struct_index),
+                GffValue::List(items) => {
+                    GffValue::ListRef(items.iter().map(|lazy| lazy.struct_index).collect())
+                }
+                other => other.into_owned(),
+            };
+            writer.encode_field(label, value)?;
+            fields[index as usize] = writer.fields.pop().expect("encode_field pushes a field");
+        }
+        writer.fields = fields;
+
+        let mut multi_field = Vec::new();
+        for index in 0..struct_count {
+            let (id, data, count) = parser.struct_header(index)?;
+            writer.structs.push((id, data, count));
+            if count > 1 {
+                multi_field.push(index as usize);
+            }
+        }
+        multi_field.sort_by_key(|&index| writer.structs[index].1);
+        for index in multi_field {
+            let indices = parser.struct_field_indices(index as u32)?;
+            writer.structs[index].1 = writer.field_indices.len() as u32 * 4;
+            writer.field_indices.extend(indices);
+        }
+
+        writer.finalize()
+    }
+
     fn flatten_value_with_id(
         &mut self,
         val: GffValue<'static>,
@@ -157,6 +248,25 @@ impl GffWriter {
                 }
                 Ok(GffValue::ListRef(new_list))
             }
+            // Lazy values (e.g. from `GffEditor::build`) are read from their
+            // parser as they are reached
+            GffValue::Struct(lazy) => {
+                let map = lazy
+                    .parser
+                    .owned_struct(lazy.struct_index, &mut Vec::new())?;
+                self.process_field_value_with_id(
+                    GffValue::StructOwned(Box::new(map)),
+                    flat_list,
+                    struct_ids,
+                )
+            }
+            GffValue::List(items) => {
+                let list = items
+                    .iter()
+                    .map(|lazy| lazy.parser.owned_struct(lazy.struct_index, &mut Vec::new()))
+                    .collect::<Result<_, _>>()?;
+                self.process_field_value_with_id(GffValue::ListOwned(list), flat_list, struct_ids)
+            }
             v => Ok(v),
         }
     }
@@ -226,8 +336,18 @@ impl GffWriter {
             }
             GffValue::ResRef(v) => {
                 let offset = self.field_data.position() as u32;
-                let bytes = encode_w1252(&v);
-                let len = bytes.len().min(32) as u8;
+                let normalized;
+                let resref: &str = if self.normalize_resrefs {
+                    normalized = normalize_resref(&v, self.strict_resrefs)?;
+                    &normalized
+                } else {
+                    if self.strict_resrefs {
+                        require_ascii_resref(&v)?;
+                    }
+                    &v
+                };
+                let bytes = encode_w1252(resref);
+                let len = bytes.len().min(RESREF_MAX_LEN) as u8;
                 self.field_data.write_u8(len)?;
                 self.field_data.write_all(&bytes[..len as usize])?;
                 (GffFieldType::ResRef, offset)
@@ -1,3 +1,4 @@
+pub mod batch;
 pub mod erf;
 pub mod gff;
 pub mod gr2;
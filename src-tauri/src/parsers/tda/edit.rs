@@ -0,0 +1,137 @@
+use super::error::{TDAError, TDAResult};
+use super::types::{CellValue, TDAParser, TDARow};
+
+impl TDAParser {
+    /// Overwrite one cell. `None` stores an empty cell and `"****"` a null
+    /// one, the same as parsing would. A short target row is padded as in
+    /// `append_row`.
+    pub fn set_cell(&mut self, row: usize, col: usize, value: Option<&str>) -> TDAResult<()> {
+        let column_count = self.column_count();
+        if col >= column_count {
+            return Err(TDAError::ColumnIndexOutOfBounds {
+                index: col,
+                max: column_count,
+            });
+        }
+        let row_count = self.row_count();
+        if row >= row_count {
+            return Err(TDAError::RowIndexOutOfBounds {
+                index: row,
+                max: row_count,
+            });
+        }
+
+        let cell = self.new_cell(value);
+        let target = &mut self.rows_mut()[row];
+        if target.len() < column_count {
+            target.resize(column_count, CellValue::Null);
+        }
+        target[col] = cell;
+        Ok(())
+    }
+
+    /// Add a row at the end. Rows shorter than the column count are padded
+    /// with null (`****`) cells, as are short rows touched by `set_cell` and
+    /// `apply_merge`. When row indices are captured the new row gets the
+    /// next index after the highest numeric one.
+    pub fn append_row(&mut self, values: &[Option<&str>]) -> TDAResult<()> {
+        self.insert_row(self.row_count(), values)
+    }
+
+    /// Insert a row before `index` (`row_count()` appends), shifting later
+    /// rows down; padding and captured indices work as in `append_row`.
+    ///
+    /// Without captured row indices the index column is not kept, so
+    /// `to_2da_string` re-synthesizes sequential indices and every row after
+    /// `index` is renumbered on write.
+    pub fn insert_row(&mut self, index: usize, values: &[Option<&str>]) -> TDAResult<()> {
+        let row_count = self.row_count();
+        if index > row_count {
+            return Err(TDAError::RowIndexOutOfBounds {
+                index,
+                max: row_count,
+            });
+        }
+        let column_count = self.column_count();
+        if values.len() > column_count {
+            return Err(TDAError::ColumnCountExceeded {
+                count: values.len(),
+                limit: column_count,
+            });
+        }
+        self.security_limits().validate_row_count(row_count + 1)?;
+
+        let mut row: TDARow = values.iter().map(|value| self.new_cell(*value)).collect();
+        row.resize(column_count, CellValue::Null);
+
+        if self.tracks_row_indices() {
+            let next = self.next_row_index();
+            let symbol = self.interner_mut().get_or_intern(next.to_string());
+            self.row_indices_mut().insert(index, symbol);
+        }
+        self.rows_mut().insert(index, row);
+        Ok(())
+    }
+
+    /// Remove the row at `index`, shifting later rows up. As with
+    /// `insert_row`, uncaptured indices are renumbered on write.
+    pub fn remove_row(&mut self, index: usize) -> TDAResult<()> {
+        let row_count = self.row_count();
+        if index >= row_count {
+            return Err(TDAError::RowIndexOutOfBounds {
+                index,
+                max: row_count,
+            });
+        }
+
+        if self.tracks_row_indices() {
+            self.row_indices_mut().remove(index);
+        }
+        self.rows_mut().remove(index);
+        Ok(())
+    }
+
+    /// Rename a column in place; lookups are case-insensitive, so a
+    /// case-only rename is allowed. Cells stay where they are.
+    pub fn rename_column(&mut self, old: &str, new: &str) -> TDAResult<()> {
+        let index = self
+            .find_column_index(old)
+            .ok_or_else(|| TDAError::ColumnNotFound {
+                column: old.to_string(),
+            })?;
+        if let Some(existing) = self.find_column_index(new)
+            && existing != index
+        {
+            return Err(TDAError::DuplicateColumn {
+                name: new.to_string(),
+                first: existing,
+                second: index,
+            });
+        }
+
+        let symbol = self.interner_mut().get_or_intern(new);
+        self.columns_mut()[index].name = symbol;
+        self.column_map_mut().remove(&old.to_lowercase());
+        self.column_map_mut().insert(new.to_lowercase(), index);
+        Ok(())
+    }
+
+    fn new_cell(&mut self, value: Option<&str>) -> CellValue {
+        match value {
+            Some(value) => CellValue::new(value, self.interner_mut()),
+            None => CellValue::Empty,
+        }
+    }
+
+    fn tracks_row_indices(&self) -> bool {
+        self.captures_row_indices()
+            && (self.row_count() == 0 || self.original_row_index(self.row_count() - 1).is_some())
+    }
+
+    fn next_row_index(&self) -> u64 {
+        (0..self.row_count())
+            .filter_map(|row| self.original_row_index(row)?.parse::<u64>().ok())
+            .max()
+            .map_or(0, |max| max + 1)
+    }
+}
@@ -55,6 +55,23 @@ pub enum TDAError {
 
     #[error("Security violation: {details}")]
     SecurityViolation { details: String },
+
+    #[error("Duplicate column '{name}' at indices {first} and {second}")]
+    DuplicateColumn {
+        name: String,
+        first: usize,
+        second: usize,
+    },
+
+    #[error("Cell at row {row}, column '{col}' has value '{value}' of the wrong type")]
+    CellTypeMismatch {
+        row: usize,
+        col: String,
+        value: String,
+    },
+
+    #[error("Loading '{path}' timed out after {timeout_ms}ms")]
+    Timeout { path: String, timeout_ms: u64 },
 }
 
 pub type TDAResult<T> = Result<T, TDAError>;
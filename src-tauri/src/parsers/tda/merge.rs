@@ -0,0 +1,96 @@
+use super::error::{TDAError, TDAResult};
+use super::types::{CellValue, ColumnInfo, TDAParser, TDARow};
+
+impl TDAParser {
+    /// Overlay a parsed 2DAM patch onto this table. Rows are matched by the
+    /// merge file's row index; only non-`****` merge cells overwrite, and
+    /// columns the base lacks are appended. The merge must be parsed with
+    /// `set_capture_row_indices(true)`. If this table captured its own
+    /// indices, unknown indices append a row; otherwise the index is the
+    /// positional row and the table is padded with `****` rows to reach it.
+    pub fn apply_merge(&mut self, merge: &TDAParser) -> TDAResult<()> {
+        if merge.row_count() > 0 && !merge.captures_row_indices() {
+            return Err(TDAError::ParseError {
+                details: "2DAM merge table was parsed without captured row indices".to_string(),
+            });
+        }
+
+        let mut column_targets = Vec::with_capacity(merge.column_count());
+        for name in merge.column_names() {
+            let target = match self.find_column_index(name) {
+                Some(index) => index,
+                None => self.append_null_column(name)?,
+            };
+            column_targets.push(target);
+        }
+
+        for (merge_row, row) in merge.rows().iter().enumerate() {
+            let index = merge.original_row_index(merge_row).unwrap_or_default();
+            let target_row = self.resolve_merge_row(index)?;
+
+            for (cell, &target_col) in row.iter().zip(&column_targets) {
+                if matches!(cell, CellValue::Null | CellValue::Empty) {
+                    continue;
+                }
+                let value = cell.as_str(merge.interner()).unwrap_or_default();
+                let cell = CellValue::new(value, self.interner_mut());
+
+                let column_count = self.column_count();
+                let row = &mut self.rows_mut()[target_row];
+                if row.len() < column_count {
+                    row.resize(column_count, CellValue::Null);
+                }
+                row[target_col] = cell;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn append_null_column(&mut self, name: &str) -> TDAResult<usize> {
+        let index = self.column_count();
+        self.security_limits().validate_column_count(index + 1)?;
+
+        let symbol = self.interner_mut().get_or_intern(name);
+        self.columns_mut().push(ColumnInfo {
+            name: symbol,
+            index,
+        });
+        self.column_map_mut().insert(name.to_lowercase(), index);
+
+        for row in self.rows_mut() {
+            row.resize(index + 1, CellValue::Null);
+        }
+
+        Ok(index)
+    }
+
+    fn resolve_merge_row(&mut self, index: &str) -> TDAResult<usize> {
+        if self.captures_row_indices() {
+            if let Some(row) = self.row_by_original_index(index) {
+                return Ok(row);
+            }
+            let symbol = self.interner_mut().get_or_intern(index);
+            self.row_indices_mut().push(symbol);
+            self.push_null_row()?;
+            return Ok(self.row_count() - 1);
+        }
+
+        let row: usize = index.parse().map_err(|_| TDAError::ParseError {
+            details: format!("2DAM row index '{index}' is not a row number"),
+        })?;
+        while self.row_count() <= row {
+            self.push_null_row()?;
+        }
+        Ok(row)
+    }
+
+    fn push_null_row(&mut self) -> TDAResult<()> {
+        self.security_limits()
+            .validate_row_count(self.row_count() + 1)?;
+
+        let row: TDARow = std::iter::repeat_n(CellValue::Null, self.column_count()).collect();
+        self.rows_mut().push(row);
+        Ok(())
+    }
+}
@@ -1,9 +1,19 @@
+pub mod edit;
 pub mod error;
+pub mod merge;
 pub mod parser;
+pub mod schema;
 pub mod tokenizer;
 pub mod types;
+pub mod writer;
 
 pub use error::{SecurityLimits, TDAError, TDAResult};
-pub use parser::{ParserStatistics, load_multiple_files};
-pub use tokenizer::TDATokenizer;
-pub use types::{CellValue, SerializableCellValue, SerializableTDAParser, TDAParser};
+pub use parser::{
+    BatchLoadResult, ParserStatistics, load_multiple_files, load_multiple_files_with_timeout,
+};
+pub use schema::{TdaColumnSchema, TdaColumnType, TdaSchema};
+pub use tokenizer::{LineTokens, SeparatorMode, TDATokenizer, Token};
+pub use types::{
+    CellValue, ControlCharMode, MemoryBreakdown, ParserOptions, RowView, SerializableCellValue,
+    SerializableTDAParser, TDAParser, TDAWarning,
+};
@@ -1,15 +1,23 @@
+use std::borrow::Cow;
 use std::fs::File;
 use std::io::{BufReader, Read};
-use std::path::Path;
-use std::time::Instant;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, mpsc};
+use std::time::{Duration, Instant};
 
-use ahash::AHashMap;
 use memmap2::Mmap;
-use tracing::{instrument, trace};
+use tracing::{instrument, trace, warn};
 
 use super::error::{SecurityLimits, TDAError, TDAResult};
-use super::tokenizer::{TDATokenizer, Token};
-use super::types::{CellValue, ColumnInfo, SerializableTDAParser, TDAParser, TDARow};
+use super::tokenizer::Token;
+use super::types::{
+    CellValue, ColumnInfo, ControlCharMode, SerializableTDAParser, StreamStage, TDAParser, TDARow,
+    TDAWarning,
+};
+use crate::parsers::batch;
+use crate::utils::CacheValidation;
 
 impl TDAParser {
     #[instrument(name = "TDAParser::parse_from_bytes", skip_all, fields(size = data.len()))]
@@ -89,42 +97,88 @@ impl TDAParser {
     }
 
     fn parse_content(&mut self, content: &str) -> TDAResult<()> {
-        let mut tokenizer = TDATokenizer::new();
-        let mut header_parsed = false;
-        let mut columns_parsed = false;
-        let mut line_count = 0;
+        let content = match content.strip_prefix('\u{FEFF}') {
+            Some(rest) => {
+                self.metadata_mut().warnings.push(TDAWarning::ByteOrderMark);
+                rest
+            }
+            None => content,
+        };
 
         for line in content.lines() {
-            line_count += 1;
+            self.parse_line(line)?;
+        }
+
+        Ok(())
+    }
 
-            self.security_limits().validate_line_length(line.len())?;
+    /// Feed one line of 2DA text, for streaming from a `BufRead`. The first
+    /// non-blank line is the header, the next the columns, the rest rows.
+    /// Call `clear()` before feeding a new table.
+    pub fn parse_line(&mut self, line: &str) -> TDAResult<()> {
+        self.metadata_mut().line_count += 1;
 
-            let tokens = tokenizer.tokenize_line(line)?;
+        self.security_limits().validate_line_length(line.len())?;
 
-            if tokens.is_empty() {
-                continue;
-            }
+        let line = self.sanitize_control_chars(line)?;
+        let line = line.as_ref();
+        let tokens = self.tokenizer_mut().tokenize_line(line)?;
+
+        if tokens.is_empty() {
+            return Ok(());
+        }
 
-            if !header_parsed {
+        match self.stream_stage() {
+            StreamStage::Header => {
                 self.parse_header_direct(line.trim())?;
-                header_parsed = true;
-            } else if !columns_parsed {
+                self.set_stream_stage(StreamStage::Columns);
+            }
+            StreamStage::Columns => {
                 self.parse_columns(&tokens)?;
                 self.security_limits()
                     .validate_column_count(self.column_count())?;
-                columns_parsed = true;
-            } else {
+                self.set_stream_stage(StreamStage::Rows);
+            }
+            StreamStage::Rows => {
                 self.parse_data_row(&tokens)?;
                 self.security_limits()
                     .validate_row_count(self.row_count())?;
             }
         }
 
-        self.metadata_mut().line_count = line_count;
-
         Ok(())
     }
 
+    fn sanitize_control_chars<'a>(&mut self, line: &'a str) -> TDAResult<Cow<'a, str>> {
+        let is_stray = |c: char| c.is_control() && !matches!(c, '\t' | '\r' | '\n');
+        let mode = self.options().control_chars;
+        if mode == ControlCharMode::Keep {
+            return Ok(Cow::Borrowed(line));
+        }
+        let Some((position, c)) = line.char_indices().find(|&(_, c)| is_stray(c)) else {
+            return Ok(Cow::Borrowed(line));
+        };
+
+        let line_number = self.metadata().line_count;
+        if mode == ControlCharMode::Reject {
+            return Err(TDAError::MalformedLine {
+                line_number,
+                details: format!("Control character U+{:04X} at byte {position}", c as u32),
+            });
+        }
+
+        let cleaned: String = line.chars().filter(|&c| !is_stray(c)).collect();
+        let count = line.chars().count() - cleaned.chars().count();
+        warn!("Stripped {count} control character(s) from 2DA line {line_number}");
+        self.metadata_mut()
+            .warnings
+            .push(TDAWarning::ControlCharsStripped {
+                line: line_number,
+                count,
+            });
+        Ok(Cow::Owned(cleaned))
+    }
+
     fn parse_header_direct(&mut self, line: &str) -> TDAResult<()> {
         let bytes = line.as_bytes();
 
@@ -155,12 +209,21 @@ impl TDAParser {
             return Err(TDAError::InvalidHeader(line.to_string()));
         }
 
-        if is_2dam {
-            self.metadata_mut().format_version = format!("2DAM{version}");
-            self.metadata_mut().has_warnings = true;
+        let format_version = if is_2dam {
+            self.metadata_mut().warnings.push(TDAWarning::MergeFormat);
+            format!("2DAM{version}")
         } else {
-            self.metadata_mut().format_version = format!("2DA {version}");
+            format!("2DA {version}")
+        };
+
+        if line != format_version {
+            self.metadata_mut()
+                .warnings
+                .push(TDAWarning::NonStandardHeader {
+                    header: line.to_string(),
+                });
         }
+        self.metadata_mut().format_version = format_version;
 
         Ok(())
     }
@@ -173,13 +236,13 @@ impl TDAParser {
             });
         }
 
-        let column_tokens = if tokens.len() > 1 && tokens[0].content.is_empty() {
+        let named_tokens = if tokens.len() > 1 && tokens[0].content.is_empty() {
             &tokens[1..]
         } else {
             tokens
         };
 
-        if column_tokens.is_empty() {
+        if named_tokens.is_empty() {
             return Err(TDAError::MalformedLine {
                 line_number: 2,
                 details: "No valid column headers found after skipping empty first column"
@@ -187,19 +250,53 @@ impl TDAParser {
             });
         }
 
-        self.columns_mut().reserve(column_tokens.len());
-        self.column_map_mut().reserve(column_tokens.len());
+        let keep_index_column = self.options().keep_index_column;
+        let column_names = keep_index_column
+            .then_some("")
+            .into_iter()
+            .chain(named_tokens.iter().map(|token| token.content));
+
+        self.columns_mut()
+            .reserve(named_tokens.len() + usize::from(keep_index_column));
+        self.column_map_mut()
+            .reserve(named_tokens.len() + usize::from(keep_index_column));
+
+        for (index, name) in column_names.enumerate() {
+            let mut key = name.to_lowercase();
+            let mut name = Cow::Borrowed(name);
+
+            if let Some(&first) = self.column_map().get(&key) {
+                if !self.options().rename_duplicate_columns {
+                    return Err(TDAError::DuplicateColumn {
+                        name: name.into_owned(),
+                        first,
+                        second: index,
+                    });
+                }
+                let mut suffix = 2;
+                while self.column_map().contains_key(&format!("{key}_{suffix}")) {
+                    suffix += 1;
+                }
+                let renamed = format!("{name}_{suffix}");
+                warn!("2DA column '{name}' repeated at index {index}; renamed to '{renamed}'");
+                self.metadata_mut()
+                    .warnings
+                    .push(TDAWarning::DuplicateColumn {
+                        name: name.to_string(),
+                        renamed: renamed.clone(),
+                    });
+                key = renamed.to_lowercase();
+                name = Cow::Owned(renamed);
+            }
 
-        for (index, token) in column_tokens.iter().enumerate() {
-            let symbol = self.interner_mut().get_or_intern(token.content);
+            let symbol = self.interner_mut().get_or_intern(&name);
             let column_info = ColumnInfo {
                 name: symbol,
                 index,
             };
 
             self.columns_mut().push(column_info);
-            self.column_map_mut()
-                .insert(token.content.to_lowercase(), index);
+            self.column_map_mut().insert(key, index);
         }
 
         Ok(())
@@ -210,7 +307,27 @@ impl TDAParser {
             return Ok(());
         }
 
-        let data_tokens = if tokens.len() > 1 { &tokens[1..] } else { &[] };
+        if self.captures_row_indices() {
+            let symbol = self.interner_mut().get_or_intern(tokens[0].content);
+            self.row_indices_mut().push(symbol);
+        }
+
+        let data_tokens = if self.options().keep_index_column {
+            tokens
+        } else if tokens.len() > 1 {
+            &tokens[1..]
+        } else {
+            &[]
+        };
+
+        if data_tokens.len() != self.column_count() {
+            let warning = TDAWarning::RaggedRow {
+                row: self.row_count(),
+                got: data_tokens.len(),
+                expected: self.column_count(),
+            };
+            self.metadata_mut().warnings.push(warning);
+        }
 
         let mut row = TDARow::new();
         row.reserve(self.columns().len());
@@ -236,6 +353,19 @@ impl TDAParser {
         self.parse_from_bytes(data.as_bytes())
     }
 
+    /// Plain MessagePack of `SerializableTDAParser`, without the zlib wrapper
+    /// `to_msgpack_compressed` adds. Cheaper for small tables.
+    pub fn to_msgpack(&self) -> TDAResult<Vec<u8>> {
+        Ok(rmp_serde::to_vec(&SerializableTDAParser::from_parser(
+            self,
+        ))?)
+    }
+
+    pub fn from_msgpack(data: &[u8]) -> TDAResult<Self> {
+        let serializable: SerializableTDAParser = rmp_serde::from_slice(data)?;
+        Ok(serializable.to_parser())
+    }
+
     pub fn to_msgpack_compressed(&self) -> TDAResult<Vec<u8>> {
         use flate2::{Compression, write::ZlibEncoder};
         use std::io::Write;
@@ -273,14 +403,41 @@ impl TDAParser {
         Ok(serializable.to_parser())
     }
 
+    /// Load from `cache_path` if it holds a usable cache, otherwise parse
+    /// `source_path` and write the cache. The cache is checked against the
+    /// source's size and mtime; see `load_with_cache_validated`.
     pub fn load_with_cache<P: AsRef<Path>>(
         &mut self,
         source_path: P,
         cache_path: Option<P>,
     ) -> TDAResult<bool> {
+        self.load_with_cache_validated(source_path, cache_path, CacheValidation::default())
+    }
+
+    /// `load_with_cache` with an explicit staleness check, e.g.
+    /// `CacheValidation::ContentHash` where mtimes can't be relied on.
+    /// Caches written before stamps were recorded fall back to comparing
+    /// the cache's mtime with the source's.
+    /// A cache parsed with different `ParserOptions` or row-index capture
+    /// than this parser's is stale, since it holds a differently shaped table.
+    pub fn load_with_cache_validated<P: AsRef<Path>>(
+        &mut self,
+        source_path: P,
+        cache_path: Option<P>,
+        validation: CacheValidation,
+    ) -> TDAResult<bool> {
+        let source_path = source_path.as_ref();
         if let Some(ref cache_path) = cache_path
             && let Ok(cache_data) = std::fs::read(cache_path)
             && let Ok(cached_parser) = Self::from_msgpack_compressed(&cache_data)
+            && cached_parser.options() == self.options()
+            && cached_parser.captures_row_indices() == self.captures_row_indices()
+            && validation.is_fresh(
+                source_path,
+                cache_path.as_ref(),
+                cached_parser.metadata().source_stamp,
+                cached_parser.metadata().source_hash,
+            )
         {
             *self = cached_parser;
             return Ok(true);
@@ -288,10 +445,13 @@ impl TDAParser {
 
         self.parse_from_file(source_path)?;
 
-        if let Some(cache_path) = cache_path
-            && let Ok(compressed_data) = self.to_msgpack_compressed()
-        {
-            std::fs::write(cache_path, compressed_data)?;
+        if let Some(cache_path) = cache_path {
+            let (stamp, hash) = validation.record(source_path);
+            self.metadata_mut().source_stamp = stamp;
+            self.metadata_mut().source_hash = hash;
+            if let Ok(compressed_data) = self.to_msgpack_compressed() {
+                std::fs::write(cache_path, compressed_data)?;
+            }
         }
 
         Ok(false)
@@ -310,6 +470,111 @@ impl TDAParser {
             },
         }
     }
+
+    /// Build a table from CSV as written by `to_csv`: the first record is
+    /// the column header, the rest are rows. Quoted fields may hold the
+    /// delimiter, `""` escapes, and line breaks. Empty fields become empty
+    /// cells and `****` null ones; short rows are padded with null cells
+    /// and recorded as `TDAWarning::RaggedRow`. A leading column with an
+    /// empty header is taken as the index column and dropped. The default
+    /// `SecurityLimits` apply.
+    pub fn from_csv(data: &str, delimiter: u8) -> TDAResult<Self> {
+        let start_time = Instant::now();
+        let mut parser = TDAParser::new();
+        parser.security_limits().validate_file_size(data.len())?;
+
+        let mut records = split_csv_records(data, char::from(delimiter))?.into_iter();
+        let Some(header) = records.next() else {
+            return Ok(parser);
+        };
+        let skip = usize::from(header.len() > 1 && header[0].is_empty());
+
+        parser
+            .security_limits()
+            .validate_column_count(header.len() - skip)?;
+        for (index, name) in header[skip..].iter().enumerate() {
+            let key = name.to_lowercase();
+            if let Some(&first) = parser.column_map().get(&key) {
+                return Err(TDAError::DuplicateColumn {
+                    name: name.clone(),
+                    first,
+                    second: index,
+                });
+            }
+            let symbol = parser.interner_mut().get_or_intern(name);
+            parser.columns_mut().push(ColumnInfo {
+                name: symbol,
+                index,
+            });
+            parser.column_map_mut().insert(key, index);
+        }
+
+        for record in records {
+            let fields = record.get(skip..).unwrap_or_default();
+            if fields.len() != parser.column_count() {
+                let warning = TDAWarning::RaggedRow {
+                    row: parser.row_count(),
+                    got: fields.len(),
+                    expected: parser.column_count(),
+                };
+                parser.metadata_mut().warnings.push(warning);
+            }
+            let values: Vec<Option<&str>> = fields.iter().map(|f| Some(f.as_str())).collect();
+            parser.append_row(&values)?;
+        }
+
+        parser.metadata_mut().file_size = data.len();
+        parser.metadata_mut().parse_time_ns = start_time.elapsed().as_nanos() as u64;
+        Ok(parser)
+    }
+}
+
+/// RFC 4180 records; blank lines outside quotes are skipped.
+fn split_csv_records(data: &str, delimiter: char) -> TDAResult<Vec<Vec<String>>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = data.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                record.push(std::mem::take(&mut field));
+                if record.len() > 1 || !record[0].is_empty() {
+                    records.push(std::mem::take(&mut record));
+                } else {
+                    record.clear();
+                }
+            }
+            c if c == delimiter => record.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(TDAError::ParseError {
+            details: "Unterminated quoted field in CSV".to_string(),
+        });
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
 }
 
 #[derive(Debug, Clone)]
@@ -321,32 +586,170 @@ pub struct ParserStatistics {
     pub compression_ratio: f64,
 }
 
+pub type BatchLoadResult = batch::BatchLoadResult<TDAParser, TDAError>;
+
+/// Parallel loading of multiple 2DA files, one result per file; see
+/// `batch::load_multiple`.
 pub fn load_multiple_files<P: AsRef<Path> + Send + Sync>(
     file_paths: &[P],
     security_limits: Option<SecurityLimits>,
-) -> TDAResult<AHashMap<String, TDAParser>> {
-    use rayon::prelude::*;
-    use std::collections::HashMap;
+    max_threads: Option<usize>,
+) -> BatchLoadResult {
+    let limits = security_limits.unwrap_or_default();
+    batch::load_multiple(file_paths, max_threads, |path| {
+        let mut parser = TDAParser::with_limits(limits.clone());
+        parser.parse_from_file(path).map(|()| parser)
+    })
+}
 
+/// Like `load_multiple_files`, but a file that takes longer than
+/// `per_file_timeout` to parse is abandoned and reported as `TDAError::Timeout`
+/// instead of stalling the whole batch (e.g. a hung network mount).
+///
+/// Files load on dedicated threads rather than the Rayon pool, so a load that
+/// never returns ties up only its own thread, not a worker other Rayon users
+/// depend on. Once the batch has returned, the remaining workers stop picking
+/// up files.
+pub fn load_multiple_files_with_timeout<P: AsRef<Path>>(
+    file_paths: &[P],
+    security_limits: Option<SecurityLimits>,
+    per_file_timeout: Duration,
+) -> BatchLoadResult {
     let limits = security_limits.unwrap_or_default();
 
-    let results: Result<HashMap<String, TDAParser>, TDAError> = file_paths
-        .par_iter()
-        .map(|path| {
-            let path_str = path.as_ref().to_string_lossy().to_string();
-            let mut parser = TDAParser::with_limits(limits.clone());
+    let workers = std::thread::available_parallelism().map_or(4, NonZeroUsize::get);
+    load_with_timeout_using(file_paths, per_file_timeout, workers, move |path| {
+        let mut parser = TDAParser::with_limits(limits.clone());
+        parser.parse_from_file(path).map(|()| parser)
+    })
+}
 
-            parser.parse_from_file(path).map(|()| (path_str, parser))
-        })
-        .collect();
+enum LoadEvent {
+    Started(usize, Instant),
+    Finished(usize, Box<TDAResult<TDAParser>>),
+}
 
-    results.map(|hashmap| {
-        let mut ahashmap = AHashMap::new();
-        for (k, v) in hashmap {
-            ahashmap.insert(k, v);
+fn load_with_timeout_using<P, F>(
+    file_paths: &[P],
+    per_file_timeout: Duration,
+    max_workers: usize,
+    load: F,
+) -> BatchLoadResult
+where
+    P: AsRef<Path>,
+    F: Fn(&Path) -> TDAResult<TDAParser> + Send + Sync + 'static,
+{
+    let paths: Arc<Vec<PathBuf>> = Arc::new(
+        file_paths
+            .iter()
+            .map(|p| p.as_ref().to_path_buf())
+            .collect(),
+    );
+    let load = Arc::new(load);
+    let next = Arc::new(AtomicUsize::new(0));
+    let (tx, rx) = mpsc::channel();
+
+    for _ in 0..max_workers.min(paths.len()) {
+        let (tx, load, next, paths) = (
+            tx.clone(),
+            Arc::clone(&load),
+            Arc::clone(&next),
+            Arc::clone(&paths),
+        );
+        let spawned = std::thread::Builder::new()
+            .name("tda-timeout-load".into())
+            .spawn(move || {
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(path) = paths.get(index) else {
+                        break;
+                    };
+                    // A closed channel means the batch already returned
+                    if tx.send(LoadEvent::Started(index, Instant::now())).is_err() {
+                        break;
+                    }
+                    let _ = tx.send(LoadEvent::Finished(index, Box::new(load(path))));
+                }
+            });
+        if let Err(e) = spawned {
+            warn!("Failed to spawn 2DA load thread: {e}");
         }
-        ahashmap
-    })
+    }
+    drop(tx);
+
+    let path_key = |index: usize| paths[index].clone();
+    let timeout_error = |index: usize| TDAError::Timeout {
+        path: paths[index].to_string_lossy().to_string(),
+        timeout_ms: per_file_timeout.as_millis() as u64,
+    };
+
+    let mut result = BatchLoadResult::default();
+    let mut started: Vec<Option<Instant>> = vec![None; paths.len()];
+    let mut settled = vec![false; paths.len()];
+    let mut remaining = paths.len();
+
+    while remaining > 0 {
+        // Wait until the earliest in-flight deadline. With nothing in flight,
+        // queued files get one timeout window to be picked up by a worker
+        // before they are written off too (all workers may be stuck).
+        let now = Instant::now();
+        let wait = started
+            .iter()
+            .zip(&settled)
+            .filter_map(|(start, done)| if *done { None } else { *start })
+            .map(|start| (start + per_file_timeout).saturating_duration_since(now))
+            .min()
+            .unwrap_or(per_file_timeout);
+
+        match rx.recv_timeout(wait) {
+            Ok(LoadEvent::Started(index, at)) => started[index] = Some(at),
+            Ok(LoadEvent::Finished(index, outcome)) => {
+                if settled[index] {
+                    continue;
+                }
+                settled[index] = true;
+                remaining -= 1;
+                result.insert(path_key(index), *outcome);
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                let now = Instant::now();
+                let in_flight = started
+                    .iter()
+                    .zip(&settled)
+                    .any(|(start, done)| !*done && start.is_some());
+
+                for index in 0..paths.len() {
+                    if settled[index] {
+                        continue;
+                    }
+                    let expired = match started[index] {
+                        Some(start) => now.duration_since(start) >= per_file_timeout,
+                        None => !in_flight,
+                    };
+                    if expired {
+                        settled[index] = true;
+                        remaining -= 1;
+                        result.errors.insert(path_key(index), timeout_error(index));
+                    }
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+    }
+
+    // Every worker is gone (none could be spawned, or they died) with files
+    // still queued; report those rather than leaving them out of the result
+    for index in (0..paths.len()).filter(|&index| !settled[index]) {
+        result.errors.insert(
+            path_key(index),
+            TDAError::IoError(std::io::Error::other(format!(
+                "no 2DA load thread was left to load '{}'",
+                paths[index].display()
+            ))),
+        );
+    }
+
+    result
 }
 
 #[cfg(test)]
@@ -390,6 +793,48 @@ Label       Name        Description
         assert_eq!(parser.get_cell_by_name(2, "Label").unwrap(), None);
     }
 
+    #[test]
+    fn test_parse_line_matches_parse_from_string() {
+        let mut whole = TDAParser::new();
+        whole.parse_from_string(SAMPLE_2DA).unwrap();
+
+        let mut streamed = TDAParser::new();
+        for line in SAMPLE_2DA.lines() {
+            streamed.parse_line(line).unwrap();
+        }
+
+        assert_eq!(streamed.format_version(), whole.format_version());
+        assert_eq!(streamed.column_names(), whole.column_names());
+        assert_eq!(streamed.row_count(), whole.row_count());
+        assert_eq!(streamed.metadata().line_count, whole.metadata().line_count);
+        for row in 0..whole.row_count() {
+            for col in 0..whole.column_count() {
+                assert_eq!(
+                    streamed.get_cell(row, col).unwrap(),
+                    whole.get_cell(row, col).unwrap()
+                );
+            }
+        }
+
+        let limits = SecurityLimits {
+            max_line_length: 16,
+            max_rows: 1,
+            ..SecurityLimits::default()
+        };
+        let mut limited = TDAParser::with_limits(limits);
+        limited.parse_line("2DA V2.0").unwrap();
+        assert!(matches!(
+            limited.parse_line(&"x".repeat(17)),
+            Err(TDAError::LineLengthExceeded { .. })
+        ));
+        limited.parse_line("Label Name").unwrap();
+        limited.parse_line("0 a b").unwrap();
+        assert!(matches!(
+            limited.parse_line("1 c d"),
+            Err(TDAError::RowCountExceeded { .. })
+        ));
+    }
+
     #[test]
     fn test_security_limits() {
         let limits = SecurityLimits {
@@ -402,4 +847,45 @@ Label       Name        Description
 
         assert!(parser.parse_from_string(&large_data).is_err());
     }
+
+    #[test]
+    fn test_iter_rows_views() {
+        use std::fmt::Write as _;
+
+        let mut content = String::from("2DA V2.0\n\n    LABEL    STRING_REF    TIMER\n");
+        for i in 0..20 {
+            writeln!(content, "{i}    ACTION_{i}    {}    0", 6000 + i).unwrap();
+        }
+
+        let mut parser = TDAParser::new();
+        parser.parse_from_string(&content).unwrap();
+
+        let zero_timers = parser
+            .iter_rows()
+            .filter(|row| row.get("timer") == Some("0") && row.get("Label").is_some())
+            .count();
+        assert_eq!(zero_timers, 20);
+
+        let row = parser.iter_rows().nth(5).unwrap();
+        assert_eq!(row.index(), 5);
+        assert_eq!(row.get_by_index(0), Some("ACTION_5"));
+        assert_eq!(row.get("STRING_REF"), Some("6005"));
+        assert_eq!(row.cells().count(), 3);
+    }
+
+    #[test]
+    fn test_load_with_timeout_reports_files_no_worker_took() {
+        let paths = ["a.2da", "b.2da", "c.2da"];
+
+        let result =
+            load_with_timeout_using(&paths, Duration::from_secs(5), 0, |_| Ok(TDAParser::new()));
+
+        assert_eq!(result.loaded.len() + result.errors.len(), paths.len());
+        assert!(
+            result
+                .errors
+                .values()
+                .all(|e| matches!(e, TDAError::IoError(_)))
+        );
+    }
 }
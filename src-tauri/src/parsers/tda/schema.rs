@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+
+use super::types::{CellValue, TDAParser, parse_int};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TdaColumnType {
+    Int,
+    Float,
+    String,
+    /// Every cell is `****` or empty, so nothing to infer from.
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TdaColumnSchema {
+    pub name: String,
+    pub column_type: TdaColumnType,
+    pub nullable: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TdaSchema {
+    pub format_version: String,
+    pub columns: Vec<TdaColumnSchema>,
+}
+
+impl TDAParser {
+    /// Infer a column's type from its non-null cells. Hex (`0x..`) counts as
+    /// Int; a column mixing ints and floats is Float.
+    pub fn infer_column_type(&self, col_index: usize) -> TdaColumnType {
+        let mut inferred = TdaColumnType::Unknown;
+
+        for value in self.iter_column(col_index).flatten() {
+            if value.is_empty() {
+                continue;
+            }
+            let cell_type = if parse_int(value).is_some() {
+                TdaColumnType::Int
+            } else if value.parse::<f64>().is_ok() {
+                TdaColumnType::Float
+            } else {
+                return TdaColumnType::String;
+            };
+
+            inferred = match (inferred, cell_type) {
+                (TdaColumnType::Unknown, t) => t,
+                (TdaColumnType::Int, TdaColumnType::Int) => TdaColumnType::Int,
+                _ => TdaColumnType::Float,
+            };
+        }
+
+        inferred
+    }
+
+    /// Column names, inferred types and nullability, for feeding code generators.
+    /// A column is nullable if any row holds `****` or is missing the cell.
+    pub fn schema(&self) -> TdaSchema {
+        let columns = self
+            .column_names()
+            .into_iter()
+            .enumerate()
+            .map(|(index, name)| TdaColumnSchema {
+                name: name.to_string(),
+                column_type: self.infer_column_type(index),
+                nullable: self.rows().iter().any(|row| {
+                    row.get(index)
+                        .is_none_or(|cell| matches!(cell, CellValue::Null | CellValue::Empty))
+                }),
+            })
+            .collect();
+
+        TdaSchema {
+            format_version: self.format_version().to_string(),
+            columns,
+        }
+    }
+}
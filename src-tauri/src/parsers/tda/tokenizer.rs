@@ -1,23 +1,72 @@
+use std::ops::Range;
+
 use smallvec::SmallVec;
 
 use super::error::{TDAError, TDAResult};
 
+/// How fields on a line are delimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SeparatorMode {
+    /// Runs of spaces/tabs separate fields; empty fields cannot occur.
+    Whitespace,
+    /// Each tab separates a field, so consecutive tabs yield empty fields.
+    Tab,
+    /// Tab mode for lines containing a tab, whitespace mode otherwise.
+    #[default]
+    Auto,
+}
+
+/// Line tokenizer for 2DA and other BioWare whitespace-delimited text.
+///
+/// Quoting rules: a field wrapped in double quotes is taken verbatim without
+/// the quotes, so it may contain spaces; there is no escape for an embedded
+/// quote. In whitespace mode an unterminated quote is an `InvalidToken`
+/// error. Blank lines and lines starting with `#` yield no tokens. `****` is
+/// returned as an ordinary token; turning it into a null cell is up to the
+/// caller (`CellValue::new` does this for 2DA).
+#[derive(Debug)]
 pub struct TDATokenizer {
     line_number: usize,
+    separator_mode: SeparatorMode,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Token<'a> {
     pub content: &'a str,
     pub was_quoted: bool,
+    /// Byte offset in the line where the token starts, including any opening quote.
     pub position: usize,
+    /// Byte offset just past the token, including any closing quote.
+    pub end: usize,
+}
+
+impl Token<'_> {
+    /// Source bytes the token occupies in its line, for error highlighting.
+    pub fn span(&self) -> Range<usize> {
+        self.position..self.end
+    }
 }
 
 pub type LineTokens<'a> = SmallVec<[Token<'a>; 16]>;
 
 impl TDATokenizer {
     pub fn new() -> Self {
-        Self { line_number: 0 }
+        Self::with_separator_mode(SeparatorMode::default())
+    }
+
+    pub fn with_separator_mode(separator_mode: SeparatorMode) -> Self {
+        Self {
+            line_number: 0,
+            separator_mode,
+        }
+    }
+
+    pub fn set_separator_mode(&mut self, separator_mode: SeparatorMode) {
+        self.separator_mode = separator_mode;
+    }
+
+    pub fn separator_mode(&self) -> SeparatorMode {
+        self.separator_mode
     }
 
     pub fn tokenize_line<'a>(&mut self, line: &'a str) -> TDAResult<LineTokens<'a>> {
@@ -33,7 +82,13 @@ impl TDATokenizer {
         // - 719 out of 1165 files (61.7%) use tabs
         // - Important files like spells.2da, classes.2da, baseitems.2da all use tabs
         // Therefore this detection is critical for correct parsing.
-        if line.contains('\t') {
+        let use_tabs = match self.separator_mode {
+            SeparatorMode::Whitespace => false,
+            SeparatorMode::Tab => true,
+            SeparatorMode::Auto => line.contains('\t'),
+        };
+
+        if use_tabs {
             Self::tokenize_tab_separated(line)
         } else {
             self.tokenize_space_separated(line)
@@ -42,33 +97,32 @@ impl TDATokenizer {
 
     fn tokenize_tab_separated<'a>(line: &'a str) -> TDAResult<LineTokens<'a>> {
         let mut tokens = SmallVec::new();
-        let mut position = 0;
+        let mut field_start = 0;
 
         for field in line.split('\t') {
             let trimmed = field.trim();
+            let position = field_start + (field.len() - field.trim_start().len());
+            let end = position + trimmed.len();
 
-            let token = if trimmed.is_empty() {
-                Token {
-                    content: trimmed,
-                    was_quoted: false,
-                    position,
-                }
-            } else if trimmed.starts_with('"') && trimmed.ends_with('"') && trimmed.len() >= 2 {
+            let token = if trimmed.starts_with('"') && trimmed.ends_with('"') && trimmed.len() >= 2
+            {
                 Token {
                     content: &trimmed[1..trimmed.len() - 1],
                     was_quoted: true,
                     position,
+                    end,
                 }
             } else {
                 Token {
                     content: trimmed,
                     was_quoted: false,
                     position,
+                    end,
                 }
             };
             tokens.push(token);
 
-            position += field.len() + 1;
+            field_start += field.len() + 1;
         }
 
         Ok(tokens)
@@ -99,6 +153,7 @@ impl TDATokenizer {
                     content: token,
                     was_quoted: true,
                     position,
+                    end: base_position + end_pos,
                 });
 
                 while chars.peek().is_some_and(|(idx, _)| *idx < end_pos) {
@@ -110,6 +165,7 @@ impl TDATokenizer {
                     content: token,
                     was_quoted: false,
                     position,
+                    end: base_position + end_pos,
                 });
 
                 while chars.peek().is_some_and(|(idx, _)| *idx < end_pos) {
@@ -225,6 +281,41 @@ mod tests {
         assert_eq!(tokens[3].content, "col4");
     }
 
+    #[test]
+    fn test_token_spans() {
+        let line = r#"  abc "q s"  d"#;
+        let mut tokenizer = TDATokenizer::new();
+        let tokens = tokenizer.tokenize_line(line).unwrap();
+        let spans: Vec<&str> = tokens.iter().map(|t| &line[t.span()]).collect();
+        assert_eq!(spans, vec!["abc", "\"q s\"", "d"]);
+
+        let line = "a\t \"b c\" \t\td";
+        let tokens = tokenizer.tokenize_line(line).unwrap();
+        let spans: Vec<&str> = tokens.iter().map(|t| &line[t.span()]).collect();
+        assert_eq!(spans, vec!["a", "\"b c\"", "", "d"]);
+        assert_eq!(tokens[1].content, "b c");
+    }
+
+    #[test]
+    fn test_separator_modes() {
+        let line = "col1\tcol2 extra\t\tcol4";
+
+        let mut tokenizer = TDATokenizer::with_separator_mode(SeparatorMode::Whitespace);
+        let tokens = tokenizer.tokenize_line(line).unwrap();
+        assert_eq!(tokens.len(), 4);
+        assert_eq!(tokens[2].content, "extra");
+
+        tokenizer.set_separator_mode(SeparatorMode::Tab);
+        let tokens = tokenizer.tokenize_line(line).unwrap();
+        assert_eq!(tokens.len(), 4);
+        assert_eq!(tokens[1].content, "col2 extra");
+        assert_eq!(tokens[2].content, "");
+
+        let tokens = tokenizer.tokenize_line("a b").unwrap();
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].content, "a b");
+    }
+
     #[test]
     fn test_empty_line() {
         let mut tokenizer = TDATokenizer::new();
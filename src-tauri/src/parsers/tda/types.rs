@@ -4,6 +4,8 @@ use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 
 use super::error::{SecurityLimits, TDAError, TDAResult};
+use super::tokenizer::TDATokenizer;
+use crate::utils::SourceStamp;
 
 pub type Symbol = Spur;
 pub type TDAStringInterner = ThreadedRodeo;
@@ -52,12 +54,37 @@ impl CellValue {
 
 pub type TDARow = SmallVec<[CellValue; 16]>;
 
+pub(crate) fn parse_int(value: &str) -> Option<i64> {
+    match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some(hex) => i64::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ColumnInfo {
     pub name: Symbol,
     pub index: usize,
 }
 
+/// Approximate heap footprint of a `TDAParser`, by structure.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryBreakdown {
+    pub interner_bytes: usize,
+    pub rows_bytes: usize,
+    pub columns_bytes: usize,
+    pub column_map_bytes: usize,
+}
+
+impl MemoryBreakdown {
+    pub fn total(&self) -> usize {
+        self.interner_bytes + self.rows_bytes + self.columns_bytes + self.column_map_bytes
+    }
+}
+
 #[derive(Debug)]
 pub struct TDAParser {
     interner: TDAStringInterner,
@@ -66,6 +93,69 @@ pub struct TDAParser {
     rows: Vec<TDARow>,
     security_limits: SecurityLimits,
     metadata: TDAMetadata,
+    capture_row_indices: bool,
+    row_indices: Vec<Symbol>,
+    options: ParserOptions,
+    stream_stage: StreamStage,
+    /// Shared by every `parse_line` call so line numbers carry across a table
+    tokenizer: TDATokenizer,
+}
+
+/// Which line `parse_line` expects next.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum StreamStage {
+    #[default]
+    Header,
+    Columns,
+    Rows,
+}
+
+/// Parse-time layout options, separate from the `SecurityLimits` bounds.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParserOptions {
+    /// Keep the unnamed row-index column as column 0 (named `""`) instead of
+    /// dropping it. Off by default: columns start at the first named header
+    /// and each row's index token is discarded.
+    pub keep_index_column: bool,
+    /// Rename a repeated column header to `name_2`, `name_3`, ... and record
+    /// a `TDAWarning::DuplicateColumn`, instead of failing with
+    /// `TDAError::DuplicateColumn`.
+    pub rename_duplicate_columns: bool,
+    /// Handling of control characters other than tab and CR/LF, such as the
+    /// NUL bytes found in recovered files. Kept as-is by default.
+    pub control_chars: ControlCharMode,
+}
+
+/// What `ParserOptions::control_chars` does with a control character.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ControlCharMode {
+    #[default]
+    Keep,
+    /// Remove them and record a `TDAWarning::ControlCharsStripped`.
+    Strip,
+    /// Fail the parse with `TDAError::MalformedLine`.
+    Reject,
+}
+
+/// Recoverable oddities found while parsing; the table is still usable.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TDAWarning {
+    /// Header accepted but not in canonical form (tab separator, trailing text).
+    NonStandardHeader { header: String },
+    /// `2DAM` merge file rather than a full table.
+    MergeFormat,
+    /// Row had `got` cells for `expected` columns; padded or truncated.
+    RaggedRow {
+        row: usize,
+        got: usize,
+        expected: usize,
+    },
+    /// Repeated column header renamed under `rename_duplicate_columns`.
+    DuplicateColumn { name: String, renamed: String },
+    /// Leading UTF-8 byte order mark, stripped before parsing.
+    ByteOrderMark,
+    /// Control characters removed from a line under `ControlCharMode::Strip`.
+    ControlCharsStripped { line: usize, count: usize },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -73,8 +163,21 @@ pub struct TDAMetadata {
     pub file_size: usize,
     pub line_count: usize,
     pub parse_time_ns: u64,
-    pub has_warnings: bool,
+    pub warnings: Vec<TDAWarning>,
     pub format_version: String,
+    /// `content_hash` of the source file, recorded by `load_with_cache`
+    /// under `CacheValidation::ContentHash`
+    #[serde(default)]
+    pub source_hash: Option<u64>,
+    /// Size and mtime of the source file, recorded by `load_with_cache`
+    #[serde(default)]
+    pub source_stamp: Option<SourceStamp>,
+}
+
+impl TDAMetadata {
+    pub fn has_warnings(&self) -> bool {
+        !self.warnings.is_empty()
+    }
 }
 
 impl Default for TDAMetadata {
@@ -83,8 +186,10 @@ impl Default for TDAMetadata {
             file_size: 0,
             line_count: 0,
             parse_time_ns: 0,
-            has_warnings: false,
+            warnings: Vec::new(),
             format_version: "2DA V2.0".to_string(),
+            source_hash: None,
+            source_stamp: None,
         }
     }
 }
@@ -95,6 +200,10 @@ impl TDAParser {
     }
 
     pub fn with_limits(limits: SecurityLimits) -> Self {
+        Self::with_options(limits, ParserOptions::default())
+    }
+
+    pub fn with_options(limits: SecurityLimits, options: ParserOptions) -> Self {
         Self {
             interner: TDAStringInterner::default(),
             columns: Vec::new(),
@@ -102,9 +211,47 @@ impl TDAParser {
             rows: Vec::new(),
             security_limits: limits,
             metadata: TDAMetadata::default(),
+            capture_row_indices: false,
+            row_indices: Vec::new(),
+            options,
+            stream_stage: StreamStage::Header,
+            tokenizer: TDATokenizer::new(),
         }
     }
 
+    pub fn options(&self) -> &ParserOptions {
+        &self.options
+    }
+
+    /// Takes effect on the next parse.
+    pub fn set_options(&mut self, options: ParserOptions) {
+        self.options = options;
+    }
+
+    /// Keep each row's original index token (normally dropped) so rows can be
+    /// resolved by declared index. Takes effect on the next parse.
+    pub fn set_capture_row_indices(&mut self, capture: bool) {
+        self.capture_row_indices = capture;
+    }
+
+    pub fn captures_row_indices(&self) -> bool {
+        self.capture_row_indices
+    }
+
+    /// Original row-index token for a positional row, if indices were captured.
+    pub fn original_row_index(&self, row_index: usize) -> Option<&str> {
+        self.row_indices
+            .get(row_index)
+            .map(|symbol| self.interner.resolve(symbol))
+    }
+
+    /// Positional row for a declared row index like `"42"`. Differs from the
+    /// declared index in tables with gaps; requires captured row indices.
+    pub fn row_by_original_index(&self, index: &str) -> Option<usize> {
+        let symbol = self.interner.get(index)?;
+        self.row_indices.iter().position(|s| *s == symbol)
+    }
+
     #[cfg(test)]
     pub fn add_column(&mut self, name: &str) {
         let index = self.columns.len();
@@ -177,6 +324,43 @@ impl TDAParser {
         self.get_cell(row_index, col_index)
     }
 
+    /// Integer cell, accepting `0x` hex as some 2DAs use for flags.
+    /// `****` and empty cells are `Ok(None)`.
+    pub fn get_int_by_name(&self, row_index: usize, column_name: &str) -> TDAResult<Option<i64>> {
+        self.get_typed_by_name(row_index, column_name, parse_int)
+    }
+
+    pub fn get_float_by_name(&self, row_index: usize, column_name: &str) -> TDAResult<Option<f64>> {
+        self.get_typed_by_name(row_index, column_name, |v| v.parse().ok())
+    }
+
+    /// NWN2 boolean columns are `0`/`1`; anything else is a type mismatch.
+    pub fn get_bool_by_name(&self, row_index: usize, column_name: &str) -> TDAResult<Option<bool>> {
+        self.get_typed_by_name(row_index, column_name, |v| match v {
+            "0" => Some(false),
+            "1" => Some(true),
+            _ => None,
+        })
+    }
+
+    fn get_typed_by_name<T>(
+        &self,
+        row_index: usize,
+        column_name: &str,
+        parse: impl Fn(&str) -> Option<T>,
+    ) -> TDAResult<Option<T>> {
+        match self.get_cell_by_name(row_index, column_name)? {
+            None | Some("") => Ok(None),
+            Some(value) => parse(value)
+                .map(Some)
+                .ok_or_else(|| TDAError::CellTypeMismatch {
+                    row: row_index,
+                    col: column_name.to_string(),
+                    value: value.to_string(),
+                }),
+        }
+    }
+
     pub fn get_row_dict(&self, row_index: usize) -> TDAResult<AHashMap<String, Option<String>>> {
         let row = self
             .rows
@@ -240,10 +424,56 @@ impl TDAParser {
         Ok(None)
     }
 
+    /// Every `(row, col)` whose cell equals `value`. Exact matches compare
+    /// interned symbols where possible. Empty and `****` cells never match.
+    pub fn find_all(&self, value: &str, case_insensitive: bool) -> Vec<(usize, usize)> {
+        let symbol = if case_insensitive {
+            None
+        } else {
+            self.interner.get(value)
+        };
+
+        let matches_cell = |cell: &CellValue| match cell {
+            CellValue::Null | CellValue::Empty => false,
+            CellValue::Interned(s) if !case_insensitive => Some(*s) == symbol,
+            CellValue::Raw(s) if !case_insensitive => s == value,
+            _ => cell
+                .as_str(&self.interner)
+                .is_some_and(|s| s.eq_ignore_ascii_case(value)),
+        };
+
+        self.rows
+            .iter()
+            .enumerate()
+            .flat_map(|(row_index, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter(|(_, cell)| matches_cell(cell))
+                    .map(move |(col_index, _)| (row_index, col_index))
+            })
+            .collect()
+    }
+
     pub fn metadata(&self) -> &TDAMetadata {
         &self.metadata
     }
 
+    pub fn format_version(&self) -> &str {
+        &self.metadata.format_version
+    }
+
+    /// Set the header version emitted when the table is written back out.
+    /// Accepts `2DA V2.0`, `2DA V1.0`, and the `2DAMV1.0`/`2DAMV2.0` merge forms.
+    pub fn set_format_version(&mut self, version: &str) -> TDAResult<()> {
+        match version {
+            "2DA V2.0" | "2DA V1.0" | "2DAMV2.0" | "2DAMV1.0" => {
+                self.metadata.format_version = version.to_string();
+                Ok(())
+            }
+            _ => Err(TDAError::InvalidHeader(version.to_string())),
+        }
+    }
+
     pub(crate) fn metadata_mut(&mut self) -> &mut TDAMetadata {
         &mut self.metadata
     }
@@ -264,6 +494,10 @@ impl TDAParser {
         &mut self.columns
     }
 
+    pub(crate) fn column_map(&self) -> &AHashMap<String, usize> {
+        &self.column_map
+    }
+
     pub(crate) fn column_map_mut(&mut self) -> &mut AHashMap<String, usize> {
         &mut self.column_map
     }
@@ -276,6 +510,22 @@ impl TDAParser {
         &self.rows
     }
 
+    pub(crate) fn row_indices_mut(&mut self) -> &mut Vec<Symbol> {
+        &mut self.row_indices
+    }
+
+    pub(crate) fn stream_stage(&self) -> StreamStage {
+        self.stream_stage
+    }
+
+    pub(crate) fn set_stream_stage(&mut self, stage: StreamStage) {
+        self.stream_stage = stage;
+    }
+
+    pub(crate) fn tokenizer_mut(&mut self) -> &mut TDATokenizer {
+        &mut self.tokenizer
+    }
+
     pub(crate) fn interner_mut(&mut self) -> &mut TDAStringInterner {
         &mut self.interner
     }
@@ -289,26 +539,70 @@ impl TDAParser {
         self.columns.clear();
         self.column_map.clear();
         self.rows.clear();
+        self.row_indices.clear();
         self.metadata = TDAMetadata::default();
+        self.stream_stage = StreamStage::Header;
+        self.tokenizer = TDATokenizer::new();
     }
 
     pub fn memory_usage(&self) -> usize {
-        let interner_size = self.interner.len() * 32;
-        let columns_size = self.columns.len() * std::mem::size_of::<ColumnInfo>();
-        let column_map_size = self.column_map.len() * (32 + 8);
-        let rows_size = self
-            .rows
-            .iter()
-            .map(|row| row.len() * std::mem::size_of::<CellValue>())
-            .sum::<usize>();
-
-        interner_size + columns_size + column_map_size + rows_size
+        self.memory_breakdown().total()
+    }
+
+    /// Per-structure split of `memory_usage`, from the live allocations.
+    pub fn memory_breakdown(&self) -> MemoryBreakdown {
+        use std::mem::size_of;
+
+        let interner_bytes = self
+            .interner
+            .strings()
+            .map(|s| s.len() + size_of::<Symbol>() + size_of::<&str>())
+            .sum();
+        let rows_bytes = self.rows.capacity() * size_of::<TDARow>()
+            + self
+                .rows
+                .iter()
+                .map(|row| {
+                    let spilled = if row.spilled() {
+                        row.capacity() * size_of::<CellValue>()
+                    } else {
+                        0
+                    };
+                    let raw: usize = row
+                        .iter()
+                        .map(|cell| match cell {
+                            CellValue::Raw(s) => s.capacity(),
+                            _ => 0,
+                        })
+                        .sum();
+                    spilled + raw
+                })
+                .sum::<usize>();
+        let columns_bytes = self.columns.capacity() * size_of::<ColumnInfo>();
+        let column_map_bytes = self
+            .column_map
+            .keys()
+            .map(|key| key.capacity() + size_of::<String>() + size_of::<usize>())
+            .sum();
+
+        MemoryBreakdown {
+            interner_bytes,
+            rows_bytes,
+            columns_bytes,
+            column_map_bytes,
+        }
     }
 
-    pub fn iter_rows(&self) -> impl Iterator<Item = impl Iterator<Item = Option<&str>> + '_> + '_ {
+    /// Borrowing row views; nothing is cloned or allocated per row.
+    pub fn iter_rows(&self) -> impl Iterator<Item = RowView<'_>> + '_ {
         self.rows
             .iter()
-            .map(move |row| row.iter().map(move |cell| cell.as_str(&self.interner)))
+            .enumerate()
+            .map(move |(index, row)| RowView {
+                parser: self,
+                index,
+                row,
+            })
     }
 
     pub fn iter_column(&self, col_index: usize) -> impl Iterator<Item = Option<&str>> + '_ {
@@ -318,6 +612,35 @@ impl TDAParser {
         })
     }
 
+    /// Every value down one column. `****`, empty cells, and rows too short to
+    /// reach the column all come back as `None`.
+    pub fn get_column(&self, name: &str) -> TDAResult<Vec<Option<&str>>> {
+        let col_index = self
+            .find_column_index(name)
+            .ok_or_else(|| TDAError::ColumnNotFound {
+                column: name.to_string(),
+            })?;
+        self.get_column_by_index(col_index)
+    }
+
+    pub fn get_column_by_index(&self, col_index: usize) -> TDAResult<Vec<Option<&str>>> {
+        if col_index >= self.columns.len() {
+            return Err(TDAError::ColumnIndexOutOfBounds {
+                index: col_index,
+                max: self.columns.len(),
+            });
+        }
+
+        Ok(self
+            .rows
+            .iter()
+            .map(|row| match row.get(col_index) {
+                Some(CellValue::Empty) | None => None,
+                Some(cell) => cell.as_str(&self.interner),
+            })
+            .collect())
+    }
+
     pub fn iter_column_by_name(
         &self,
         column_name: &str,
@@ -327,6 +650,55 @@ impl TDAParser {
     }
 }
 
+#[derive(Clone, Copy)]
+pub struct RowView<'a> {
+    parser: &'a TDAParser,
+    index: usize,
+    row: &'a TDARow,
+}
+
+impl<'a> RowView<'a> {
+    /// Positional row index.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Case-insensitive like `get_cell_by_name`, but without lowercasing into
+    /// a fresh `String` on every call.
+    pub fn get(&self, column_name: &str) -> Option<&'a str> {
+        let col_index = match self.parser.column_map.get(column_name) {
+            Some(&index) => index,
+            None => self.parser.columns.iter().position(|col| {
+                self.parser
+                    .interner
+                    .resolve(&col.name)
+                    .eq_ignore_ascii_case(column_name)
+            })?,
+        };
+        self.get_by_index(col_index)
+    }
+
+    pub fn get_by_index(&self, col_index: usize) -> Option<&'a str> {
+        self.row
+            .get(col_index)
+            .and_then(|cell| cell.as_str(&self.parser.interner))
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = Option<&'a str>> + 'a {
+        let interner = &self.parser.interner;
+        self.row.iter().map(move |cell| cell.as_str(interner))
+    }
+}
+
+impl std::fmt::Debug for RowView<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RowView")
+            .field("index", &self.index)
+            .field("cells", &self.cells().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
 impl Default for TDAParser {
     fn default() -> Self {
         Self::new()
@@ -345,6 +717,13 @@ pub struct SerializableTDAParser {
     pub rows: Vec<Vec<SerializableCellValue>>,
     pub security_limits: SecurityLimits,
     pub metadata: TDAMetadata,
+    #[serde(default)]
+    pub options: ParserOptions,
+    #[serde(default)]
+    pub capture_row_indices: bool,
+    /// Original row-index tokens, one per row, when indices were captured
+    #[serde(default)]
+    pub row_indices: Vec<String>,
 }
 
 impl SerializableTDAParser {
@@ -378,12 +757,24 @@ impl SerializableTDAParser {
             rows,
             security_limits: SecurityLimits::default(),
             metadata: parser.metadata().clone(),
+            options: parser.options.clone(),
+            capture_row_indices: parser.capture_row_indices,
+            row_indices: parser
+                .row_indices
+                .iter()
+                .map(|symbol| parser.interner().resolve(symbol).to_string())
+                .collect(),
         }
     }
 
     pub fn to_parser(self) -> TDAParser {
-        let mut parser = TDAParser::with_limits(self.security_limits);
+        let mut parser = TDAParser::with_options(self.security_limits, self.options);
         parser.metadata = self.metadata;
+        parser.capture_row_indices = self.capture_row_indices;
+        for index in &self.row_indices {
+            let symbol = parser.interner_mut().get_or_intern(index);
+            parser.row_indices.push(symbol);
+        }
 
         for (idx, name) in self.column_names.into_iter().enumerate() {
             let symbol = parser.interner_mut().get_or_intern(&name);
@@ -0,0 +1,161 @@
+use std::fmt::Write as _;
+use std::path::Path;
+
+use super::error::TDAResult;
+use super::types::{CellValue, TDAParser};
+
+// BioWare pads every column to its widest entry plus a few spaces
+const COLUMN_GAP: usize = 4;
+
+fn quote_if_needed(value: &str) -> String {
+    if value.is_empty() {
+        "****".to_string()
+    } else if value.chars().any(char::is_whitespace) {
+        format!("\"{value}\"")
+    } else {
+        value.to_string()
+    }
+}
+
+fn push_padded(line: &mut String, value: &str, width: usize) {
+    let _ = write!(line, "{value:<width$}", width = width + COLUMN_GAP);
+}
+
+impl TDAParser {
+    /// Serialize back to 2DA text using the parsed `format_version` header.
+    /// Row indices are the originals when captured (or kept as an index
+    /// column), otherwise sequential.
+    /// Null and empty cells are both written as `****`.
+    pub fn to_2da_string(&self) -> String {
+        // A kept index column is written back as the row index, not a cell
+        let skip = self.kept_index_columns();
+
+        let header: Vec<String> = self
+            .column_names()
+            .into_iter()
+            .skip(skip)
+            .map(quote_if_needed)
+            .collect();
+
+        let rows: Vec<(String, Vec<String>)> = self
+            .rows()
+            .iter()
+            .enumerate()
+            .map(|(row_index, row)| {
+                let index = self.row_index_label(row_index, skip);
+                let cells = (skip..skip + header.len())
+                    .map(|col| match row.get(col) {
+                        Some(CellValue::Null | CellValue::Empty) | None => "****".to_string(),
+                        Some(cell) => quote_if_needed(cell.as_str(self.interner()).unwrap_or("")),
+                    })
+                    .collect();
+                (index, cells)
+            })
+            .collect();
+
+        let index_width = rows.iter().map(|(i, _)| i.len()).max().unwrap_or(0);
+        let widths: Vec<usize> = header
+            .iter()
+            .enumerate()
+            .map(|(col, name)| {
+                rows.iter()
+                    .map(|(_, cells)| cells[col].len())
+                    .max()
+                    .unwrap_or(0)
+                    .max(name.len())
+            })
+            .collect();
+
+        let mut out = String::new();
+        out.push_str(self.format_version());
+        out.push_str("\r\n\r\n");
+
+        let mut write_line = |first: &str, cells: &[String]| {
+            let mut line = String::new();
+            push_padded(&mut line, first, index_width);
+            for (cell, width) in cells.iter().zip(&widths) {
+                push_padded(&mut line, cell, *width);
+            }
+            out.push_str(line.trim_end());
+            out.push_str("\r\n");
+        };
+
+        write_line("", &header);
+        for (index, cells) in &rows {
+            write_line(index, cells);
+        }
+
+        out
+    }
+
+    pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> TDAResult<()> {
+        std::fs::write(path, self.to_2da_string())?;
+        Ok(())
+    }
+
+    /// Export for spreadsheets: a header row of column names, then one line
+    /// per row, CRLF-terminated. Fields holding the delimiter, a quote, or a
+    /// line break are quoted per RFC 4180. Null cells are written as `****`
+    /// and empty cells as an empty field. With `include_index` the first
+    /// column (headed `""`) holds the row index `to_2da_string` would write.
+    pub fn to_csv(&self, delimiter: u8, include_index: bool) -> String {
+        let delimiter = char::from(delimiter);
+        let skip = self.kept_index_columns();
+
+        let mut out = String::new();
+        let mut write_record = |index: Option<String>, fields: Vec<&str>| {
+            let record: Vec<String> = index
+                .into_iter()
+                .chain(fields.into_iter().map(str::to_string))
+                .map(|field| csv_quote(&field, delimiter))
+                .collect();
+            out.push_str(&record.join(&delimiter.to_string()));
+            out.push_str("\r\n");
+        };
+
+        let header = self.column_names().into_iter().skip(skip).collect();
+        write_record(include_index.then(String::new), header);
+
+        for (row_index, row) in self.rows().iter().enumerate() {
+            let fields = (skip..self.column_count())
+                .map(|col| match row.get(col) {
+                    Some(CellValue::Null) | None => "****",
+                    Some(cell) => cell.as_str(self.interner()).unwrap_or(""),
+                })
+                .collect();
+            let index = include_index.then(|| self.row_index_label(row_index, skip));
+            write_record(index, fields);
+        }
+
+        out
+    }
+
+    /// 1 when column 0 is a kept row-index column, else 0.
+    fn kept_index_columns(&self) -> usize {
+        usize::from(self.options().keep_index_column && self.column_names().first() == Some(&""))
+    }
+
+    /// Captured original index, else the kept index cell, else the position.
+    fn row_index_label(&self, row_index: usize, skip: usize) -> String {
+        self.original_row_index(row_index)
+            .or_else(|| {
+                (skip == 1)
+                    .then(|| {
+                        self.rows()[row_index]
+                            .first()
+                            .and_then(|cell| cell.as_str(self.interner()))
+                    })
+                    .flatten()
+                    .filter(|index| !index.is_empty())
+            })
+            .map_or_else(|| row_index.to_string(), str::to_string)
+    }
+}
+
+fn csv_quote(field: &str, delimiter: char) -> String {
+    if field.contains([delimiter, '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
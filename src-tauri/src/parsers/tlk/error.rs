@@ -47,6 +47,12 @@ pub enum TLKError {
     #[error("Memory mapping error: {0}")]
     MemoryMapError(String),
 
+    #[error("String {str_ref} has characters {encoding} cannot represent")]
+    UnencodableString {
+        str_ref: usize,
+        encoding: &'static str,
+    },
+
     #[error("Security violation: {message}")]
     SecurityViolation { message: String },
 
@@ -1,10 +1,12 @@
 pub mod error;
 pub mod parser;
 pub mod types;
+mod writer;
 
 pub use error::{SecurityLimits, TLKError, TLKResult};
-pub use parser::load_multiple_files;
+pub use parser::{BatchLoadResult, load_multiple_files};
 pub use types::{
-    BatchMetrics, BatchStringResult, FileMetadata, ParserStatistics, SearchOptions, SearchResult,
-    SerializableTLKParser, TLKHeader, TLKParser, TLKStringEntry,
+    BatchMetrics, BatchStringResult, FileMetadata, ParserStatistics, SearchMode, SearchOptions,
+    SearchResult, SerializableTLKParser, TLKHeader, TLKParser, TLKStringEntry, TlkChange,
+    TlkDecodeError, TlkDiff, ValidationReport,
 };
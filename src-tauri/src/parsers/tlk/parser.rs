@@ -1,18 +1,26 @@
 use super::error::{SecurityLimits, TLKError, TLKResult};
 use super::types::{
-    BatchMetrics, BatchStringResult, CachedString, SearchOptions, SearchResult,
-    SerializableTLKParser, TLKHeader, TLKParser, TLKStringEntry,
+    BatchMetrics, BatchStringResult, CachedString, SearchMode, SearchOptions, SearchResult,
+    SerializableTLKParser, TLKHeader, TLKParser, TLKStringEntry, TlkChange, TlkDecodeError,
+    TlkDiff, ValidationReport,
 };
+use crate::parsers::batch;
+use crate::utils::CacheValidation;
 use byteorder::{LittleEndian, ReadBytesExt};
+use encoding_rs::{
+    BIG5, DecoderResult, EUC_KR, Encoding, GBK, SHIFT_JIS, UTF_8, WINDOWS_1250, WINDOWS_1252,
+};
 use flate2::{Compression, read::GzDecoder, write::GzEncoder};
-use rayon::prelude::*;
+use memmap2::Mmap;
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::Write;
-use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::io::{Cursor, Read};
 use std::path::Path;
 use std::time::Instant;
 
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
 impl TLKParser {
     /// Parse TLK data from a file path
     pub fn parse_from_file<P: AsRef<Path>>(&mut self, path: P) -> TLKResult<()> {
@@ -44,13 +52,88 @@ impl TLKParser {
         Ok(())
     }
 
-    /// Parse TLK data from byte buffer
+    /// Parse TLK data from byte buffer. A leading UTF-8 BOM, as some
+    /// editors add on save, is skipped and flagged in `metadata`.
     pub fn parse_from_bytes(&mut self, data: &[u8]) -> TLKResult<()> {
         let start_time = Instant::now();
 
         // Clear existing state
         self.clear();
 
+        let data = self.strip_bom(data);
+
+        let string_data_start = self.parse_table(data)?;
+        self.string_data = data[string_data_start..].to_vec();
+
+        self.finish_parse(start_time)
+    }
+
+    /// Like `parse_from_file`, but memory-maps the file and only parses the
+    /// header and entry table up front. String bytes stay in the mapping and
+    /// are decoded on `get_string`, so sparse lookups into a large
+    /// dialog.tlk never read most of it. `set_string` copies the string data
+    /// out of the mapping first.
+    pub fn parse_from_file_mmap<P: AsRef<Path>>(&mut self, path: P) -> TLKResult<()> {
+        let path = path.as_ref();
+        let start_time = Instant::now();
+
+        let file = File::open(path)?;
+        let file_size = file.metadata()?.len() as usize;
+        self.security_limits.validate_file_size(file_size)?;
+
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        self.clear();
+        let data = self.strip_bom(&mmap);
+        self.parse_table(data)?;
+        self.string_mmap = Some(mmap);
+
+        self.metadata.file_path = Some(path.to_string_lossy().to_string());
+        self.metadata.file_size = file_size;
+
+        self.finish_parse(start_time)
+    }
+
+    /// Whether string bytes are read lazily from a memory-mapped file.
+    pub fn is_memory_mapped(&self) -> bool {
+        self.string_mmap.is_some()
+    }
+
+    /// The string data section, from the mapping or `string_data`.
+    pub(crate) fn string_bytes(&self) -> &[u8] {
+        match (&self.string_mmap, &self.header) {
+            (Some(mmap), Some(header)) => mmap
+                .strip_prefix(UTF8_BOM)
+                .unwrap_or(mmap)
+                .get(header.string_data_offset as usize..)
+                .unwrap_or_default(),
+            _ => &self.string_data,
+        }
+    }
+
+    /// Copy mapped string data into `string_data` so it can be appended to.
+    pub(crate) fn detach_string_mmap(&mut self) {
+        if self.string_mmap.is_some() {
+            self.string_data = self.string_bytes().to_vec();
+            self.string_mmap = None;
+        }
+    }
+
+    /// Skip a leading UTF-8 BOM, flagging it in `metadata`.
+    fn strip_bom<'a>(&mut self, data: &'a [u8]) -> &'a [u8] {
+        match data.strip_prefix(UTF8_BOM) {
+            Some(rest) => {
+                self.metadata.had_bom = true;
+                self.metadata.has_warnings = true;
+                rest
+            }
+            None => data,
+        }
+    }
+
+    /// Header and entry table; sets `header` and returns where string data
+    /// starts in `data`.
+    fn parse_table(&mut self, data: &[u8]) -> TLKResult<usize> {
         // Minimum file size check
         if data.len() < 20 {
             return Err(TLKError::FileTooShort {
@@ -69,9 +152,9 @@ impl TLKParser {
         // Parse string table entries
         self.parse_string_entries(&mut cursor, header.string_count as usize)?;
 
-        // Extract string data section
+        // Locate string data section
         let string_data_start = header.string_data_offset as usize;
-        if string_data_start >= data.len() {
+        if string_data_start > data.len() {
             return Err(TLKError::CorruptedStringEntry {
                 index: 0,
                 offset: header.string_data_offset,
@@ -79,9 +162,11 @@ impl TLKParser {
             });
         }
 
-        self.string_data = data[string_data_start..].to_vec();
         self.header = Some(header);
+        Ok(string_data_start)
+    }
 
+    fn finish_parse(&mut self, start_time: Instant) -> TLKResult<()> {
         // Pre-cache frequently accessed strings (first 100)
         self.pre_cache_strings(100)?;
 
@@ -178,8 +263,7 @@ impl TLKParser {
         self.security_limits
             .validate_string_size(string_size as usize)?;
 
-        // Skip reserved bytes (4 bytes)
-        cursor.seek(SeekFrom::Current(4))?;
+        let sound_length = cursor.read_f32::<LittleEndian>()?;
 
         Ok(TLKStringEntry {
             flags,
@@ -188,6 +272,7 @@ impl TLKParser {
             pitch_variance,
             data_offset,
             string_size,
+            sound_length,
         })
     }
 
@@ -196,7 +281,7 @@ impl TLKParser {
         let cache_count = count.min(self.entries.len());
 
         for i in 0..cache_count {
-            if let Some(string) = self.get_string_internal(i)? {
+            if let Some(string) = self.decode_and_record(i)? {
                 let symbol = self.interner.get_or_intern(&string);
                 self.string_cache.insert(
                     i,
@@ -213,8 +298,7 @@ impl TLKParser {
 
     /// Get a string by reference ID (main public method)
     pub fn get_string(&mut self, str_ref: usize) -> TLKResult<Option<String>> {
-        // Check bounds
-        if str_ref >= self.entries.len() {
+        if !self.is_loaded() || str_ref >= self.entries.len() {
             return Ok(None);
         }
 
@@ -225,7 +309,7 @@ impl TLKParser {
         }
 
         // Load from string data
-        let result = self.get_string_internal(str_ref)?;
+        let result = self.decode_and_record(str_ref)?;
 
         // Cache the result if successful
         if let Some(ref string) = result {
@@ -244,6 +328,58 @@ impl TLKParser {
 
     /// Internal string retrieval without caching
     fn get_string_internal(&self, str_ref: usize) -> TLKResult<Option<String>> {
+        Ok(self.decode_entry(str_ref)?.map(|(string, _)| string))
+    }
+
+    /// Like `get_string_internal`, but notes the entry in `decode_errors` if
+    /// its bytes did not decode cleanly
+    fn decode_and_record(&mut self, str_ref: usize) -> TLKResult<Option<String>> {
+        let Some((string, invalid_at)) = self.decode_entry(str_ref)? else {
+            return Ok(None);
+        };
+        if let Some(invalid_at) = invalid_at
+            && let Err(pos) = self
+                .decode_errors
+                .binary_search_by_key(&str_ref, |e| e.str_ref)
+        {
+            let offset = self.entries[str_ref].data_offset as usize + invalid_at;
+            self.decode_errors
+                .insert(pos, TlkDecodeError { str_ref, offset });
+        }
+        Ok(Some(string))
+    }
+
+    /// Check every entry's offsets and encoding in one pass without touching
+    /// the string cache or `decode_errors`. An unloaded parser reports
+    /// nothing.
+    pub fn validate_all(&self) -> TLKResult<ValidationReport> {
+        let mut report = ValidationReport::default();
+        if !self.is_loaded() {
+            return Ok(report);
+        }
+
+        for (str_ref, entry) in self.entries.iter().enumerate() {
+            if !entry.is_present() {
+                report.absent += 1;
+                continue;
+            }
+            report.present += 1;
+            match self.decode_entry(str_ref)? {
+                None => report.out_of_bounds.push(str_ref),
+                Some((_, Some(invalid_at))) => report.decode_failures.push(TlkDecodeError {
+                    str_ref,
+                    offset: entry.data_offset as usize + invalid_at,
+                }),
+                Some((_, None)) => {}
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Decoded text plus, if the bytes were not clean, the position of the
+    /// first bad byte relative to the start of the string
+    fn decode_entry(&self, str_ref: usize) -> TLKResult<Option<(String, Option<usize>)>> {
         if str_ref >= self.entries.len() {
             return Ok(None);
         }
@@ -252,82 +388,166 @@ impl TLKParser {
 
         // Check if string is present
         if !entry.is_present() {
-            return Ok(Some(String::new())); // Not present strings return empty string
+            return Ok(Some((String::new(), None))); // Not present strings return empty string
         }
 
         // Check for zero-length strings
         if entry.string_size == 0 {
-            return Ok(Some(String::new()));
+            return Ok(Some((String::new(), None)));
         }
 
         // Validate bounds
         let start = entry.data_offset as usize;
         let end = start + entry.string_size as usize;
 
-        if end > self.string_data.len() {
+        let string_data = self.string_bytes();
+        if end > string_data.len() {
             // Note: We can't modify stats here as it's immutable, but that's okay for error reporting
             return Ok(None); // Corrupted entry
         }
 
         // Extract string bytes
-        let string_bytes = &self.string_data[start..end];
-
-        // Convert to UTF-8 string
-        match String::from_utf8(string_bytes.to_vec()) {
-            Ok(string) => Ok(Some(string)),
-            Err(_e) => {
-                // Try to recover with lossy conversion
-                let string = String::from_utf8_lossy(string_bytes).to_string();
-                // Note: We can't modify metadata here as it's immutable, but that's okay for error reporting
-                Ok(Some(string))
-            }
+        let string_bytes = &string_data[start..end];
+        let encoding = self.decoding_encoding(string_bytes);
+        let invalid_at = first_invalid_byte(encoding, string_bytes);
+        let text = encoding
+            .decode_without_bom_handling(string_bytes)
+            .0
+            .into_owned();
+
+        Ok(Some((text, invalid_at)))
+    }
+
+    /// The explicit encoding if one is set. Otherwise UTF-8 when the bytes
+    /// are valid UTF-8, else the legacy code page for the header's
+    /// `language_id`.
+    fn decoding_encoding(&self, bytes: &[u8]) -> &'static Encoding {
+        if let Some(encoding) = self.encoding {
+            return encoding;
+        }
+
+        if std::str::from_utf8(bytes).is_ok() {
+            return UTF_8;
+        }
+
+        let language_id = self.header.as_ref().map_or(0, |h| h.language_id);
+        language_encoding(language_id)
+    }
+
+    /// Force a string encoding instead of auto-detecting from `language_id`.
+    /// Also used by `set_string` when encoding new text. Drops cached strings
+    /// decoded under the previous setting.
+    pub fn set_encoding(&mut self, encoding: &'static Encoding) {
+        self.encoding = Some(encoding);
+        self.string_cache.clear();
+        self.decode_errors.clear();
+        if self.search_index.is_some() {
+            self.build_search_index();
         }
     }
 
+    /// The explicit encoding, or `None` when auto-detecting.
+    pub fn encoding(&self) -> Option<&'static Encoding> {
+        self.encoding
+    }
+
     /// Get multiple strings in one batch operation (high performance)
     pub fn get_strings_batch(&mut self, str_refs: &[usize]) -> TLKResult<BatchStringResult> {
-        let start_time = Instant::now();
         let mut strings = HashMap::new();
         let mut errors = HashMap::new();
+
+        let metrics = self.lookup_batch(str_refs, |str_ref, result| match result {
+            Ok(Some(string)) => {
+                strings.insert(str_ref, string);
+            }
+            Ok(None) => {
+                errors.insert(str_ref, "String not found".to_string());
+            }
+            Err(e) => {
+                errors.insert(str_ref, e.to_string());
+            }
+        });
+
+        Ok(BatchStringResult {
+            strings,
+            errors,
+            metrics,
+        })
+    }
+
+    /// Batch lookup aligned to the input: one entry per `str_refs` element,
+    /// `None` where the ref is out of bounds or its text lies past the string
+    /// data. Fails with the first lookup error instead of dropping it.
+    pub fn get_strings_batch_ordered(
+        &mut self,
+        str_refs: &[usize],
+    ) -> TLKResult<Vec<Option<String>>> {
+        let mut strings = Vec::with_capacity(str_refs.len());
+        let mut first_error = None;
+        self.lookup_batch(str_refs, |_, result| match result {
+            Ok(string) => strings.push(string),
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        });
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(strings),
+        }
+    }
+
+    /// Shared loop behind `get_strings_batch` and `get_strings_batch_ordered`.
+    fn lookup_batch(
+        &mut self,
+        str_refs: &[usize],
+        mut record: impl FnMut(usize, TLKResult<Option<String>>),
+    ) -> BatchMetrics {
+        let start_time = Instant::now();
         let mut cache_hits = 0;
         let mut cache_misses = 0;
         let mut bytes_read = 0;
 
         for &str_ref in str_refs {
-            match self.get_string(str_ref) {
-                Ok(Some(string)) => {
-                    strings.insert(str_ref, string);
-                    if self.string_cache.contains_key(&str_ref) {
-                        cache_hits += 1;
-                    } else {
-                        cache_misses += 1;
-                        if str_ref < self.entries.len() {
-                            bytes_read += self.entries[str_ref].string_size as usize;
-                        }
-                    }
-                }
-                Ok(None) => {
-                    errors.insert(str_ref, "String not found".to_string());
-                }
-                Err(e) => {
-                    errors.insert(str_ref, e.to_string());
+            let was_cached = self.string_cache.contains_key(&str_ref);
+            let result = self.get_string(str_ref);
+            if matches!(result, Ok(Some(_))) {
+                if was_cached {
+                    cache_hits += 1;
+                } else {
+                    cache_misses += 1;
+                    bytes_read += self.entries[str_ref].string_size as usize;
                 }
             }
+            record(str_ref, result);
         }
 
         let elapsed = start_time.elapsed();
-        let metrics = BatchMetrics {
+        BatchMetrics {
             total_time_ms: elapsed.as_secs_f64() * 1000.0,
             cache_hits,
             cache_misses,
             bytes_read,
-        };
+        }
+    }
 
-        Ok(BatchStringResult {
-            strings,
-            errors,
-            metrics,
-        })
+    /// Decode and lowercase every entry once so case-insensitive searches
+    /// scan the index instead of decoding each string per query. Kept in
+    /// sync by `set_string`; dropped on re-parse.
+    pub fn build_search_index(&mut self) {
+        let index = (0..self.entries.len())
+            .map(|i| {
+                self.get_string_internal(i)
+                    .ok()
+                    .flatten()
+                    .map(|s| s.to_lowercase())
+                    .unwrap_or_default()
+            })
+            .collect();
+        self.search_index = Some(index);
+    }
+
+    pub fn has_search_index(&self) -> bool {
+        self.search_index.is_some()
     }
 
     /// Search for strings containing the given text
@@ -337,35 +557,66 @@ impl TLKParser {
         options: &SearchOptions,
     ) -> TLKResult<Vec<SearchResult>> {
         let mut results = Vec::new();
+        if !self.is_loaded() {
+            return Ok(results);
+        }
+
         let search_text_processed = if options.case_sensitive {
             search_text.to_string()
         } else {
             search_text.to_lowercase()
         };
 
+        let query_tokens: Vec<&str> = search_text_processed.split_whitespace().collect();
+        let score_content = |content_processed: &str| match options.mode {
+            SearchMode::Substring => content_processed
+                .contains(&search_text_processed)
+                .then(|| calculate_match_score(content_processed, &search_text_processed)),
+            SearchMode::Tokenized => calculate_token_score(content_processed, &query_tokens),
+        };
+
         let entry_count = self.entries.len();
         for i in 0..entry_count {
             if results.len() >= options.max_results {
                 break;
             }
 
-            if let Ok(Some(content)) = self.get_string(i) {
+            let indexed = self
+                .search_index
+                .as_ref()
+                .filter(|_| !options.case_sensitive)
+                .and_then(|index| index.get(i));
+
+            let (score, content) = if let Some(lowered) = indexed {
+                // Only matches pay for a full string fetch
+                let Some(score) = score_content(lowered) else {
+                    continue;
+                };
+                let Ok(Some(content)) = self.get_string(i) else {
+                    continue;
+                };
+                (score, content)
+            } else {
+                let Ok(Some(content)) = self.get_string(i) else {
+                    continue;
+                };
                 let content_processed = if options.case_sensitive {
                     content.clone()
                 } else {
                     content.to_lowercase()
                 };
+                let Some(score) = score_content(&content_processed) else {
+                    continue;
+                };
+                (score, content)
+            };
 
-                if content_processed.contains(&search_text_processed) {
-                    let score = calculate_match_score(&content_processed, &search_text_processed);
-                    if score >= options.min_score {
-                        results.push(SearchResult {
-                            str_ref: i,
-                            content,
-                            score,
-                        });
-                    }
-                }
+            if score >= options.min_score {
+                results.push(SearchResult {
+                    str_ref: i,
+                    content,
+                    score,
+                });
             }
         }
 
@@ -379,11 +630,35 @@ impl TLKParser {
         Ok(results)
     }
 
-    /// Load with MessagePack cache support
+    /// Load with MessagePack cache support. The cache is checked against
+    /// the source's size and mtime; see `load_with_cache_validated`.
     pub fn load_with_cache<P: AsRef<Path>>(
         &mut self,
         source_path: P,
         cache_path: Option<P>,
+    ) -> TLKResult<bool> {
+        self.load_with_cache_validated(source_path, cache_path, CacheValidation::default())
+    }
+
+    /// `load_with_cache_validated` with `CacheValidation::ContentHash`, for
+    /// sources that may be copied or restored with a stale mtime.
+    pub fn load_with_cache_hashed<P: AsRef<Path>>(
+        &mut self,
+        source_path: P,
+        cache_path: P,
+    ) -> TLKResult<bool> {
+        self.load_with_cache_validated(source_path, Some(cache_path), CacheValidation::ContentHash)
+    }
+
+    /// `load_with_cache` with an explicit staleness check, e.g.
+    /// `CacheValidation::ContentHash` where mtimes can't be relied on.
+    /// Caches written before stamps were recorded fall back to comparing
+    /// the cache's mtime with the source's.
+    pub fn load_with_cache_validated<P: AsRef<Path>>(
+        &mut self,
+        source_path: P,
+        cache_path: Option<P>,
+        validation: CacheValidation,
     ) -> TLKResult<bool> {
         let source_path = source_path.as_ref();
 
@@ -391,16 +666,13 @@ impl TLKParser {
         if let Some(ref cache_path_ref) = cache_path {
             let cache_path = cache_path_ref.as_ref();
             if let Ok(cached_data) = self.load_from_cache(cache_path) {
-                // Verify cache is newer than source
-                if let Ok(source_metadata) = std::fs::metadata(source_path)
-                    && let Ok(cache_metadata) = std::fs::metadata(cache_path)
-                    && cache_metadata
-                        .modified()
-                        .unwrap_or(std::time::SystemTime::UNIX_EPOCH)
-                        >= source_metadata
-                            .modified()
-                            .unwrap_or(std::time::SystemTime::UNIX_EPOCH)
-                {
+                let fresh = validation.is_fresh(
+                    source_path,
+                    cache_path,
+                    cached_data.metadata.source_stamp,
+                    cached_data.metadata.source_hash,
+                );
+                if fresh {
                     *self = cached_data;
                     return Ok(true); // Loaded from cache
                 }
@@ -413,6 +685,8 @@ impl TLKParser {
         // Save to cache if path provided
         if let Some(ref cache_path_ref) = cache_path {
             let cache_path = cache_path_ref.as_ref();
+            (self.metadata.source_stamp, self.metadata.source_hash) =
+                validation.record(source_path);
             let _ = self.save_to_cache(cache_path); // Ignore cache save errors
         }
 
@@ -461,6 +735,36 @@ impl TLKParser {
         Ok(batch_result.strings)
     }
 
+    /// Compare decoded strings against `other` over the union of both ref
+    /// ranges. Refs past the end of this table are `Added`, refs past the
+    /// end of `other` are `Removed`.
+    pub fn diff(&mut self, other: &mut TLKParser) -> TLKResult<Vec<TlkDiff>> {
+        let mut diffs = Vec::new();
+        let shared = self.entries.len().min(other.entries.len());
+
+        for str_ref in 0..self.entries.len().max(other.entries.len()) {
+            let change = if str_ref < shared {
+                let old = self.get_string(str_ref)?.unwrap_or_default();
+                let new = other.get_string(str_ref)?.unwrap_or_default();
+                if old == new {
+                    continue;
+                }
+                TlkChange::Changed { old, new }
+            } else if str_ref < self.entries.len() {
+                TlkChange::Removed {
+                    old: self.get_string(str_ref)?.unwrap_or_default(),
+                }
+            } else {
+                TlkChange::Added {
+                    new: other.get_string(str_ref)?.unwrap_or_default(),
+                }
+            };
+            diffs.push(TlkDiff { str_ref, change });
+        }
+
+        Ok(diffs)
+    }
+
     /// Find first string containing the given value
     pub fn find_string(&mut self, search_text: &str) -> TLKResult<Option<usize>> {
         let options = SearchOptions {
@@ -513,6 +817,38 @@ impl TLKParser {
     }
 }
 
+/// Legacy code page for a TLK `language_id`
+fn language_encoding(language_id: u32) -> &'static Encoding {
+    match language_id {
+        5 => WINDOWS_1250, // Polish
+        128 => EUC_KR,     // Korean
+        129 => BIG5,       // Chinese (Traditional)
+        130 => GBK,        // Chinese (Simplified)
+        131 => SHIFT_JIS,  // Japanese
+        _ => WINDOWS_1252, // English, French, German, Italian, Spanish
+    }
+}
+
+/// Position of the first byte `encoding` cannot decode, if any
+fn first_invalid_byte(encoding: &'static Encoding, bytes: &[u8]) -> Option<usize> {
+    if encoding == UTF_8 {
+        return std::str::from_utf8(bytes).err().map(|e| e.valid_up_to());
+    }
+
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let mut out = String::with_capacity(
+        decoder
+            .max_utf8_buffer_length_without_replacement(bytes.len())
+            .unwrap_or(bytes.len() * 3),
+    );
+    match decoder.decode_to_string_without_replacement(bytes, &mut out, true) {
+        (DecoderResult::Malformed(bad, pushed_back), read) => {
+            Some(read - usize::from(bad) - usize::from(pushed_back))
+        }
+        _ => None,
+    }
+}
+
 /// Extract null-terminated string from byte array
 fn extract_null_terminated_string(bytes: &[u8]) -> Option<String> {
     let null_pos = bytes.iter().position(|&b| b == 0)?;
@@ -549,28 +885,52 @@ fn calculate_match_score(content: &str, search_text: &str) -> f32 {
     (matches as f32 / max_possible as f32).min(0.7)
 }
 
-/// Parallel loading of multiple TLK files
-pub fn load_multiple_files(
-    paths: &[&str],
-    limits: Option<SecurityLimits>,
-) -> TLKResult<HashMap<String, TLKParser>> {
-    let results: Result<Vec<_>, _> = paths
-        .par_iter()
-        .map(|&path| {
-            let mut parser = if let Some(ref limits) = limits {
-                TLKParser::with_limits(limits.clone())
-            } else {
-                TLKParser::new()
-            };
+/// Score a tokenized query: fraction of query words present, up to 0.8, plus
+/// up to 0.2 for consecutive query words found side by side. `None` when no
+/// query word occurs at all.
+fn calculate_token_score(content: &str, query_tokens: &[&str]) -> Option<f32> {
+    let present = query_tokens
+        .iter()
+        .filter(|token| content.contains(*token))
+        .count();
+    if present == 0 {
+        return None;
+    }
 
-            parser
-                .parse_from_file(path)
-                .map(|()| (path.to_string(), parser))
-        })
-        .collect();
+    let coverage = present as f32 / query_tokens.len() as f32;
+    let pairs = query_tokens.len().saturating_sub(1);
+    let proximity = if pairs == 0 {
+        1.0
+    } else {
+        let content_words: Vec<&str> = content
+            .split_whitespace()
+            .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()))
+            .collect();
+        let adjacent = query_tokens
+            .windows(2)
+            .filter(|pair| content_words.windows(2).any(|words| words == *pair))
+            .count();
+        adjacent as f32 / pairs as f32
+    };
+
+    Some(coverage * 0.8 + proximity * coverage * 0.2)
+}
 
-    match results {
-        Ok(parsers) => Ok(parsers.into_iter().collect()),
-        Err(e) => Err(e),
-    }
+pub type BatchLoadResult = batch::BatchLoadResult<TLKParser, TLKError>;
+
+/// Parallel loading of multiple TLK files, one result per file; see
+/// `batch::load_multiple`.
+pub fn load_multiple_files<P: AsRef<Path> + Sync>(
+    paths: &[P],
+    limits: Option<SecurityLimits>,
+    max_threads: Option<usize>,
+) -> BatchLoadResult {
+    batch::load_multiple(paths, max_threads, |path| {
+        let mut parser = if let Some(ref limits) = limits {
+            TLKParser::with_limits(limits.clone())
+        } else {
+            TLKParser::new()
+        };
+        parser.parse_from_file(path).map(|()| parser)
+    })
 }
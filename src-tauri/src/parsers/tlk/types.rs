@@ -1,7 +1,11 @@
+use encoding_rs::Encoding;
 use lasso::{Key, Rodeo, Spur};
+use memmap2::Mmap;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::utils::SourceStamp;
+
 /// TLK file header information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TLKHeader {
@@ -32,6 +36,9 @@ pub struct TLKStringEntry {
     pub data_offset: u32,
     /// Size of string data in bytes
     pub string_size: u32,
+    /// Voiceover length in seconds
+    #[serde(default)]
+    pub sound_length: f32,
 }
 
 impl TLKStringEntry {
@@ -66,14 +73,35 @@ pub struct TLKParser {
     pub string_cache: HashMap<usize, CachedString>,
     /// String interner for memory efficiency
     pub interner: Rodeo,
-    /// Raw string data (loaded once, indexed by entries)
+    /// Raw string data (loaded once, indexed by entries). Empty while
+    /// `string_mmap` is set.
     pub string_data: Vec<u8>,
+    /// Whole file mapped by `parse_from_file_mmap`; string bytes are read
+    /// from it on demand instead of being copied into `string_data`
+    pub(crate) string_mmap: Option<Mmap>,
     /// Security limits
     pub security_limits: super::error::SecurityLimits,
     /// Parser statistics
     pub stats: ParserStatistics,
     /// File metadata
     pub metadata: FileMetadata,
+    /// Lowercased text per entry, built on demand by `build_search_index`
+    pub(crate) search_index: Option<Vec<String>>,
+    /// Explicit string encoding; `None` auto-detects from `language_id`
+    pub(crate) encoding: Option<&'static Encoding>,
+    /// Entries seen so far whose bytes did not decode cleanly, by `str_ref`
+    pub(crate) decode_errors: Vec<TlkDecodeError>,
+}
+
+/// A string whose bytes are not valid in the encoding in effect: UTF-8 when
+/// auto-detecting (the text was recovered through the legacy code page), or
+/// the explicit encoding otherwise
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TlkDecodeError {
+    /// String reference ID
+    pub str_ref: usize,
+    /// Offset of the first undecodable byte within the string data section
+    pub offset: usize,
 }
 
 /// Statistics about parser performance and memory usage
@@ -124,6 +152,16 @@ pub struct FileMetadata {
     pub language_id: u32,
     /// File path (if loaded from file)
     pub file_path: Option<String>,
+    /// A UTF-8 byte order mark preceded the header and was skipped
+    #[serde(default)]
+    pub had_bom: bool,
+    /// `content_hash` of the source file, recorded by `load_with_cache`
+    /// under `CacheValidation::ContentHash`
+    #[serde(default)]
+    pub source_hash: Option<u64>,
+    /// Size and mtime of the source file, recorded by `load_with_cache`
+    #[serde(default)]
+    pub source_stamp: Option<SourceStamp>,
 }
 
 /// Serializable version of TLKParser for caching
@@ -155,9 +193,13 @@ impl TLKParser {
             string_cache: HashMap::new(),
             interner: Rodeo::default(),
             string_data: Vec::new(),
+            string_mmap: None,
             security_limits: super::error::SecurityLimits::default(),
             stats: ParserStatistics::default(),
             metadata: FileMetadata::default(),
+            search_index: None,
+            encoding: None,
+            decode_errors: Vec::new(),
         }
     }
 
@@ -176,8 +218,11 @@ impl TLKParser {
         self.string_cache.clear();
         self.interner = Rodeo::default();
         self.string_data.clear();
+        self.string_mmap = None;
         self.stats = ParserStatistics::default();
         self.metadata = FileMetadata::default();
+        self.search_index = None;
+        self.decode_errors.clear();
     }
 
     /// Get total number of strings
@@ -185,7 +230,19 @@ impl TLKParser {
         self.entries.len()
     }
 
-    /// Check if parser has loaded data
+    /// Get the raw table entry (flags, sound ResRef, variances) for a string
+    pub fn get_entry(&self, str_ref: usize) -> Option<&TLKStringEntry> {
+        self.entries.get(str_ref)
+    }
+
+    /// Get the voiceover ResRef for a string, if it has one
+    pub fn get_sound_resref(&self, str_ref: usize) -> Option<&str> {
+        self.get_entry(str_ref)?.sound_resref.as_deref()
+    }
+
+    /// True once a table has been parsed (or started with `set_string`) and
+    /// holds at least one entry. False for a new parser, after `clear()`, and
+    /// for a parsed table with no entries; lookups then return nothing.
     pub fn is_loaded(&self) -> bool {
         self.header.is_some() && !self.entries.is_empty()
     }
@@ -206,6 +263,13 @@ impl TLKParser {
         &self.stats
     }
 
+    /// Entries that failed to decode cleanly, sorted by `str_ref`. Filled in
+    /// as strings are decoded (the pre-cached first 100 at parse time, the
+    /// rest on first lookup), so it grows as more of the table is read.
+    pub fn decode_errors(&self) -> &[TlkDecodeError] {
+        &self.decode_errors
+    }
+
     /// Get file metadata
     pub fn metadata(&self) -> &FileMetadata {
         &self.metadata
@@ -234,7 +298,7 @@ impl TLKParser {
         SerializableTLKParser {
             header: self.header.clone(),
             entries: self.entries.clone(),
-            string_data: self.string_data.clone(),
+            string_data: self.string_bytes().to_vec(),
             stats: self.stats.clone(),
             metadata: self.metadata.clone(),
             interner_data,
@@ -277,6 +341,26 @@ impl TLKParser {
     }
 }
 
+/// Result of `TLKParser::validate_all`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ValidationReport {
+    /// Entries flagged as present
+    pub present: usize,
+    /// Entries with the present flag clear
+    pub absent: usize,
+    /// Present entries whose text runs past the end of the string data
+    pub out_of_bounds: Vec<usize>,
+    /// Present entries whose bytes do not decode cleanly
+    pub decode_failures: Vec<TlkDecodeError>,
+}
+
+impl ValidationReport {
+    /// True when every present entry is in bounds and decodes cleanly
+    pub fn is_valid(&self) -> bool {
+        self.out_of_bounds.is_empty() && self.decode_failures.is_empty()
+    }
+}
+
 /// Batch operation result for bulk string retrieval
 #[derive(Debug, Clone)]
 pub struct BatchStringResult {
@@ -301,6 +385,26 @@ pub struct BatchMetrics {
     pub bytes_read: usize,
 }
 
+/// One string ref that differs between two talk tables
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlkDiff {
+    /// String reference ID
+    pub str_ref: usize,
+    /// What happened to it
+    pub change: TlkChange,
+}
+
+/// Kind of difference for a `TlkDiff`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TlkChange {
+    /// Present in both tables with different text
+    Changed { old: String, new: String },
+    /// Only present in the other table
+    Added { new: String },
+    /// Only present in this table
+    Removed { old: String },
+}
+
 /// Search result for string searching operations
 #[derive(Debug, Clone)]
 pub struct SearchResult {
@@ -323,6 +427,19 @@ pub struct SearchOptions {
     pub max_results: usize,
     /// Minimum match score threshold
     pub min_score: f32,
+    /// How the query is matched and scored
+    pub mode: SearchMode,
+}
+
+/// Matching strategy for `search_strings`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchMode {
+    /// Whole query as one substring, scored by exact/prefix/suffix/frequency
+    #[default]
+    Substring,
+    /// Query split on whitespace; scored by the fraction of words present,
+    /// with a bonus for query words appearing adjacently in order
+    Tokenized,
 }
 
 impl Default for SearchOptions {
@@ -332,6 +449,7 @@ impl Default for SearchOptions {
             use_regex: false,
             max_results: 1000,
             min_score: 0.0,
+            mode: SearchMode::default(),
         }
     }
 }
@@ -0,0 +1,139 @@
+use std::borrow::Cow;
+use std::path::Path;
+
+use byteorder::{LittleEndian, WriteBytesExt};
+
+use super::error::{TLKError, TLKResult};
+use super::types::{TLKHeader, TLKParser, TLKStringEntry};
+
+const HEADER_SIZE: usize = 20;
+const ENTRY_SIZE: usize = 40;
+const FLAG_TEXT_PRESENT: u32 = 0x01;
+
+impl TLKParser {
+    /// Serialize to TLK V3.0 bytes. String data is rewritten in entry order
+    /// with `data_offset`/`string_size` recomputed, so text replaced by
+    /// `set_string` leaves no dead bytes behind. Flags, sound ResRefs, and
+    /// variances are written back unchanged.
+    pub fn to_bytes(&self) -> TLKResult<Vec<u8>> {
+        let count = self.entries.len();
+        let string_data_offset = HEADER_SIZE + ENTRY_SIZE * count;
+        let language_id = self.header.as_ref().map_or(0, |h| h.language_id);
+
+        let mut table = Vec::with_capacity(ENTRY_SIZE * count);
+        let source = self.string_bytes();
+        let mut string_data = Vec::with_capacity(source.len());
+
+        for (index, entry) in self.entries.iter().enumerate() {
+            let start = entry.data_offset as usize;
+            let end = start + entry.string_size as usize;
+            let bytes = if entry.string_size == 0 {
+                &[][..]
+            } else {
+                source
+                    .get(start..end)
+                    .ok_or(TLKError::CorruptedStringEntry {
+                        index,
+                        offset: entry.data_offset,
+                        size: entry.string_size,
+                    })?
+            };
+
+            write_entry(&mut table, entry, string_data.len() as u32)?;
+            string_data.extend_from_slice(bytes);
+        }
+
+        let mut out = Vec::with_capacity(string_data_offset + string_data.len());
+        out.extend_from_slice(b"TLK V3.0");
+        out.write_u32::<LittleEndian>(language_id)?;
+        out.write_u32::<LittleEndian>(count as u32)?;
+        out.write_u32::<LittleEndian>(string_data_offset as u32)?;
+        out.extend_from_slice(&table);
+        out.extend_from_slice(&string_data);
+
+        Ok(out)
+    }
+
+    pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> TLKResult<()> {
+        std::fs::write(path, self.to_bytes()?)?;
+        Ok(())
+    }
+
+    /// Replace (or create) the text for `str_ref` and mark it present. Refs
+    /// past the end grow the table with empty, not-present entries. Text is
+    /// stored as UTF-8 unless an explicit encoding was set; text that
+    /// encoding can't represent fails with `UnencodableString` and leaves the
+    /// table unchanged. On an unloaded parser this starts a new V3.0 table
+    /// with language 0.
+    pub fn set_string(&mut self, str_ref: usize, text: &str) -> TLKResult<()> {
+        let encoded = match self.encoding {
+            Some(encoding) => {
+                let (encoded, _, had_errors) = encoding.encode(text);
+                if had_errors {
+                    return Err(TLKError::UnencodableString {
+                        str_ref,
+                        encoding: encoding.name(),
+                    });
+                }
+                encoded
+            }
+            None => Cow::Borrowed(text.as_bytes()),
+        };
+        self.security_limits.validate_string_size(encoded.len())?;
+
+        if str_ref >= self.entries.len() {
+            self.security_limits.validate_string_count(str_ref + 1)?;
+            self.entries.resize_with(str_ref + 1, || TLKStringEntry {
+                flags: 0,
+                sound_resref: None,
+                volume_variance: 0,
+                pitch_variance: 0,
+                data_offset: 0,
+                string_size: 0,
+                sound_length: 0.0,
+            });
+        }
+
+        self.detach_string_mmap();
+        let entry = &mut self.entries[str_ref];
+        entry.flags |= FLAG_TEXT_PRESENT;
+        entry.data_offset = self.string_data.len() as u32;
+        entry.string_size = encoded.len() as u32;
+        self.string_data.extend_from_slice(&encoded);
+
+        self.string_cache.remove(&str_ref);
+        self.decode_errors.retain(|e| e.str_ref != str_ref);
+        self.stats.total_strings = self.entries.len();
+        self.header.get_or_insert_with(|| TLKHeader {
+            file_type: "TLK ".to_string(),
+            version: "V3.0".to_string(),
+            language_id: 0,
+            string_count: 0,
+            string_data_offset: 0,
+        });
+
+        if let Some(index) = self.search_index.as_mut() {
+            index.resize(self.entries.len(), String::new());
+            index[str_ref] = text.to_lowercase();
+        }
+
+        Ok(())
+    }
+}
+
+fn write_entry(out: &mut Vec<u8>, entry: &TLKStringEntry, data_offset: u32) -> TLKResult<()> {
+    let mut resref = [0u8; 16];
+    if let Some(ref sound) = entry.sound_resref {
+        let len = sound.len().min(resref.len());
+        resref[..len].copy_from_slice(&sound.as_bytes()[..len]);
+    }
+
+    out.write_u32::<LittleEndian>(entry.flags)?;
+    out.extend_from_slice(&resref);
+    out.write_u32::<LittleEndian>(entry.volume_variance)?;
+    out.write_u32::<LittleEndian>(entry.pitch_variance)?;
+    out.write_u32::<LittleEndian>(data_offset)?;
+    out.write_u32::<LittleEndian>(entry.string_size)?;
+    out.write_f32::<LittleEndian>(entry.sound_length)?;
+    Ok(())
+}
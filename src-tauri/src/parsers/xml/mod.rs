@@ -2,7 +2,8 @@ pub mod parser;
 pub mod types;
 
 pub use parser::{
-    CompanionDefinition, CompanionStatus, FullSummary, QuestGroup, QuestOverview, RustXmlParser,
-    get_companion_definitions,
+    CompanionDefinition, CompanionStatus, FullSummary, QuestClassifierConfig, QuestGroup,
+    QuestGroupOrder, QuestOverview, RustXmlParser, XmlChange, XmlChangeKind, XmlDiff, XmlValue,
+    get_companion_definitions, last_write_time_to_datetime,
 };
 pub use types::XmlData;
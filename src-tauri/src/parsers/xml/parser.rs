@@ -1,8 +1,9 @@
 use super::types::{GlobalsXml, XmlData};
-use chrono::{TimeZone, Utc};
+use chrono::{DateTime, TimeZone, Utc};
+use indexmap::IndexMap;
 use quick_xml::de::from_str;
 use regex::Regex;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::OnceLock;
 
@@ -80,6 +81,8 @@ pub struct CompanionDefinition {
     pub ros_name: Option<&'static str>,
 }
 
+/// Built-in OC/MotB roster keyed by comp_id. Extend a copy and pass it to
+/// `RustXmlParser::with_companion_definitions` for other campaigns.
 pub fn get_companion_definitions() -> HashMap<&'static str, CompanionDefinition> {
     let mut map = HashMap::new();
     map.insert(
@@ -255,8 +258,51 @@ pub fn get_companion_definitions() -> HashMap<&'static str, CompanionDefinition>
     map
 }
 
+/// Latest Unix time chrono can represent (9999-12-31T23:59:59Z); anything
+/// larger can only be a FILETIME.
+const MAX_UNIX_SECONDS: i64 = 253_402_300_799;
+/// Seconds between the FILETIME epoch (1601-01-01) and the Unix epoch.
+const FILETIME_EPOCH_OFFSET: i64 = 11_644_473_600;
+const FILETIME_TICKS_PER_SECOND: i64 = 10_000_000;
+
+/// Convert a `LastWriteTime` global to a date. Saves store either Unix
+/// seconds or a .NET FILETIME (100 ns ticks since 1601); values too large to
+/// be Unix seconds are read as FILETIME.
+pub fn last_write_time_to_datetime(raw: i64) -> Option<DateTime<Utc>> {
+    let seconds = if raw > MAX_UNIX_SECONDS {
+        raw / FILETIME_TICKS_PER_SECOND - FILETIME_EPOCH_OFFSET
+    } else {
+        raw
+    };
+    Utc.timestamp_opt(seconds, 0).single()
+}
+
+/// Campaign-specific tuning for quest classification, applied on top of the
+/// built-in `QUEST_PATTERNS`. Extra exclude patterns are checked first, then
+/// extra include patterns (classified like `*State` variables), then the
+/// built-ins.
+#[derive(Debug, Clone)]
+pub struct QuestClassifierConfig {
+    /// State variables at or above this value count as completed.
+    pub completion_threshold: i32,
+    pub include_patterns: Vec<Regex>,
+    pub exclude_patterns: Vec<Regex>,
+}
+
+impl Default for QuestClassifierConfig {
+    fn default() -> Self {
+        Self {
+            completion_threshold: 50,
+            include_patterns: Vec::new(),
+            exclude_patterns: Vec::new(),
+        }
+    }
+}
+
 pub struct RustXmlParser {
     pub data: XmlData,
+    quest_config: QuestClassifierConfig,
+    extra_companions: HashMap<&'static str, CompanionDefinition>,
 }
 
 impl Default for RustXmlParser {
@@ -269,6 +315,8 @@ impl RustXmlParser {
     pub fn new() -> Self {
         Self {
             data: XmlData::default(),
+            quest_config: QuestClassifierConfig::default(),
+            extra_companions: HashMap::new(),
         }
     }
 
@@ -277,9 +325,24 @@ impl RustXmlParser {
             from_str(content).map_err(|e| format!("Failed to parse XML: {e}"))?;
         Ok(Self {
             data: XmlData::from_xml_struct(globals),
+            quest_config: QuestClassifierConfig::default(),
+            extra_companions: HashMap::new(),
         })
     }
 
+    pub fn with_quest_config(mut self, config: QuestClassifierConfig) -> Self {
+        self.quest_config = config;
+        self
+    }
+
+    pub fn set_quest_config(&mut self, config: QuestClassifierConfig) {
+        self.quest_config = config;
+    }
+
+    pub fn quest_config(&self) -> &QuestClassifierConfig {
+        &self.quest_config
+    }
+
     pub fn to_xml_string(&self) -> Result<String, String> {
         let mut xml_struct = self.data.to_xml_struct();
 
@@ -302,6 +365,48 @@ impl RustXmlParser {
         Ok(result)
     }
 
+    /// Register companions for campaigns the built-in roster doesn't cover.
+    /// Entries replace built-ins that share a comp_id.
+    pub fn with_companion_definitions(
+        mut self,
+        definitions: HashMap<&'static str, CompanionDefinition>,
+    ) -> Self {
+        self.extra_companions = definitions;
+        self
+    }
+
+    /// Built-in definitions merged with any registered extras.
+    pub fn companion_definitions(&self) -> HashMap<&'static str, CompanionDefinition> {
+        let mut definitions = get_companion_definitions();
+        definitions.extend(
+            self.extra_companions
+                .iter()
+                .map(|(id, def)| (*id, def.clone())),
+        );
+        definitions
+    }
+
+    /// Set an integer global, adding it at the end of its section if absent.
+    pub fn set_integer(&mut self, name: &str, value: i32) {
+        self.data
+            .integers
+            .insert(name.to_string(), i64::from(value));
+    }
+
+    pub fn set_string(&mut self, name: &str, value: &str) {
+        self.data
+            .strings
+            .insert(name.to_string(), value.to_string());
+    }
+
+    pub fn set_float(&mut self, name: &str, value: f32) {
+        self.data.floats.insert(name.to_string(), value);
+    }
+
+    pub fn set_vector(&mut self, name: &str, value: [f32; 3]) {
+        self.data.vectors.insert(name.to_string(), value);
+    }
+
     pub fn discover_potential_companions(&self) -> HashMap<String, BTreeMap<String, String>> {
         let mut discovered = HashMap::new();
         let blacklist_set: HashSet<&str> = BLACKLIST.iter().copied().collect();
@@ -330,7 +435,7 @@ impl RustXmlParser {
 
     pub fn get_companion_status(&self) -> HashMap<String, CompanionStatus> {
         let mut companion_status = HashMap::new();
-        let defs = get_companion_definitions();
+        let defs = self.companion_definitions();
 
         // 1. Explicit definitions
         for (comp_id, def) in defs {
@@ -338,7 +443,7 @@ impl RustXmlParser {
             let mut recruitment = "not_recruited".to_string();
 
             if let Some(val) = self.data.integers.get(def.influence_var) {
-                influence = Some(*val);
+                influence = i32::try_from(*val).ok();
             }
 
             let joined = self.data.integers.get(def.joined_var).copied().unwrap_or(0);
@@ -388,16 +493,32 @@ impl RustXmlParser {
         let mut completed = HashSet::new();
         let mut active = HashSet::new();
 
-        let sorted_patterns = get_quest_patterns();
+        let config = &self.quest_config;
+        let sorted_patterns: Vec<(&Regex, &str)> = config
+            .exclude_patterns
+            .iter()
+            .map(|pattern| (pattern, "exclude"))
+            .chain(
+                config
+                    .include_patterns
+                    .iter()
+                    .map(|pattern| (pattern, "state")),
+            )
+            .chain(
+                get_quest_patterns()
+                    .iter()
+                    .map(|(pattern, category, _)| (pattern, *category)),
+            )
+            .collect();
 
         for (var_name, &value) in &self.data.integers {
             if value <= 0 {
                 continue;
             }
 
-            for (pattern, category, _) in sorted_patterns {
+            for &(pattern, category) in &sorted_patterns {
                 if pattern.is_match(var_name) {
-                    match *category {
+                    match category {
                         "exclude" => break,
                         "completed" => {
                             completed.insert(var_name.clone());
@@ -408,7 +529,7 @@ impl RustXmlParser {
                             break;
                         }
                         "state" => {
-                            if value >= 50 {
+                            if value >= i64::from(config.completion_threshold) {
                                 completed.insert(var_name.clone());
                             } else {
                                 active.insert(var_name.clone());
@@ -471,6 +592,19 @@ impl RustXmlParser {
         }
     }
 
+    /// Quest groups ordered most-progressed first by `order`, ties broken
+    /// alphabetically by group name.
+    pub fn get_quest_groups_sorted(&self, order: QuestGroupOrder) -> Vec<(String, QuestGroup)> {
+        let mut groups: Vec<(String, QuestGroup)> = self
+            .get_quest_overview_struct()
+            .quest_groups
+            .into_iter()
+            .collect();
+        // BTreeMap yields names in order, and the sort is stable
+        groups.sort_by_key(|(_, group)| std::cmp::Reverse(order.count(group)));
+        groups
+    }
+
     pub fn get_general_info(&self) -> HashMap<String, Option<String>> {
         let mut info = HashMap::new();
         info.insert("player_name".to_string(), None);
@@ -501,8 +635,16 @@ impl RustXmlParser {
             info.insert("difficulty".to_string(), Some(label.to_string()));
         }
 
-        if let Some(timestamp) = self.data.integers.get("LastWriteTime") {
-            if let Some(dt) = Utc.timestamp_opt(i64::from(*timestamp), 0).single() {
+        // Normally an integer (Unix seconds or FILETIME), occasionally a string
+        let last_write_time = self
+            .data
+            .integers
+            .get("LastWriteTime")
+            .map(ToString::to_string)
+            .or_else(|| self.data.strings.get("LastWriteTime").cloned());
+        if let Some(timestamp) = last_write_time {
+            let raw = timestamp.trim().parse::<i64>().ok();
+            if let Some(dt) = raw.and_then(last_write_time_to_datetime) {
                 info.insert("last_saved".to_string(), Some(dt.to_rfc3339()));
             } else {
                 info.insert(
@@ -525,9 +667,117 @@ impl RustXmlParser {
                 ("booleans".to_string(), self.data.booleans.len()),
                 ("floats".to_string(), self.data.floats.len()),
                 ("strings".to_string(), self.data.strings.len()),
+                ("vectors".to_string(), self.data.vectors.len()),
             ]),
         }
     }
+
+    /// Variables added, removed, or changed going from `self` to `other`.
+    pub fn diff(&self, other: &RustXmlParser) -> XmlDiff {
+        let mut diff = XmlDiff::default();
+        let (old, new) = (&self.data, &other.data);
+
+        diff_maps(&mut diff, &old.integers, &new.integers, |v| {
+            XmlValue::Integer(*v)
+        });
+        diff_maps(&mut diff, &old.booleans, &new.booleans, |v| {
+            XmlValue::Boolean(*v)
+        });
+        diff_maps(&mut diff, &old.floats, &new.floats, |v| XmlValue::Float(*v));
+        diff_maps(&mut diff, &old.strings, &new.strings, |v| {
+            XmlValue::String(v.clone())
+        });
+        diff_maps(&mut diff, &old.vectors, &new.vectors, |v| {
+            XmlValue::Vector(*v)
+        });
+
+        diff
+    }
+}
+
+fn diff_maps<T>(
+    diff: &mut XmlDiff,
+    old: &IndexMap<String, T>,
+    new: &IndexMap<String, T>,
+    to_value: impl Fn(&T) -> XmlValue,
+) {
+    for (name, old_value) in old {
+        let old_value = to_value(old_value);
+        match new.get(name).map(&to_value) {
+            None => diff.changes.push(XmlChange {
+                name: name.clone(),
+                kind: XmlChangeKind::Removed,
+                old: Some(old_value),
+                new: None,
+            }),
+            Some(new_value) if !new_value.same_as(&old_value) => diff.changes.push(XmlChange {
+                name: name.clone(),
+                kind: XmlChangeKind::Changed,
+                old: Some(old_value),
+                new: Some(new_value),
+            }),
+            Some(_) => {}
+        }
+    }
+
+    for (name, new_value) in new {
+        if !old.contains_key(name) {
+            diff.changes.push(XmlChange {
+                name: name.clone(),
+                kind: XmlChangeKind::Added,
+                old: None,
+                new: Some(to_value(new_value)),
+            });
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum XmlValue {
+    Integer(i64),
+    Boolean(i32),
+    Float(f32),
+    String(String),
+    Vector([f32; 3]),
+}
+
+impl XmlValue {
+    // Bitwise so NaN == NaN and we don't report phantom float changes
+    fn same_as(&self, other: &XmlValue) -> bool {
+        match (self, other) {
+            (XmlValue::Float(a), XmlValue::Float(b)) => a.to_bits() == b.to_bits(),
+            (XmlValue::Vector(a), XmlValue::Vector(b)) => {
+                a.iter().zip(b).all(|(x, y)| x.to_bits() == y.to_bits())
+            }
+            _ => self == other,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum XmlChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct XmlChange {
+    pub name: String,
+    pub kind: XmlChangeKind,
+    pub old: Option<XmlValue>,
+    pub new: Option<XmlValue>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct XmlDiff {
+    pub changes: Vec<XmlChange>,
+}
+
+impl XmlDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
 }
 
 // Helper types for quest overview
@@ -546,6 +796,23 @@ pub struct QuestGroup {
     pub active: Vec<String>,
 }
 
+/// Sort key for `RustXmlParser::get_quest_groups_sorted`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuestGroupOrder {
+    Completed,
+    Total,
+}
+
+impl QuestGroupOrder {
+    fn count(self, group: &QuestGroup) -> usize {
+        match self {
+            QuestGroupOrder::Completed => group.completed.len(),
+            QuestGroupOrder::Total => group.completed.len() + group.active.len(),
+        }
+    }
+}
+
 #[derive(Serialize)]
 pub struct QuestOverview {
     pub completed_count: usize,
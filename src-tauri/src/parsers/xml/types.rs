@@ -3,18 +3,20 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Default, Serialize)]
 pub struct XmlData {
-    pub integers: IndexMap<String, i32>,
+    pub integers: IndexMap<String, i64>,
     pub booleans: IndexMap<String, i32>,
     pub floats: IndexMap<String, f32>,
     pub strings: IndexMap<String, String>,
+    pub vectors: IndexMap<String, [f32; 3]>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct IntegerEntry {
     #[serde(rename = "Name")]
     pub name: String,
+    // Wide enough for FILETIME-sized globals such as `LastWriteTime`
     #[serde(rename = "Value")]
-    pub value: i32,
+    pub value: i64,
 }
 
 #[derive(Debug, Default, Deserialize, Serialize)]
@@ -70,6 +72,30 @@ pub struct StringsWrapper {
     pub entries: Vec<StringEntry>,
 }
 
+#[derive(Debug, Deserialize, Serialize)]
+pub struct VectorEntry {
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "X", serialize_with = "serialize_f32_fixed")]
+    pub x: f32,
+    #[serde(rename = "Y", serialize_with = "serialize_f32_fixed")]
+    pub y: f32,
+    #[serde(rename = "Z", serialize_with = "serialize_f32_fixed")]
+    pub z: f32,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct VectorsWrapper {
+    #[serde(rename = "Vector", default)]
+    pub entries: Vec<VectorEntry>,
+}
+
+impl VectorsWrapper {
+    fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
 #[derive(Debug, Default, Deserialize, Serialize)]
 #[serde(rename = "Globals")]
 pub struct GlobalsXml {
@@ -81,6 +107,13 @@ pub struct GlobalsXml {
     pub floats: FloatsWrapper,
     #[serde(rename = "Strings", default)]
     pub strings: StringsWrapper,
+    // Most saves have no vectors; omit the section so those round-trip byte-exact
+    #[serde(
+        rename = "Vectors",
+        default,
+        skip_serializing_if = "VectorsWrapper::is_empty"
+    )]
+    pub vectors: VectorsWrapper,
 }
 
 impl XmlData {
@@ -99,6 +132,9 @@ impl XmlData {
         for entry in xml.strings.entries {
             data.strings.insert(entry.name, entry.value);
         }
+        for entry in xml.vectors.entries {
+            data.vectors.insert(entry.name, [entry.x, entry.y, entry.z]);
+        }
         data
     }
 
@@ -139,11 +175,23 @@ impl XmlData {
             })
             .collect();
 
+        let vectors = self
+            .vectors
+            .iter()
+            .map(|(k, [x, y, z])| VectorEntry {
+                name: k.clone(),
+                x: *x,
+                y: *y,
+                z: *z,
+            })
+            .collect();
+
         GlobalsXml {
             integers: IntegersWrapper { entries: integers },
             booleans: BooleansWrapper { entries: booleans },
             floats: FloatsWrapper { entries: floats },
             strings: StringsWrapper { entries: strings },
+            vectors: VectorsWrapper { entries: vectors },
         }
     }
 }
@@ -97,7 +97,10 @@ impl CampaignManager {
         let xml_content = handler.extract_globals_xml().map_err(|e| e.to_string())?;
         let mut parser = GlobalsParser::from_string(&xml_content)?;
 
-        parser.data.integers.insert(name.to_string(), value);
+        parser
+            .data
+            .integers
+            .insert(name.to_string(), i64::from(value));
 
         let new_xml = parser.to_xml_string()?;
         handler
@@ -246,7 +249,7 @@ impl CampaignManager {
         for (name, value, var_type) in updates {
             match var_type.as_str() {
                 "int" => {
-                    let v: i32 = value
+                    let v: i64 = value
                         .parse()
                         .map_err(|e| format!("Invalid int value for '{name}': {e}"))?;
                     parser.data.integers.insert(name.clone(), v);
@@ -8,6 +8,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::parsers::tda::TDAParser;
 
+use super::module_loader::HakIcon;
 use super::override_chain::ModuleInfo;
 
 const DEFAULT_MAX_MODULES: usize = 5;
@@ -16,6 +17,7 @@ const DEFAULT_MAX_MODULES: usize = 5;
 pub struct CachedModuleState {
     pub module_info: ModuleInfo,
     pub hak_overrides: Vec<HashMap<String, Arc<TDAParser>>>,
+    pub hak_icons: HashMap<String, HakIcon>,
     pub module_overrides: HashMap<String, Arc<TDAParser>>,
     pub campaign_overrides: HashMap<String, Arc<TDAParser>>,
     pub custom_tlk_path: Option<PathBuf>,
@@ -135,6 +137,26 @@ pub struct CacheStats {
     pub hit_ratio: f64,
 }
 
+/// Icon availability by effective source plus lookup counters.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IconStats {
+    pub total: usize,
+    /// Icon count keyed like `ResourceManager::resource_source_counts`.
+    pub source_counts: HashMap<String, usize>,
+    pub hits: u64,
+    pub misses: u64,
+    pub hit_ratio: f64,
+}
+
+/// Result of merging HAK icons into the icon set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HakIconStats {
+    /// Icons with no base-game counterpart.
+    pub added: usize,
+    /// Icons that now shadow a base-game icon of the same name.
+    pub overridden: usize,
+}
+
 #[derive(Debug, Default)]
 pub struct FileModificationTracker {
     mod_times: HashMap<PathBuf, f64>,
@@ -214,6 +236,7 @@ mod tests {
         let state = CachedModuleState {
             module_info: ModuleInfo::default(),
             hak_overrides: Vec::new(),
+            hak_icons: HashMap::new(),
             module_overrides: HashMap::new(),
             campaign_overrides: HashMap::new(),
             custom_tlk_path: None,
@@ -237,6 +260,7 @@ mod tests {
         let state = CachedModuleState {
             module_info: ModuleInfo::default(),
             hak_overrides: Vec::new(),
+            hak_icons: HashMap::new(),
             module_overrides: HashMap::new(),
             campaign_overrides: HashMap::new(),
             custom_tlk_path: None,
@@ -260,6 +284,7 @@ mod tests {
         let state = CachedModuleState {
             module_info: ModuleInfo::default(),
             hak_overrides: Vec::new(),
+            hak_icons: HashMap::new(),
             module_overrides: HashMap::new(),
             campaign_overrides: HashMap::new(),
             custom_tlk_path: None,
@@ -285,6 +310,7 @@ mod tests {
         let state = CachedModuleState {
             module_info: ModuleInfo::default(),
             hak_overrides: Vec::new(),
+            hak_icons: HashMap::new(),
             module_overrides: HashMap::new(),
             campaign_overrides: HashMap::new(),
             custom_tlk_path: None,
@@ -7,6 +7,7 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, RwLock as StdRwLock};
+use std::time::{Duration, Instant};
 
 use dashmap::DashMap;
 use indexmap::IndexMap;
@@ -21,7 +22,9 @@ use crate::parsers::tda::TDAParser;
 use crate::parsers::tlk::TLKParser;
 use crate::utils::ZipContentReader;
 
-pub use cache::{CacheStats, CachedModuleState, FileModificationTracker, ModuleLRUCache};
+pub use cache::{
+    CacheStats, CachedModuleState, FileModificationTracker, HakIconStats, IconStats, ModuleLRUCache,
+};
 pub use error::{ResourceManagerError, ResourceManagerResult};
 pub use override_chain::{
     CampaignInfo, ContainerType, ModuleInfo, OverrideSource, ResourceLocation, TemplateInfo,
@@ -49,6 +52,47 @@ const LOD_ZIPS: &[&str] = &[
     "lod-merged_x2_v121.zip",
 ];
 const VO_ZIPS: &[&str] = &["vo.zip", "vo_x1.zip", "vo_x2.zip"];
+/// Workshop icons live at `<workshop>/<item>/ui/<variant>/icons`.
+const WORKSHOP_ICON_DIR_DEPTH: usize = 4;
+/// Minimum gap between `icons_stale` directory walks.
+const ICON_STALE_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// An icon source directory and its `latest_dir_mtime` when it was scanned.
+struct IconDirStamp {
+    dir: PathBuf,
+    depth: usize,
+    mtime: Option<f64>,
+    workshop: bool,
+}
+
+impl IconDirStamp {
+    fn new(dir: PathBuf, depth: usize, workshop: bool) -> Self {
+        let mtime = crate::utils::directory_scanner::latest_dir_mtime(&dir, depth);
+        Self {
+            dir,
+            depth,
+            mtime,
+            workshop,
+        }
+    }
+
+    fn is_stale(&self) -> bool {
+        crate::utils::directory_scanner::latest_dir_mtime(&self.dir, self.depth).map(f64::to_bits)
+            != self.mtime.map(f64::to_bits)
+    }
+
+    /// Whether `path` was indexed by the icon scan of this directory.
+    fn covers(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.dir) else {
+            return false;
+        };
+        !self.workshop
+            || relative
+                .components()
+                .nth(1)
+                .is_some_and(|c| c.as_os_str().eq_ignore_ascii_case("ui"))
+    }
+}
 
 pub struct ResourceManager {
     paths: Arc<RwLock<NWN2Paths>>,
@@ -60,6 +104,7 @@ pub struct ResourceManager {
     custom_tlk_cache: Option<Arc<StdRwLock<TLKParser>>>,
 
     hak_overrides: Vec<HashMap<String, Arc<TDAParser>>>,
+    hak_icons: HashMap<String, module_loader::HakIcon>,
     module_overrides: DashMap<String, Arc<TDAParser>>,
     tda_cache: DashMap<(String, OverrideSource), Arc<TDAParser>>,
 
@@ -78,9 +123,13 @@ pub struct ResourceManager {
 
     resource_index: HashMap<String, Vec<ResourceLocation>>,
     icon_file_paths: HashMap<String, PathBuf>,
+    icon_dir_stamps: Vec<IconDirStamp>,
+    icon_stale_checked_at: Mutex<Option<Instant>>,
 
     cache_hits: AtomicU64,
     cache_misses: AtomicU64,
+    icon_hits: AtomicU64,
+    icon_misses: AtomicU64,
     initialized: bool,
 }
 
@@ -93,6 +142,7 @@ impl ResourceManager {
             tlk_cache: None,
             custom_tlk_cache: None,
             hak_overrides: Vec::new(),
+            hak_icons: HashMap::new(),
             module_overrides: DashMap::new(),
             tda_cache: DashMap::new(),
             current_module: None,
@@ -103,10 +153,14 @@ impl ResourceManager {
             data_zip_paths: Vec::new(),
             resource_index: HashMap::new(),
             icon_file_paths: HashMap::new(),
+            icon_dir_stamps: Vec::new(),
+            icon_stale_checked_at: Mutex::new(None),
             module_cache: ModuleLRUCache::new(),
             file_mod_tracker: FileModificationTracker::new(),
             cache_hits: AtomicU64::new(0),
             cache_misses: AtomicU64::new(0),
+            icon_hits: AtomicU64::new(0),
+            icon_misses: AtomicU64::new(0),
             initialized: false,
         }
     }
@@ -122,6 +176,7 @@ impl ResourceManager {
         self.scan_workshop_directories().await?;
         self.scan_override_directories().await?;
         self.scan_icon_directories().await?;
+        self.scan_workshop_icons().await;
         self.load_base_tlk().await?;
         self.cache_data_zip_paths().await;
 
@@ -147,6 +202,7 @@ impl ResourceManager {
         self.tlk_cache = None;
         self.custom_tlk_cache = None;
         self.hak_overrides.clear();
+        self.hak_icons.clear();
         self.module_overrides.clear();
         self.tda_cache.clear();
         self.current_module = None;
@@ -159,8 +215,12 @@ impl ResourceManager {
         self.data_zip_paths.clear();
         self.resource_index.clear();
         self.icon_file_paths.clear();
+        self.icon_dir_stamps.clear();
+        *self.icon_stale_checked_at.lock() = None;
         self.cache_hits.store(0, Ordering::Relaxed);
         self.cache_misses.store(0, Ordering::Relaxed);
+        self.icon_hits.store(0, Ordering::Relaxed);
+        self.icon_misses.store(0, Ordering::Relaxed);
         *self.zip_reader.lock() = ZipContentReader::new();
     }
 
@@ -333,14 +393,19 @@ impl ResourceManager {
             return Ok(());
         };
 
+        // Missing folders are stamped too, so creating one later is noticed
         let upscaled = game_folder.join("ui").join("upscaled").join("icons");
         let icon_dir = if upscaled.exists() {
             upscaled
         } else {
+            self.icon_dir_stamps
+                .push(IconDirStamp::new(upscaled, usize::MAX, false));
             let default = game_folder.join("ui").join("default").join("icons");
             if default.exists() {
                 default
             } else {
+                self.icon_dir_stamps
+                    .push(IconDirStamp::new(default, usize::MAX, false));
                 return Ok(());
             }
         };
@@ -362,9 +427,87 @@ impl ResourceManager {
             self.icon_file_paths.len(),
             icon_dir.display()
         );
+        self.icon_dir_stamps
+            .push(IconDirStamp::new(icon_dir, usize::MAX, false));
         Ok(())
     }
 
+    /// Workshop icons shadow base game ones, matching `OverrideSource` priority.
+    async fn scan_workshop_icons(&mut self) {
+        let paths = self.paths.read().await;
+        let workshop_dir = paths.steam_workshop_folder().cloned();
+        drop(paths);
+
+        let Some(workshop_dir) = workshop_dir else {
+            return;
+        };
+
+        let mut seen = std::collections::HashSet::new();
+        for file in crate::utils::directory_scanner::scan_workshop_icons(&workshop_dir) {
+            if !seen.insert(file.stem.clone()) {
+                continue;
+            }
+            self.icon_file_paths
+                .insert(file.stem.clone(), file.path.clone());
+
+            let key = resource_key(&file.stem, &file.extension);
+            let location =
+                ResourceLocation::from_file(OverrideSource::Workshop, file.path, file.mtime);
+            self.resource_index.entry(key).or_default().push(location);
+        }
+
+        if !seen.is_empty() {
+            info!(
+                "Indexed {} workshop icons from {}",
+                seen.len(),
+                workshop_dir.display()
+            );
+        }
+        self.icon_dir_stamps.push(IconDirStamp::new(
+            workshop_dir,
+            WORKSHOP_ICON_DIR_DEPTH,
+            true,
+        ));
+    }
+
+    /// Whether an icon directory changed since it was indexed. Directories are
+    /// walked at most once per `ICON_STALE_CHECK_INTERVAL`, so this is cheap
+    /// enough to call before every icon lookup.
+    pub fn icons_stale(&self) -> bool {
+        let now = Instant::now();
+        {
+            let mut checked_at = self.icon_stale_checked_at.lock();
+            if checked_at.is_some_and(|at| now.duration_since(at) < ICON_STALE_CHECK_INTERVAL) {
+                return false;
+            }
+            *checked_at = Some(now);
+        }
+        self.icon_dir_stamps.iter().any(IconDirStamp::is_stale)
+    }
+
+    /// Rescan icon directories if any changed since they were indexed, so
+    /// newly added icons show up without a full re-initialize. Returns whether
+    /// a rescan happened.
+    pub async fn refresh_icons_if_stale(&mut self) -> ResourceManagerResult<bool> {
+        if !self.icon_dir_stamps.iter().any(IconDirStamp::is_stale) {
+            return Ok(false);
+        }
+
+        let stamps = std::mem::take(&mut self.icon_dir_stamps);
+        for locations in self.resource_index.values_mut() {
+            locations.retain(|l| {
+                !(l.is_loose_file() && stamps.iter().any(|s| s.covers(&l.container_path)))
+            });
+        }
+        self.resource_index.retain(|_, v| !v.is_empty());
+        self.icon_file_paths.clear();
+
+        debug!("Icon directories changed, rescanning icons");
+        self.scan_icon_directories().await?;
+        self.scan_workshop_icons().await;
+        Ok(true)
+    }
+
     fn index_scanned_files(
         &mut self,
         files: Vec<crate::utils::directory_scanner::ScannedFile>,
@@ -374,7 +517,8 @@ impl ResourceManager {
             let key = resource_key(&file.stem, &file.extension);
             self.file_mod_tracker.track(file.path.clone(), file.mtime);
 
-            let location = ResourceLocation::from_file(source.clone(), file.path, file.mtime);
+            let location = ResourceLocation::from_file(source.clone(), file.path, file.mtime)
+                .with_content_hash(file.content_hash);
             self.resource_index
                 .entry(key)
                 .or_default()
@@ -713,39 +857,7 @@ impl ResourceManager {
         }
         self.current_module = Some(module_key.clone());
 
-        self.hak_overrides.clear();
-
-        let paths = self.paths.read().await;
-        let custom_hak_folders = paths.custom_hak_folders().to_vec();
-        let user_hak = paths.hak_dir();
-        let install_hak = paths.hak_dir();
-        drop(paths);
-
-        for hak_name in &module_info.hak_list {
-            if let Some(hak_path) = module_loader::find_hak_path(
-                hak_name,
-                &custom_hak_folders,
-                user_hak.as_ref(),
-                install_hak.as_ref(),
-            ) {
-                match module_loader::load_hak_2das(&hak_path) {
-                    Ok(hak_2das) => {
-                        self.hak_overrides.push(hak_2das);
-
-                        if let Some(tlk_path) = module_loader::check_hak_for_tlk(&hak_path)
-                            && let Ok(tlk) = module_loader::load_tlk(&tlk_path)
-                        {
-                            self.custom_tlk_cache = Some(Arc::new(StdRwLock::new(tlk)));
-                        }
-                    }
-                    Err(e) => {
-                        warn!("Failed to load HAK {}: {}", hak_name, e);
-                    }
-                }
-            } else {
-                warn!("HAK not found: {}", hak_name);
-            }
-        }
+        self.load_module_haks(&module_info.hak_list).await;
 
         if !module_info.custom_tlk.is_empty() && self.custom_tlk_cache.is_none() {
             self.load_custom_tlk(&module_info.custom_tlk).await;
@@ -758,6 +870,7 @@ impl ResourceManager {
         let cached_state = CachedModuleState {
             module_info: module_info.clone(),
             hak_overrides: self.hak_overrides.clone(),
+            hak_icons: self.hak_icons.clone(),
             module_overrides: self.module_overrides.clone().into_iter().collect(),
             campaign_overrides: self
                 .tda_cache
@@ -782,6 +895,7 @@ impl ResourceManager {
         self.module_info = Some(cached.module_info.clone());
         self.module_path = Some(cached.module_info.path.clone());
         self.hak_overrides = cached.hak_overrides;
+        self.hak_icons = cached.hak_icons;
         self.module_overrides.clear();
         for (k, v) in cached.module_overrides {
             self.module_overrides.insert(k, v);
@@ -791,6 +905,89 @@ impl ResourceManager {
         }
     }
 
+    /// Rebuild `hak_overrides` and the HAK icon index from a module's HAK
+    /// list, dropping everything indexed for the previously loaded module.
+    async fn load_module_haks(&mut self, hak_list: &[String]) {
+        self.hak_overrides.clear();
+        self.hak_icons.clear();
+
+        let paths = self.paths.read().await;
+        let custom_hak_folders = paths.custom_hak_folders().to_vec();
+        let user_hak = paths.hak_dir();
+        let install_hak = paths.hak_dir();
+        drop(paths);
+
+        for hak_name in hak_list {
+            if let Some(hak_path) = module_loader::find_hak_path(
+                hak_name,
+                &custom_hak_folders,
+                user_hak.as_ref(),
+                install_hak.as_ref(),
+            ) {
+                match module_loader::load_hak_2das(&hak_path) {
+                    Ok(hak_2das) => {
+                        self.hak_overrides.push(hak_2das);
+                        self.merge_hak_icons(&hak_path);
+
+                        if let Some(tlk_path) = module_loader::check_hak_for_tlk(&hak_path)
+                            && let Ok(tlk) = module_loader::load_tlk(&tlk_path)
+                        {
+                            self.custom_tlk_cache = Some(Arc::new(StdRwLock::new(tlk)));
+                        }
+                    }
+                    Err(e) => {
+                        warn!("Failed to load HAK {}: {}", hak_name, e);
+                    }
+                }
+            } else {
+                warn!("HAK not found: {}", hak_name);
+            }
+        }
+    }
+
+    /// HAKs are merged in load order, so an icon from an earlier HAK is never
+    /// replaced by a later one (same precedence as `hak_overrides`).
+    fn merge_hak_icons(&mut self, hak_path: &Path) -> HakIconStats {
+        let mut stats = HakIconStats::default();
+        match module_loader::index_hak_icons(hak_path) {
+            Ok(icons) => {
+                for (name, icon) in icons {
+                    if let std::collections::hash_map::Entry::Vacant(slot) =
+                        self.hak_icons.entry(name)
+                    {
+                        if self.icon_file_paths.contains_key(slot.key()) {
+                            stats.overridden += 1;
+                        } else {
+                            stats.added += 1;
+                        }
+                        slot.insert(icon);
+                    }
+                }
+            }
+            Err(e) => {
+                warn!("Failed to index icons in HAK {}: {}", hak_path.display(), e);
+            }
+        }
+        stats
+    }
+
+    /// Merge icons from the given HAK files into the current icon set without
+    /// rescanning base game content. Icons already provided by a HAK are kept.
+    pub fn add_hak_icons(&mut self, hak_paths: &[PathBuf]) -> HakIconStats {
+        let mut stats = HakIconStats::default();
+        for hak_path in hak_paths {
+            let hak_stats = self.merge_hak_icons(hak_path);
+            stats.added += hak_stats.added;
+            stats.overridden += hak_stats.overridden;
+        }
+
+        info!(
+            "Merged HAK icons: {} added, {} overriding base game",
+            stats.added, stats.overridden
+        );
+        stats
+    }
+
     async fn load_custom_tlk(&mut self, tlk_name: &str) {
         let paths = self.paths.read().await;
 
@@ -875,36 +1072,7 @@ impl ResourceManager {
         campaign_guid: &str,
     ) -> ResourceManagerResult<bool> {
         self.clear_override_caches();
-
-        let paths = self.paths.read().await;
-        let custom_hak_folders = paths.custom_hak_folders().to_vec();
-        let user_hak = paths.hak_dir();
-        let install_hak = paths.hak_dir();
-        drop(paths);
-
-        for hak_name in hak_list {
-            if let Some(hak_path) = module_loader::find_hak_path(
-                hak_name,
-                &custom_hak_folders,
-                user_hak.as_ref(),
-                install_hak.as_ref(),
-            ) {
-                match module_loader::load_hak_2das(&hak_path) {
-                    Ok(hak_2das) => {
-                        self.hak_overrides.push(hak_2das);
-
-                        if let Some(tlk_path) = module_loader::check_hak_for_tlk(&hak_path)
-                            && let Ok(tlk) = module_loader::load_tlk(&tlk_path)
-                        {
-                            self.custom_tlk_cache = Some(Arc::new(StdRwLock::new(tlk)));
-                        }
-                    }
-                    Err(e) => {
-                        warn!("Failed to load HAK {}: {}", hak_name, e);
-                    }
-                }
-            }
-        }
+        self.load_module_haks(hak_list).await;
 
         if !custom_tlk.is_empty() && self.custom_tlk_cache.is_none() {
             self.load_custom_tlk(custom_tlk).await;
@@ -974,6 +1142,7 @@ impl ResourceManager {
 
     pub fn clear_override_caches(&mut self) {
         self.hak_overrides.clear();
+        self.hak_icons.clear();
         self.module_overrides.clear();
         self.tda_cache.clear();
         self.custom_tlk_cache = None;
@@ -1262,22 +1431,54 @@ impl ResourceManager {
         let mut counts: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
         for locations in self.resource_index.values() {
             for loc in locations {
-                let key = match loc.source {
-                    OverrideSource::BaseGame => "base_game",
-                    OverrideSource::Expansion => "expansion",
-                    OverrideSource::Module => "module",
-                    OverrideSource::Campaign => "campaign",
-                    OverrideSource::OverrideDir => "override_dir",
-                    OverrideSource::Workshop => "workshop",
-                    OverrideSource::CustomOverride => "custom_override",
-                    OverrideSource::Hak(_) => "hak",
-                };
-                *counts.entry(key.to_string()).or_default() += 1;
+                *counts
+                    .entry(source_count_key(&loc.source).to_string())
+                    .or_default() += 1;
             }
         }
         counts
     }
 
+    /// Icons counted by the source that actually serves them, so a base game
+    /// icon shadowed by a HAK is only counted as `hak`.
+    pub fn get_icon_stats(&self) -> IconStats {
+        let mut source_counts: HashMap<String, usize> = HashMap::new();
+        for (name, path) in &self.icon_file_paths {
+            if self.hak_icons.contains_key(name) {
+                continue;
+            }
+            let source = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .and_then(|ext| {
+                    self.resource_index
+                        .get(&resource_key(name, &ext.to_lowercase()))
+                })
+                .and_then(|locs| locs.iter().find(|l| &l.container_path == path))
+                .map_or(OverrideSource::BaseGame, |l| l.source.clone());
+            *source_counts
+                .entry(source_count_key(&source).to_string())
+                .or_default() += 1;
+        }
+        if !self.hak_icons.is_empty() {
+            source_counts.insert("hak".to_string(), self.hak_icons.len());
+        }
+
+        let hits = self.icon_hits.load(Ordering::Relaxed);
+        let misses = self.icon_misses.load(Ordering::Relaxed);
+        IconStats {
+            total: source_counts.values().sum(),
+            source_counts,
+            hits,
+            misses,
+            hit_ratio: if hits + misses > 0 {
+                hits as f64 / (hits + misses) as f64
+            } else {
+                0.0
+            },
+        }
+    }
+
     pub fn get_available_2da_files(&self) -> Vec<String> {
         self.resource_index
             .keys()
@@ -1294,6 +1495,59 @@ impl ResourceManager {
         self.icon_file_paths.get(&resref.to_lowercase()).cloned()
     }
 
+    /// Raw icon image bytes, read on demand. Icons from the loaded module's
+    /// HAKs take priority over the game's `ui/*/icons` folder, which takes
+    /// priority over DDS/TGA resources found through `get_resource_bytes`.
+    /// Every call counts as an icon hit or miss in `get_icon_stats`.
+    pub fn get_icon_bytes(&self, resref: &str) -> Option<Vec<u8>> {
+        let name = resref.to_lowercase();
+        let data = match self.hak_icons.get(&name) {
+            Some(icon) => match icon.read() {
+                Ok(data) => Some(data),
+                Err(e) => {
+                    warn!("Failed to read HAK icon {}: {}", name, e);
+                    None
+                }
+            },
+            None => self
+                .icon_file_paths
+                .get(&name)
+                .and_then(|path| std::fs::read(path).ok()),
+        }
+        .or_else(|| {
+            ["dds", "tga"]
+                .iter()
+                .find_map(|ext| self.get_resource_bytes(&name, ext).ok())
+        });
+
+        let counter = if data.is_some() {
+            &self.icon_hits
+        } else {
+            &self.icon_misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        data
+    }
+
+    /// Resolve many icons in one call. Names that don't resolve are omitted;
+    /// icons are read in parallel.
+    pub fn get_icons_batch(&self, resrefs: &[String]) -> HashMap<String, Vec<u8>> {
+        use rayon::prelude::*;
+
+        resrefs
+            .par_iter()
+            .filter_map(|resref| {
+                self.get_icon_bytes(resref)
+                    .map(|data| (resref.to_lowercase(), data))
+            })
+            .collect()
+    }
+
+    /// Dump the icon index as pretty JSON for support/debugging.
+    pub fn export_icon_index_json(&self) -> String {
+        icon_index_json(&self.icon_file_paths)
+    }
+
     pub fn has_resource(&self, resref: &str, extension: &str) -> bool {
         let key = resource_key(&resref.to_lowercase(), &extension.to_lowercase());
         self.resource_index.contains_key(&key)
@@ -1419,6 +1673,117 @@ fn resource_key(stem: &str, extension: &str) -> String {
     format!("{stem}.{extension}")
 }
 
+fn source_count_key(source: &OverrideSource) -> &'static str {
+    match source {
+        OverrideSource::BaseGame => "base_game",
+        OverrideSource::Expansion => "expansion",
+        OverrideSource::Module => "module",
+        OverrideSource::Campaign => "campaign",
+        OverrideSource::OverrideDir => "override_dir",
+        OverrideSource::Workshop => "workshop",
+        OverrideSource::CustomOverride => "custom_override",
+        OverrideSource::Hak(_) => "hak",
+    }
+}
+
+fn icon_index_json(icon_paths: &HashMap<String, PathBuf>) -> String {
+    let mut names: Vec<&String> = icon_paths.keys().collect();
+    names.sort();
+
+    let icons: Vec<serde_json::Value> = names
+        .iter()
+        .map(|name| {
+            let path = &icon_paths[*name];
+            let source_type = detect_icon_format(path).map_or_else(
+                || {
+                    path.extension()
+                        .and_then(|e| e.to_str())
+                        .map(str::to_lowercase)
+                        .unwrap_or_default()
+                },
+                str::to_string,
+            );
+            let byte_size = std::fs::metadata(path).map(|m| m.len()).ok();
+            let dimensions = read_icon_dimensions(path, &source_type);
+
+            serde_json::json!({
+                "name": name,
+                "source_type": source_type,
+                "width": dimensions.map(|(w, _)| w),
+                "height": dimensions.map(|(_, h)| h),
+                "byte_size": byte_size,
+            })
+        })
+        .collect();
+
+    let fallback_paths: serde_json::Map<String, serde_json::Value> = names
+        .iter()
+        .map(|name| {
+            (
+                (*name).clone(),
+                serde_json::json!(icon_paths[*name].to_string_lossy()),
+            )
+        })
+        .collect();
+
+    let index = serde_json::json!({
+        "icon_count": icons.len(),
+        "icons": icons,
+        "fallback_paths": fallback_paths,
+    });
+    serde_json::to_string_pretty(&index).unwrap_or_default()
+}
+
+const TGA_FOOTER_SIGNATURE: &[u8; 18] = b"TRUEVISION-XFILE.\0";
+
+/// Identify an icon's format from its magic bytes (or the TGA 2.0 footer).
+/// Returns `None` for signature-less data such as old-style TGAs, in which
+/// case callers fall back to the file extension or a TGA decode.
+pub fn sniff_icon_format(data: &[u8]) -> Option<&'static str> {
+    if data.starts_with(b"DDS ") {
+        return Some("dds");
+    }
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return Some("png");
+    }
+    if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        return Some("jpg");
+    }
+    if data.ends_with(TGA_FOOTER_SIGNATURE) {
+        return Some("tga");
+    }
+    None
+}
+
+fn detect_icon_format(path: &Path) -> Option<&'static str> {
+    sniff_icon_format(&std::fs::read(path).ok()?)
+}
+
+/// Read width/height from the image header without decoding pixel data.
+fn read_icon_dimensions(path: &Path, source_type: &str) -> Option<(u32, u32)> {
+    use std::io::Read;
+
+    let mut header = [0u8; 24];
+    std::fs::File::open(path)
+        .ok()?
+        .read_exact(&mut header)
+        .ok()?;
+
+    let le_u32 = |at: usize| u32::from_le_bytes(header[at..at + 4].try_into().unwrap_or_default());
+    let le_u16 = |at: usize| u32::from(u16::from_le_bytes([header[at], header[at + 1]]));
+
+    match source_type {
+        "dds" if &header[0..4] == b"DDS " => Some((le_u32(16), le_u32(12))),
+        "tga" => Some((le_u16(12), le_u16(14))),
+        "png" if &header[1..4] == b"PNG" => {
+            let be_u32 =
+                |at: usize| u32::from_be_bytes(header[at..at + 4].try_into().unwrap_or_default());
+            Some((be_u32(16), be_u32(20)))
+        }
+        _ => None,
+    }
+}
+
 fn gff_value_to_json(value: &crate::parsers::gff::GffValue<'_>) -> serde_json::Value {
     use crate::parsers::gff::GffValue;
 
@@ -1505,3 +1870,347 @@ fn gff_value_to_json(value: &crate::parsers::gff::GffValue<'_>) -> serde_json::V
         GffValue::ListRef(indices) => serde_json::json!({ "list_ref": indices }),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::erf::{ErfBuilder, ErfType};
+
+    /// Write a HAK holding the given `(name, resource type, data)` resources.
+    fn write_hak(path: &Path, resources: &[(&str, u16, &[u8])]) {
+        let mut builder = ErfBuilder::new(ErfType::HAK);
+        for (name, resource_type, data) in resources {
+            builder = builder.add_resource_with_type(name, *resource_type, data.to_vec());
+        }
+        builder.build().write(path).unwrap();
+    }
+
+    /// Write a directory module whose module.ifo lists `haks`.
+    fn write_module_dir(dir: &Path, haks: &[&str]) {
+        use crate::parsers::gff::{GffValue, GffWriter};
+        use std::borrow::Cow;
+
+        let hak_list = haks
+            .iter()
+            .map(|hak| {
+                IndexMap::from([(
+                    "Mod_Hak".to_string(),
+                    GffValue::String(Cow::Owned((*hak).to_string())),
+                )])
+            })
+            .collect();
+        let root = IndexMap::from([("Mod_HakList".to_string(), GffValue::ListOwned(hak_list))]);
+
+        std::fs::create_dir_all(dir).unwrap();
+        let ifo = GffWriter::new("IFO ", "V3.2").write(root).unwrap();
+        std::fs::write(dir.join("module.ifo"), ifo).unwrap();
+    }
+
+    #[test]
+    fn test_icon_index_json_lists_names_and_source_types() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut tga = vec![0u8; 18];
+        tga[12..14].copy_from_slice(&32u16.to_le_bytes());
+        tga[14..16].copy_from_slice(&48u16.to_le_bytes());
+        tga.extend_from_slice(&[0u8; 16]);
+        let tga_path = dir.path().join("ife_bless.tga");
+        std::fs::write(&tga_path, &tga).unwrap();
+
+        let mut dds = vec![0u8; 128];
+        dds[0..4].copy_from_slice(b"DDS ");
+        dds[12..16].copy_from_slice(&64u32.to_le_bytes());
+        dds[16..20].copy_from_slice(&64u32.to_le_bytes());
+        let dds_path = dir.path().join("is_fireball.dds");
+        std::fs::write(&dds_path, &dds).unwrap();
+
+        let mut icons = HashMap::new();
+        icons.insert("ife_bless".to_string(), tga_path);
+        icons.insert("is_fireball".to_string(), dds_path);
+
+        let json: serde_json::Value = serde_json::from_str(&icon_index_json(&icons)).unwrap();
+        assert_eq!(json["icon_count"], 2);
+
+        let entries = json["icons"].as_array().unwrap();
+        assert_eq!(entries[0]["name"], "ife_bless");
+        assert_eq!(entries[0]["source_type"], "tga");
+        assert_eq!(entries[0]["width"], 32);
+        assert_eq!(entries[0]["height"], 48);
+        assert_eq!(entries[1]["name"], "is_fireball");
+        assert_eq!(entries[1]["source_type"], "dds");
+        assert_eq!(entries[1]["byte_size"], 128);
+
+        assert!(
+            json["fallback_paths"]["is_fireball"]
+                .as_str()
+                .unwrap()
+                .ends_with("is_fireball.dds")
+        );
+    }
+
+    #[test]
+    fn test_index_hak_icons_reads_tga_and_dds_by_stem() {
+        let dir = tempfile::tempdir().unwrap();
+        let hak_path = dir.path().join("custom.hak");
+        write_hak(
+            &hak_path,
+            &[
+                ("IT_Custom_Sword.tga", 3, &[1, 2, 3, 4]),
+                ("is_custom.dds", 2033, &[5, 6]),
+                ("custom.2da", 2017, b"2DA V2.0\n"),
+            ],
+        );
+
+        let icons = module_loader::index_hak_icons(&hak_path).unwrap();
+        assert_eq!(icons.len(), 2);
+        assert_eq!(icons["it_custom_sword"].read().unwrap(), [1, 2, 3, 4]);
+        assert_eq!(icons["is_custom"].read().unwrap(), [5, 6]);
+    }
+
+    #[test]
+    fn test_get_icons_batch_prefers_hak_icons_and_skips_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        let icon_path = dir.path().join("ife_bless.tga");
+        std::fs::write(&icon_path, [9u8, 9]).unwrap();
+
+        let hak_path = dir.path().join("icons.hak");
+        write_hak(&hak_path, &[("it_custom.tga", 3, &[1, 2, 3])]);
+
+        let mut manager = ResourceManager::new(Arc::new(RwLock::new(NWN2Paths::new())));
+        manager
+            .icon_file_paths
+            .insert("ife_bless".to_string(), icon_path);
+        manager.add_hak_icons(&[hak_path]);
+
+        let names = vec![
+            "IFE_Bless".to_string(),
+            "it_custom".to_string(),
+            "missing".to_string(),
+        ];
+        let icons = manager.get_icons_batch(&names);
+        assert_eq!(icons.len(), 2);
+        assert_eq!(&*icons["ife_bless"], &[9, 9]);
+        assert_eq!(&*icons["it_custom"], &[1, 2, 3]);
+    }
+
+    #[test]
+    fn test_add_hak_icons_overrides_base_game_icon() {
+        let dir = tempfile::tempdir().unwrap();
+        let base_icon = dir.path().join("ife_bless.tga");
+        std::fs::write(&base_icon, [0u8; 4]).unwrap();
+
+        let hak_path = dir.path().join("icons.hak");
+        write_hak(
+            &hak_path,
+            &[
+                ("ife_bless.tga", 3, &[7, 7, 7]),
+                ("ife_custom.tga", 3, &[8]),
+            ],
+        );
+
+        let mut manager = ResourceManager::new(Arc::new(RwLock::new(NWN2Paths::new())));
+        manager
+            .icon_file_paths
+            .insert("ife_bless".to_string(), base_icon);
+
+        let stats = manager.add_hak_icons(&[hak_path]);
+        assert_eq!(
+            stats,
+            HakIconStats {
+                added: 1,
+                overridden: 1
+            }
+        );
+        assert_eq!(&*manager.get_icon_bytes("ife_bless").unwrap(), &[7, 7, 7]);
+        assert_eq!(&*manager.get_icon_bytes("ife_custom").unwrap(), &[8]);
+    }
+
+    #[tokio::test]
+    async fn test_set_module_drops_previous_module_hak_icons() {
+        let docs = tempfile::tempdir().unwrap();
+        let hak_dir = docs.path().join("hak");
+        std::fs::create_dir_all(&hak_dir).unwrap();
+        write_hak(&hak_dir.join("icons_a.hak"), &[("it_from_a.tga", 3, &[1])]);
+        write_hak(&hak_dir.join("icons_b.hak"), &[("it_from_b.tga", 3, &[2])]);
+        let module_a = docs.path().join("module_a");
+        let module_b = docs.path().join("module_b");
+        write_module_dir(&module_a, &["icons_a"]);
+        write_module_dir(&module_b, &["icons_b"]);
+
+        let mut paths = NWN2Paths::new();
+        paths.set_documents_folder_for_test(docs.path().to_path_buf());
+        let mut manager = ResourceManager::new(Arc::new(RwLock::new(paths)));
+
+        manager.set_module(&module_a).await.unwrap();
+        assert_eq!(manager.get_icon_bytes("it_from_a"), Some(vec![1]));
+
+        manager.set_module(&module_b).await.unwrap();
+        assert_eq!(manager.get_icon_bytes("it_from_a"), None);
+        assert_eq!(manager.get_icon_bytes("it_from_b"), Some(vec![2]));
+
+        // Switching back restores A from the module cache, without B's icons
+        manager.set_module(&module_a).await.unwrap();
+        assert_eq!(manager.get_icon_bytes("it_from_a"), Some(vec![1]));
+        assert_eq!(manager.get_icon_bytes("it_from_b"), None);
+    }
+
+    #[test]
+    fn test_detect_icon_format_ignores_misleading_extensions() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut dds = vec![0u8; 128];
+        dds[0..4].copy_from_slice(b"DDS ");
+        dds[12..16].copy_from_slice(&128u32.to_le_bytes());
+        dds[16..20].copy_from_slice(&128u32.to_le_bytes());
+        let dds_path = dir.path().join("ife_upscaled.tga");
+        std::fs::write(&dds_path, &dds).unwrap();
+
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        png.extend_from_slice(&[0u8; 16]);
+        let png_path = dir.path().join("is_png.dds");
+        std::fs::write(&png_path, &png).unwrap();
+
+        let mut tga = vec![0u8; 18];
+        tga.extend_from_slice(&[0u8; 8]);
+        tga.extend_from_slice(TGA_FOOTER_SIGNATURE);
+        let tga_path = dir.path().join("is_tga.png");
+        std::fs::write(&tga_path, &tga).unwrap();
+
+        let plain_tga_path = dir.path().join("is_old.tga");
+        std::fs::write(&plain_tga_path, [0u8; 40]).unwrap();
+
+        assert_eq!(detect_icon_format(&dds_path), Some("dds"));
+        assert_eq!(detect_icon_format(&png_path), Some("png"));
+        assert_eq!(detect_icon_format(&tga_path), Some("tga"));
+        assert_eq!(detect_icon_format(&plain_tga_path), None);
+
+        let mut icons = HashMap::new();
+        icons.insert("ife_upscaled".to_string(), dds_path);
+        icons.insert("is_old".to_string(), plain_tga_path);
+        let json: serde_json::Value = serde_json::from_str(&icon_index_json(&icons)).unwrap();
+        let entries = json["icons"].as_array().unwrap();
+        assert_eq!(entries[0]["source_type"], "dds");
+        assert_eq!(entries[0]["width"], 128);
+        assert_eq!(entries[1]["source_type"], "tga");
+    }
+
+    #[test]
+    fn test_icon_stats_count_sources_and_lookups() {
+        let dir = tempfile::tempdir().unwrap();
+        let base_icon = dir.path().join("ife_bless.tga");
+        let workshop_icon = dir.path().join("ife_mod.dds");
+        std::fs::write(&base_icon, [0u8; 4]).unwrap();
+        std::fs::write(&workshop_icon, [0u8; 4]).unwrap();
+
+        let mut manager = ResourceManager::new(Arc::new(RwLock::new(NWN2Paths::new())));
+        manager
+            .icon_file_paths
+            .insert("ife_bless".to_string(), base_icon);
+        manager
+            .icon_file_paths
+            .insert("ife_mod".to_string(), workshop_icon.clone());
+        manager.resource_index.insert(
+            resource_key("ife_mod", "dds"),
+            vec![ResourceLocation::from_file(
+                OverrideSource::Workshop,
+                workshop_icon,
+                0.0,
+            )],
+        );
+        let hak_path = dir.path().join("icons.hak");
+        write_hak(&hak_path, &[("ife_hak.dds", 2033, &[1, 2, 3])]);
+        manager.add_hak_icons(&[hak_path]);
+
+        assert!(manager.get_icon_bytes("ife_bless").is_some());
+        assert!(manager.get_icon_bytes("IFE_HAK").is_some());
+        assert!(manager.get_icon_bytes("ife_mod").is_some());
+        assert!(manager.get_icon_bytes("missing").is_none());
+
+        let stats = manager.get_icon_stats();
+        assert_eq!(stats.total, 3);
+        assert_eq!(stats.source_counts["base_game"], 1);
+        assert_eq!(stats.source_counts["workshop"], 1);
+        assert_eq!(stats.source_counts["hak"], 1);
+        assert_eq!((stats.hits, stats.misses), (3, 1));
+        assert!((stats.hit_ratio - 0.75).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_icon_lookups_count_resource_fallback_and_batches() {
+        let dir = tempfile::tempdir().unwrap();
+        let override_icon = dir.path().join("ife_override.tga");
+        std::fs::write(&override_icon, [5u8, 6]).unwrap();
+
+        let mut manager = ResourceManager::new(Arc::new(RwLock::new(NWN2Paths::new())));
+        manager.resource_index.insert(
+            resource_key("ife_override", "tga"),
+            vec![ResourceLocation::from_file(
+                OverrideSource::OverrideDir,
+                override_icon,
+                0.0,
+            )],
+        );
+
+        assert_eq!(manager.get_icon_bytes("IFE_Override"), Some(vec![5, 6]));
+        let names = vec!["ife_override".to_string(), "missing".to_string()];
+        assert_eq!(manager.get_icons_batch(&names).len(), 1);
+
+        let stats = manager.get_icon_stats();
+        assert_eq!((stats.hits, stats.misses), (2, 1));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_icons_indexes_icon_folder_created_later() {
+        let game = tempfile::tempdir().unwrap();
+        let mut paths = NWN2Paths::new();
+        paths.set_game_folder_for_test(game.path().to_path_buf());
+        let mut manager = ResourceManager::new(Arc::new(RwLock::new(paths)));
+
+        manager.scan_icon_directories().await.unwrap();
+        assert!(!manager.icons_stale());
+        assert!(manager.get_icon_bytes("ife_new").is_none());
+
+        let icons = game.path().join("ui").join("default").join("icons");
+        std::fs::create_dir_all(&icons).unwrap();
+        std::fs::write(icons.join("ife_new.tga"), [1u8, 2]).unwrap();
+
+        // Still inside the check interval of the first call
+        assert!(!manager.icons_stale());
+        *manager.icon_stale_checked_at.lock() = None;
+        assert!(manager.icons_stale());
+
+        assert!(manager.refresh_icons_if_stale().await.unwrap());
+        assert_eq!(manager.get_icon_bytes("ife_new"), Some(vec![1, 2]));
+        assert!(!manager.refresh_icons_if_stale().await.unwrap());
+    }
+
+    #[test]
+    fn test_icon_dir_stamp_detects_changes_and_covers_icon_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let icons = dir
+            .path()
+            .join("123")
+            .join("ui")
+            .join("default")
+            .join("icons");
+        let overrides = dir.path().join("123").join("override");
+        std::fs::create_dir_all(&icons).unwrap();
+        std::fs::create_dir_all(&overrides).unwrap();
+
+        let stamp = IconDirStamp::new(dir.path().to_path_buf(), WORKSHOP_ICON_DIR_DEPTH, true);
+        assert!(!stamp.is_stale());
+        assert!(stamp.covers(&icons.join("ife_new.dds")));
+        assert!(!stamp.covers(&overrides.join("texture.dds")));
+
+        let later = std::time::SystemTime::now() + std::time::Duration::from_mins(1);
+        std::fs::File::open(&icons)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert!(stamp.is_stale());
+
+        let base = IconDirStamp::new(icons.clone(), usize::MAX, false);
+        assert!(base.covers(&icons.join("ife_bless.tga")));
+        assert!(!base.covers(&overrides.join("ife_bless.tga")));
+    }
+}
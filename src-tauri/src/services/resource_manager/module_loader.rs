@@ -1,4 +1,6 @@
 use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
@@ -6,7 +8,7 @@ use hex;
 use indexmap::IndexMap;
 use tracing::{debug, warn};
 
-use crate::parsers::erf::ErfParser;
+use crate::parsers::erf::{ErfParser, ResourceEntry};
 use crate::parsers::gff::{GffParser, GffValue};
 use crate::parsers::tda::TDAParser;
 use crate::parsers::tlk::TLKParser;
@@ -17,6 +19,8 @@ use super::override_chain::{CampaignInfo, ModuleInfo};
 const ERF_TYPE_2DA: u16 = 2017;
 // const ERF_TYPE_TLK: u16 = 2018; // Unused
 const ERF_TYPE_IFO: u16 = 2014;
+const ERF_TYPE_TGA: u16 = 3;
+const ERF_TYPE_DDS: u16 = 2033;
 
 pub fn extract_module_info(module_path: &Path) -> ResourceManagerResult<ModuleInfo> {
     let is_directory = module_path.is_dir();
@@ -70,9 +74,9 @@ pub fn extract_module_info(module_path: &Path) -> ResourceManagerResult<ModuleIn
     info.custom_tlk = extract_string(&root_fields, "Mod_CustomTlk").unwrap_or_default();
     info.campaign_id = extract_string(&root_fields, "Campaign_ID");
 
-    if let Some(GffValue::ListOwned(hak_entries)) = root_fields.get("Mod_HakList") {
+    if let Some(GffValue::List(hak_entries)) = root_fields.get("Mod_HakList") {
         for entry in hak_entries {
-            if let Some(hak_name) = extract_string(entry, "Mod_Hak")
+            if let Some(hak_name) = extract_string(&entry.force_load(), "Mod_Hak")
                 && !hak_name.is_empty()
             {
                 info.hak_list.push(hak_name);
@@ -176,6 +180,59 @@ pub fn load_hak_2das(hak_path: &Path) -> ResourceManagerResult<HashMap<String, A
     Ok(overrides)
 }
 
+/// A TGA/DDS resource inside a HAK, located well enough to read it back
+/// without parsing the archive again.
+#[derive(Debug, Clone)]
+pub struct HakIcon {
+    pub hak_path: PathBuf,
+    pub entry: ResourceEntry,
+}
+
+impl HakIcon {
+    pub fn read(&self) -> ResourceManagerResult<Vec<u8>> {
+        let mut file = File::open(&self.hak_path)?;
+        file.seek(SeekFrom::Start(u64::from(self.entry.offset)))?;
+        let mut data = vec![0u8; self.entry.size as usize];
+        file.read_exact(&mut data)?;
+        Ok(data)
+    }
+}
+
+/// Index the TGA/DDS icon resources in a HAK by lowercase resref. Only the
+/// archive's tables are read; icon bytes are read on demand with
+/// `HakIcon::read`.
+pub fn index_hak_icons(hak_path: &Path) -> ResourceManagerResult<HashMap<String, HakIcon>> {
+    let mut erf = ErfParser::new();
+    erf.read(hak_path).map_err(|e| {
+        ResourceManagerError::InvalidErfFormat(format!(
+            "Failed to parse HAK {}: {}",
+            hak_path.display(),
+            e
+        ))
+    })?;
+
+    let mut icons = HashMap::new();
+    for (name, resource) in &erf.resources {
+        if !matches!(resource.key.resource_type, ERF_TYPE_TGA | ERF_TYPE_DDS) {
+            continue;
+        }
+        let stem = name
+            .rsplit_once('.')
+            .map_or(name.as_str(), |(stem, _)| stem);
+        icons.entry(stem.to_lowercase()).or_insert_with(|| HakIcon {
+            hak_path: hak_path.to_path_buf(),
+            entry: resource.entry.clone(),
+        });
+    }
+
+    debug!(
+        "Indexed {} icons in HAK {}",
+        icons.len(),
+        hak_path.display()
+    );
+    Ok(icons)
+}
+
 pub fn check_hak_for_tlk(hak_path: &Path) -> Option<PathBuf> {
     let hak_stem = hak_path.file_stem()?.to_str()?;
     let hak_dir = hak_path.parent()?;
@@ -69,6 +69,8 @@ pub struct ResourceLocation {
     pub container_path: PathBuf,
     pub internal_path: Option<String>,
     pub modified_time: f64,
+    #[serde(default)]
+    pub content_hash: Option<u64>,
 }
 
 impl ResourceLocation {
@@ -84,6 +86,7 @@ impl ResourceLocation {
             container_path: zip_path,
             internal_path: Some(internal),
             modified_time: mtime,
+            content_hash: None,
         }
     }
 
@@ -99,6 +102,7 @@ impl ResourceLocation {
             container_path: erf_path,
             internal_path: Some(internal),
             modified_time: mtime,
+            content_hash: None,
         }
     }
 
@@ -109,9 +113,15 @@ impl ResourceLocation {
             container_path: file_path,
             internal_path: None,
             modified_time: mtime,
+            content_hash: None,
         }
     }
 
+    pub fn with_content_hash(mut self, hash: Option<u64>) -> Self {
+        self.content_hash = hash;
+        self
+    }
+
     pub fn is_loose_file(&self) -> bool {
         matches!(self.container_type, ContainerType::Directory)
     }
@@ -0,0 +1,113 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+use xxhash_rust::xxh3::{Xxh3, xxh3_64};
+
+const READ_CHUNK: usize = 64 * 1024;
+
+/// xxh3 of a file's full contents, read in chunks so large files are never
+/// held in memory. Matches `content_hash_bytes` over the same bytes.
+pub fn content_hash(path: &Path) -> io::Result<u64> {
+    let mut file = File::open(path)?;
+    let mut hasher = Xxh3::new();
+    let mut buffer = vec![0u8; READ_CHUNK];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.digest())
+}
+
+/// xxh3 of an in-memory buffer.
+pub fn content_hash_bytes(data: &[u8]) -> u64 {
+    xxh3_64(data)
+}
+
+/// Size and modification time of a cache's source file, the cheap check
+/// `CacheValidation::Metadata` compares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceStamp {
+    pub size: u64,
+    /// Nanoseconds since the Unix epoch
+    pub modified_ns: u64,
+}
+
+impl SourceStamp {
+    pub fn of(path: &Path) -> io::Result<Self> {
+        let metadata = std::fs::metadata(path)?;
+        let modified_ns = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| {
+                u64::try_from(since.as_nanos()).unwrap_or(u64::MAX)
+            });
+        Ok(Self {
+            size: metadata.len(),
+            modified_ns,
+        })
+    }
+}
+
+/// How a parser's `load_with_cache` decides its cache still matches the
+/// source file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CacheValidation {
+    /// The source's size and mtime equal those recorded with the cache.
+    /// Only a `stat`, but a same-size rewrite with a restored mtime (e.g.
+    /// from a backup) goes unnoticed.
+    #[default]
+    Metadata,
+    /// The source's `content_hash` equals the one recorded with the cache.
+    /// Survives any mtime quirk, at the cost of reading the whole source on
+    /// every load.
+    ContentHash,
+}
+
+impl CacheValidation {
+    /// The stamp and hash to record with a cache freshly built from `path`.
+    /// The hash is only computed when this mode will check it.
+    pub fn record(self, path: &Path) -> (Option<SourceStamp>, Option<u64>) {
+        let hash = match self {
+            Self::Metadata => None,
+            Self::ContentHash => content_hash(path).ok(),
+        };
+        (SourceStamp::of(path).ok(), hash)
+    }
+
+    /// Whether `path` still matches what the cache recorded. `None` when a
+    /// `Metadata` check finds no recorded stamp (a cache written before
+    /// stamps existed), leaving the fallback to the caller; a
+    /// `ContentHash` check without a recorded hash is always stale.
+    pub fn check(self, path: &Path, stamp: Option<SourceStamp>, hash: Option<u64>) -> Option<bool> {
+        match self {
+            Self::Metadata => stamp.map(|recorded| SourceStamp::of(path).ok() == Some(recorded)),
+            Self::ContentHash => {
+                Some(hash.is_some_and(|recorded| content_hash(path).ok() == Some(recorded)))
+            }
+        }
+    }
+
+    /// `check`, falling back for a stamp-less cache to whether `cache_path`
+    /// was modified no earlier than `path`. Unreadable mtimes count as stale.
+    pub fn is_fresh(
+        self,
+        path: &Path,
+        cache_path: &Path,
+        stamp: Option<SourceStamp>,
+        hash: Option<u64>,
+    ) -> bool {
+        self.check(path, stamp, hash).unwrap_or_else(|| {
+            let modified = |p: &Path| std::fs::metadata(p).and_then(|m| m.modified()).ok();
+            match (modified(path), modified(cache_path)) {
+                (Some(source), Some(cache)) => cache >= source,
+                _ => false,
+            }
+        })
+    }
+}
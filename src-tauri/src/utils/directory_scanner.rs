@@ -1,37 +1,239 @@
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
+use rayon::prelude::*;
+use regex::Regex;
 use walkdir::WalkDir;
 
+use crate::utils::content_hash;
+
+#[derive(Debug, Clone)]
 pub struct ScannedFile {
     pub stem: String,
     pub extension: String,
     pub path: PathBuf,
     pub mtime: f64,
+    /// `content_hash` of the file; only set when scanning with `hash_contents`.
+    pub content_hash: Option<u64>,
+}
+
+/// Output of [`ResourceScanner::scan_tracked`], kept around so a later
+/// [`ResourceScanner::rescan_changed`] can skip unchanged directories.
+#[derive(Debug, Clone, Default)]
+pub struct ScanResults {
+    pub root: PathBuf,
+    pub files: Vec<ScannedFile>,
+    /// Modification time of every directory walked, keyed by path.
+    pub dir_mtimes: HashMap<PathBuf, f64>,
+    /// Directories whose files were listed (rather than reused) by this scan.
+    pub rescanned_dirs: Vec<PathBuf>,
+}
+
+/// Configurable directory scan. The free `scan_directory` is the plain
+/// recursive-or-not case; this adds opt-in extras like content hashing.
+#[derive(Debug, Clone)]
+pub struct ResourceScanner {
+    pub recursive: bool,
+    /// Hash every file's contents (in parallel) so changes are detectable
+    /// even when mtime is unreliable, e.g. on copied files.
+    pub hash_contents: bool,
+    exclude_globs: Vec<ExcludeGlob>,
+}
+
+impl Default for ResourceScanner {
+    fn default() -> Self {
+        Self {
+            recursive: true,
+            hash_contents: false,
+            exclude_globs: Vec::new(),
+        }
+    }
+}
+
+impl ResourceScanner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Skip files and directories matching any of `patterns` (case-insensitive).
+    /// `*` and `?` don't cross `/`; `**` does. Patterns without a `/` match the
+    /// entry name, others match the path relative to the scanned directory.
+    /// An excluded directory is not descended into.
+    pub fn set_exclude_globs(&mut self, patterns: Vec<String>) {
+        self.exclude_globs = patterns
+            .iter()
+            .filter_map(|pattern| ExcludeGlob::new(pattern))
+            .collect();
+    }
+
+    pub fn scan(&self, dir: &Path) -> Vec<ScannedFile> {
+        let max_depth = if self.recursive { usize::MAX } else { 1 };
+        let mut files = list_files(dir, dir, max_depth, &self.exclude_globs);
+        self.hash_files(&mut files);
+        files
+    }
+
+    /// Like [`scan`](Self::scan), but also records directory mtimes for
+    /// [`rescan_changed`](Self::rescan_changed).
+    pub fn scan_tracked(&self, dir: &Path) -> ScanResults {
+        self.rescan_changed(&ScanResults {
+            root: dir.to_path_buf(),
+            ..ScanResults::default()
+        })
+    }
+
+    /// Rescan `previous.root`, listing only directories whose mtime changed and
+    /// reusing `previous` entries for the rest. A directory's mtime changes when
+    /// files are added, removed or renamed in it, not when a file is edited in
+    /// place, so in-place edits need a full [`scan_tracked`](Self::scan_tracked).
+    pub fn rescan_changed(&self, previous: &ScanResults) -> ScanResults {
+        let root = previous.root.as_path();
+        let mut results = ScanResults {
+            root: root.to_path_buf(),
+            ..ScanResults::default()
+        };
+        if !root.exists() {
+            return results;
+        }
+
+        let mut reusable: HashMap<&Path, Vec<&ScannedFile>> = HashMap::new();
+        for file in &previous.files {
+            if let Some(parent) = file.path.parent() {
+                reusable.entry(parent).or_default().push(file);
+            }
+        }
+
+        let max_depth = if self.recursive { usize::MAX } else { 0 };
+        let dirs: Vec<(PathBuf, f64)> = WalkDir::new(root)
+            .max_depth(max_depth)
+            .into_iter()
+            .filter_entry(|e| e.depth() == 0 || !is_excluded(root, e.path(), &self.exclude_globs))
+            .filter_map(Result::ok)
+            .filter(|e| e.file_type().is_dir())
+            .map(|e| {
+                let mtime = entry_mtime(&e);
+                (e.into_path(), mtime)
+            })
+            .collect();
+
+        let mut listed = Vec::new();
+        for (dir, mtime) in dirs {
+            let unchanged = previous
+                .dir_mtimes
+                .get(&dir)
+                .is_some_and(|prev| prev.to_bits() == mtime.to_bits());
+            if unchanged {
+                if let Some(files) = reusable.get(dir.as_path()) {
+                    results.files.extend(files.iter().map(|f| (*f).clone()));
+                }
+            } else {
+                listed.extend(list_files(root, &dir, 1, &self.exclude_globs));
+                results.rescanned_dirs.push(dir.clone());
+            }
+            results.dir_mtimes.insert(dir, mtime);
+        }
+
+        self.hash_files(&mut listed);
+        results.files.extend(listed);
+        results
+    }
+
+    fn hash_files(&self, files: &mut [ScannedFile]) {
+        if self.hash_contents {
+            files.par_iter_mut().for_each(|file| {
+                file.content_hash = content_hash(&file.path).ok();
+            });
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ExcludeGlob {
+    regex: Regex,
+    /// Match against the relative path rather than just the entry name.
+    match_path: bool,
+}
+
+impl ExcludeGlob {
+    fn new(pattern: &str) -> Option<Self> {
+        Some(Self {
+            regex: glob_to_regex(pattern).ok()?,
+            match_path: pattern.contains(['/', '\\']),
+        })
+    }
+}
+
+fn glob_to_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    let mut expr = String::from("(?i)^");
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                expr.push_str(".*");
+            }
+            '*' => expr.push_str("[^/]*"),
+            '?' => expr.push_str("[^/]"),
+            '\\' => expr.push('/'),
+            _ => expr.push_str(&regex::escape(c.encode_utf8(&mut [0u8; 4]))),
+        }
+    }
+    expr.push('$');
+    Regex::new(&expr)
+}
+
+fn is_excluded(root: &Path, path: &Path, exclude: &[ExcludeGlob]) -> bool {
+    let name = path.file_name().map(|n| n.to_string_lossy());
+    let relative = path
+        .strip_prefix(root)
+        .map(|p| p.to_string_lossy().replace('\\', "/"));
+
+    exclude.iter().any(|glob| {
+        if glob.match_path {
+            relative
+                .as_deref()
+                .is_ok_and(|rel| glob.regex.is_match(rel))
+        } else {
+            name.as_deref().is_some_and(|n| glob.regex.is_match(n))
+        }
+    })
 }
 
 /// Scan a directory for all files, optionally recursive.
 /// Returns lowercase stem and extension for each file found.
 pub fn scan_directory(dir: &Path, recursive: bool) -> Vec<ScannedFile> {
+    let max_depth = if recursive { usize::MAX } else { 1 };
+    list_files(dir, dir, max_depth, &[])
+}
+
+fn entry_mtime(entry: &walkdir::DirEntry) -> f64 {
+    entry
+        .metadata()
+        .ok()
+        .and_then(|m| m.modified().ok())
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map_or(0.0, |d| d.as_secs_f64())
+}
+
+/// Files under `dir` down to `max_depth`; exclude globs are matched relative to `root`.
+fn list_files(
+    root: &Path,
+    dir: &Path,
+    max_depth: usize,
+    exclude: &[ExcludeGlob],
+) -> Vec<ScannedFile> {
     if !dir.exists() {
         return Vec::new();
     }
 
-    let walker = if recursive {
-        WalkDir::new(dir).into_iter()
-    } else {
-        WalkDir::new(dir).max_depth(1).into_iter()
-    };
-
-    walker
+    WalkDir::new(dir)
+        .max_depth(max_depth)
+        .into_iter()
+        .filter_entry(|e| e.depth() == 0 || !is_excluded(root, e.path(), exclude))
         .filter_map(Result::ok)
         .filter(|e| e.file_type().is_file())
         .filter_map(|entry| {
-            let mtime = entry
-                .metadata()
-                .ok()
-                .and_then(|m| m.modified().ok())
-                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-                .map_or(0.0, |d| d.as_secs_f64());
+            let mtime = entry_mtime(&entry);
             let path = entry.into_path();
             let stem = path.file_stem()?.to_str()?.to_lowercase();
             let ext = path.extension()?.to_str()?.to_lowercase();
@@ -41,11 +243,36 @@ pub fn scan_directory(dir: &Path, recursive: bool) -> Vec<ScannedFile> {
                 extension: ext,
                 path,
                 mtime,
+                content_hash: None,
             })
         })
         .collect()
 }
 
+/// Latest modification time (seconds since epoch) of `dir` and the
+/// directories below it, down to `max_depth` levels. Adding or removing a file
+/// bumps its parent directory's mtime, so this is a cheap staleness stamp.
+/// Returns `None` if `dir` doesn't exist.
+pub fn latest_dir_mtime(dir: &Path, max_depth: usize) -> Option<f64> {
+    if !dir.is_dir() {
+        return None;
+    }
+
+    WalkDir::new(dir)
+        .max_depth(max_depth)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_dir())
+        .filter_map(|e| {
+            e.metadata()
+                .ok()
+                .and_then(|m| m.modified().ok())
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs_f64())
+        })
+        .reduce(f64::max)
+}
+
 /// Scan Steam Workshop directory structure.
 /// Expects: `<workshop_dir>/<mod_id>/override/` layout.
 /// Recursively scans each mod's override subdirectory.
@@ -72,10 +299,38 @@ pub fn scan_workshop(workshop_dir: &Path) -> Vec<ScannedFile> {
         .collect()
 }
 
+/// Scan Steam Workshop items for UI icons.
+/// Mirrors the game's own layout: `<workshop_dir>/<mod_id>/ui/{upscaled,default}/icons/`.
+/// Upscaled icons are listed first so callers that keep the first hit prefer them.
+pub fn scan_workshop_icons(workshop_dir: &Path) -> Vec<ScannedFile> {
+    let Ok(entries) = std::fs::read_dir(workshop_dir) else {
+        return Vec::new();
+    };
+
+    let mut mod_dirs: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .collect();
+    mod_dirs.sort();
+
+    mod_dirs
+        .iter()
+        .flat_map(|mod_dir| {
+            ["upscaled", "default"]
+                .into_iter()
+                .flat_map(move |variant| {
+                    scan_directory(&mod_dir.join("ui").join(variant).join("icons"), true)
+                })
+        })
+        .filter(|file| matches!(file.extension.as_str(), "dds" | "tga" | "png"))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs;
+    use std::fs::{self, File};
     use tempfile::TempDir;
 
     #[test]
@@ -140,4 +395,154 @@ mod tests {
         let results = scan_workshop(temp.path());
         assert_eq!(results.len(), 2);
     }
+
+    #[test]
+    fn test_resource_scanner_hashes_contents() {
+        let temp = TempDir::new().unwrap();
+        let sub = temp.path().join("override");
+        fs::create_dir(&sub).unwrap();
+        fs::write(temp.path().join("classes.2da"), b"2DA V2.0").unwrap();
+        fs::write(sub.join("feat.2da"), b"2DA V2.0\n\nLabel").unwrap();
+
+        let scanner = ResourceScanner {
+            hash_contents: true,
+            ..ResourceScanner::new()
+        };
+        let first = scanner.scan(temp.path());
+        assert_eq!(first.len(), 2);
+        assert!(first.iter().all(|f| f.content_hash.is_some_and(|h| h != 0)));
+
+        let feat_path = sub.join("feat.2da");
+        let mtime = fs::metadata(&feat_path).unwrap().modified().unwrap();
+        fs::write(&feat_path, b"2DA V2.0\n\nLabel Name").unwrap();
+        File::options()
+            .write(true)
+            .open(&feat_path)
+            .unwrap()
+            .set_modified(mtime)
+            .unwrap();
+
+        let second = scanner.scan(temp.path());
+        let find = |files: &[ScannedFile], stem: &str| {
+            let file = files.iter().find(|f| f.stem == stem).unwrap();
+            (file.content_hash, file.mtime.to_bits())
+        };
+        let (old_hash, old_mtime) = find(&first, "feat");
+        let (new_hash, new_mtime) = find(&second, "feat");
+        assert_eq!(old_mtime, new_mtime);
+        assert_ne!(old_hash, new_hash);
+        assert_eq!(find(&first, "classes"), find(&second, "classes"));
+
+        assert!(
+            scan_directory(temp.path(), true)
+                .iter()
+                .all(|f| f.content_hash.is_none())
+        );
+    }
+
+    #[test]
+    fn test_scan_workshop_icons_reads_ui_icon_folders_only() {
+        let temp = TempDir::new().unwrap();
+        let upscaled = temp
+            .path()
+            .join("111")
+            .join("ui")
+            .join("upscaled")
+            .join("icons");
+        let default = temp
+            .path()
+            .join("222")
+            .join("ui")
+            .join("default")
+            .join("icons");
+        let override_dir = temp.path().join("333").join("override");
+        fs::create_dir_all(&upscaled).unwrap();
+        fs::create_dir_all(&default).unwrap();
+        fs::create_dir_all(&override_dir).unwrap();
+        fs::write(upscaled.join("ife_custom.dds"), b"DDS").unwrap();
+        fs::write(default.join("is_custom.tga"), b"TGA").unwrap();
+        fs::write(default.join("notes.txt"), b"txt").unwrap();
+        fs::write(override_dir.join("texture.dds"), b"DDS").unwrap();
+
+        let results = scan_workshop_icons(temp.path());
+        let stems: Vec<&str> = results.iter().map(|f| f.stem.as_str()).collect();
+        assert_eq!(stems, vec!["ife_custom", "is_custom"]);
+    }
+
+    #[test]
+    fn test_latest_dir_mtime_tracks_nested_directories() {
+        let temp = TempDir::new().unwrap();
+        let sub = temp.path().join("icons");
+        fs::create_dir(&sub).unwrap();
+
+        let before = latest_dir_mtime(temp.path(), 8).unwrap();
+        let later = std::time::SystemTime::now() + std::time::Duration::from_mins(1);
+        File::open(&sub).unwrap().set_modified(later).unwrap();
+
+        let after = latest_dir_mtime(temp.path(), 8).unwrap();
+        assert!(after > before);
+        assert!(latest_dir_mtime(temp.path(), 0).unwrap() < after);
+        assert!(latest_dir_mtime(&temp.path().join("missing"), 8).is_none());
+    }
+
+    #[test]
+    fn test_resource_scanner_skips_excluded_globs() {
+        let temp = TempDir::new().unwrap();
+        let backups = temp.path().join("Backups");
+        let nested = temp.path().join("override").join("tmp_work");
+        fs::create_dir_all(&backups).unwrap();
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(temp.path().join("classes.2da"), b"2DA").unwrap();
+        fs::write(temp.path().join("classes.2da.bak"), b"2DA").unwrap();
+        fs::write(backups.join("feat.2da"), b"2DA").unwrap();
+        fs::write(nested.join("spells.2da"), b"2DA").unwrap();
+        fs::write(temp.path().join("override").join("skills.2da"), b"2DA").unwrap();
+        fs::write(temp.path().join("override").join("old.bak"), b"2DA").unwrap();
+
+        let mut scanner = ResourceScanner::new();
+        scanner.set_exclude_globs(vec![
+            "backups".to_string(),
+            "*.bak".to_string(),
+            "override/tmp_*".to_string(),
+        ]);
+
+        let mut stems: Vec<String> = scanner
+            .scan(temp.path())
+            .into_iter()
+            .map(|f| f.stem)
+            .collect();
+        stems.sort();
+        assert_eq!(stems, vec!["classes", "skills"]);
+        assert_eq!(scan_directory(temp.path(), true).len(), 6);
+    }
+
+    #[test]
+    fn test_rescan_changed_only_lists_modified_directories() {
+        let temp = TempDir::new().unwrap();
+        let stable = temp.path().join("stable");
+        let changed = temp.path().join("changed");
+        fs::create_dir(&stable).unwrap();
+        fs::create_dir(&changed).unwrap();
+        fs::write(stable.join("classes.2da"), b"2DA").unwrap();
+        fs::write(changed.join("feat.2da"), b"2DA").unwrap();
+
+        let scanner = ResourceScanner::new();
+        let first = scanner.scan_tracked(temp.path());
+        assert_eq!(first.files.len(), 2);
+        assert_eq!(first.rescanned_dirs.len(), 3);
+
+        fs::write(changed.join("spells.2da"), b"2DA").unwrap();
+        let later = std::time::SystemTime::now() + std::time::Duration::from_mins(1);
+        File::open(&changed).unwrap().set_modified(later).unwrap();
+
+        let second = scanner.rescan_changed(&first);
+        assert_eq!(second.rescanned_dirs, vec![changed.clone()]);
+        let mut stems: Vec<&str> = second.files.iter().map(|f| f.stem.as_str()).collect();
+        stems.sort_unstable();
+        assert_eq!(stems, vec!["classes", "feat", "spells"]);
+
+        let third = scanner.rescan_changed(&second);
+        assert!(third.rescanned_dirs.is_empty());
+        assert_eq!(third.files.len(), 3);
+    }
 }
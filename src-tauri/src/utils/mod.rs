@@ -1,3 +1,4 @@
+pub mod content_hash;
 pub mod directory_scanner;
 pub mod parsing;
 pub mod path_discovery;
@@ -6,6 +7,7 @@ pub mod prerequisite_graph;
 pub mod zip_content_reader;
 pub mod zip_scanner;
 
+pub use content_hash::{CacheValidation, SourceStamp, content_hash, content_hash_bytes};
 pub use parsing::{Row, row_bool, row_int, row_str, safe_bool, safe_int};
 pub use path_discovery::{
     DiscoveryResult, PathTiming, discover_nwn2_paths_rust, profile_path_discovery_rust,
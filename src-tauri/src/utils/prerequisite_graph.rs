@@ -1,5 +1,6 @@
 use parking_lot::RwLock;
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Instant;
@@ -13,9 +14,11 @@ pub struct PrerequisiteGraph {
     is_built: bool,
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 struct Prerequisites {
     feats: Vec<u32>,
+    /// Alternative feat groups; any one feat from each group satisfies it.
+    or_feats: Vec<Vec<u32>>,
     abilities: HashMap<String, u32>,
     class: Option<u32>,
     level: u32,
@@ -23,12 +26,15 @@ struct Prerequisites {
     spell_level: u32,
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 struct GraphStats {
     total_feats: usize,
     feats_with_prereqs: usize,
     max_chain_depth: usize,
     circular_dependencies: Vec<u32>,
+    /// Distinct cycles, each starting at its lowest feat id and ending with
+    /// that id repeated, e.g. `[A, B, A]`.
+    circular_cycles: Vec<Vec<u32>>,
 }
 
 impl PrerequisiteGraph {
@@ -71,6 +77,33 @@ impl PrerequisiteGraph {
                 prereqs.feats.push(feat2 as u32);
             }
 
+            let or_group: Vec<u32> = (0..5)
+                .filter_map(|i| {
+                    feat_dict
+                        .get(&format!("orreqfeat{i}"))
+                        .and_then(serde_json::Value::as_i64)
+                })
+                .filter(|&feat| feat >= 0)
+                .map(|feat| feat as u32)
+                .collect();
+            if !or_group.is_empty() {
+                prereqs.or_feats.push(or_group);
+            }
+            if let Some(group) = feat_dict
+                .get("prereqorfeat")
+                .and_then(serde_json::Value::as_array)
+            {
+                let group: Vec<u32> = group
+                    .iter()
+                    .filter_map(serde_json::Value::as_i64)
+                    .filter(|&feat| feat >= 0)
+                    .map(|feat| feat as u32)
+                    .collect();
+                if !group.is_empty() {
+                    prereqs.or_feats.push(group);
+                }
+            }
+
             if let Some(min_str) = feat_dict.get("minstr").and_then(serde_json::Value::as_u64)
                 && min_str > 0
             {
@@ -114,6 +147,14 @@ impl PrerequisiteGraph {
                     .insert("charisma".to_string(), min_cha as u32);
             }
 
+            if let Some(class) = ["prereqclass", "reqclass"]
+                .iter()
+                .find_map(|key| feat_dict.get(*key).and_then(serde_json::Value::as_i64))
+                && class >= 0
+            {
+                prereqs.class = Some(class as u32);
+            }
+
             if let Some(min_level) = feat_dict
                 .get("minlevel")
                 .and_then(serde_json::Value::as_u64)
@@ -134,6 +175,7 @@ impl PrerequisiteGraph {
             }
 
             let has_prereqs = !prereqs.feats.is_empty()
+                || !prereqs.or_feats.is_empty()
                 || !prereqs.abilities.is_empty()
                 || prereqs.class.is_some()
                 || prereqs.level > 0
@@ -156,10 +198,11 @@ impl PrerequisiteGraph {
             .into_par_iter()
             .map(|feat_id| {
                 let mut visited = vec![false; total_feats];
+                let mut path = Vec::new();
                 Self::flatten_prerequisites_internal(
                     feat_id as u32,
                     &mut visited,
-                    1,
+                    &mut path,
                     &direct_prereqs,
                     &max_depth,
                     &circular_deps,
@@ -169,7 +212,13 @@ impl PrerequisiteGraph {
 
         self.feat_requirements = flattened_results;
         self.stats.max_chain_depth = *max_depth.read();
-        self.stats.circular_dependencies = circular_deps.read().clone();
+        let detected = circular_deps.read();
+        self.stats.circular_dependencies = detected
+            .iter()
+            .filter_map(|cycle| cycle.last().copied())
+            .collect();
+        self.stats.circular_cycles = Self::distinct_cycles(&detected);
+        drop(detected);
 
         self.build_time_ms = start.elapsed().as_millis() as f64;
         self.is_built = true;
@@ -177,23 +226,56 @@ impl PrerequisiteGraph {
         Ok(())
     }
 
+    /// MessagePack of the built graph, for caching across restarts keyed on
+    /// the feat.2da content.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        if !self.is_built {
+            return Err("Prerequisite graph has not been built".to_string());
+        }
+        rmp_serde::to_vec(&(
+            &self.feat_requirements,
+            &self.direct_prerequisites,
+            &self.stats,
+        ))
+        .map_err(|e| format!("Failed to serialize prerequisite graph: {e}"))
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self, String> {
+        let (feat_requirements, direct_prerequisites, stats) = rmp_serde::from_slice(data)
+            .map_err(|e| format!("Failed to deserialize prerequisite graph: {e}"))?;
+        Ok(PrerequisiteGraph {
+            feat_requirements,
+            direct_prerequisites,
+            stats,
+            build_time_ms: 0.0,
+            is_built: true,
+        })
+    }
+
+    /// `path` is the stack of feats above `feat_id`; a revisit records the
+    /// cycle from the first occurrence of `feat_id` on it.
     fn flatten_prerequisites_internal(
         feat_id: u32,
         visited: &mut Vec<bool>,
-        depth: usize,
+        path: &mut Vec<u32>,
         direct_prereqs: &[Prerequisites],
         max_depth: &Arc<RwLock<usize>>,
-        circular_deps: &Arc<RwLock<Vec<u32>>>,
+        circular_deps: &Arc<RwLock<Vec<Vec<u32>>>>,
     ) -> Vec<u32> {
         let idx = feat_id as usize;
 
         if idx >= visited.len() || visited[idx] {
-            if idx < visited.len() {
-                circular_deps.write().push(feat_id);
+            if idx < visited.len()
+                && let Some(start) = path.iter().position(|&f| f == feat_id)
+            {
+                let mut cycle = path[start..].to_vec();
+                cycle.push(feat_id);
+                circular_deps.write().push(cycle);
             }
             return Vec::new();
         }
 
+        let depth = path.len() + 1;
         {
             let mut max = max_depth.write();
             if depth > *max {
@@ -202,6 +284,7 @@ impl PrerequisiteGraph {
         }
 
         visited[idx] = true;
+        path.push(feat_id);
 
         let mut all_requirements = Vec::new();
         if idx < direct_prereqs.len() {
@@ -214,7 +297,7 @@ impl PrerequisiteGraph {
                 let nested = Self::flatten_prerequisites_internal(
                     req_feat,
                     visited,
-                    depth + 1,
+                    path,
                     direct_prereqs,
                     max_depth,
                     circular_deps,
@@ -227,10 +310,44 @@ impl PrerequisiteGraph {
             }
         }
 
+        path.pop();
         visited[idx] = false;
         all_requirements
     }
 
+    /// Every root that reaches a cycle reports it, starting from wherever it
+    /// entered; rotate each so the lowest id leads, then dedupe.
+    fn distinct_cycles(detected: &[Vec<u32>]) -> Vec<Vec<u32>> {
+        let mut cycles: Vec<Vec<u32>> = detected
+            .iter()
+            .filter(|cycle| cycle.len() >= 2)
+            .map(|cycle| {
+                let members = &cycle[..cycle.len() - 1];
+                let min_pos = members
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|&(_, &feat)| feat)
+                    .map_or(0, |(pos, _)| pos);
+                let mut rotated: Vec<u32> = members[min_pos..]
+                    .iter()
+                    .chain(&members[..min_pos])
+                    .copied()
+                    .collect();
+                rotated.push(rotated[0]);
+                rotated
+            })
+            .collect();
+        cycles.sort();
+        cycles.dedup();
+        cycles
+    }
+
+    /// Distinct prerequisite cycles found while building, e.g. `[A, B, A]`
+    /// when A requires B and B requires A.
+    pub fn get_circular_dependencies(&self) -> Vec<Vec<u32>> {
+        self.stats.circular_cycles.clone()
+    }
+
     pub fn get_all_feat_requirements(&self, feat_id: u32) -> Vec<u32> {
         if !self.is_built {
             return Vec::new();
@@ -244,6 +361,55 @@ impl PrerequisiteGraph {
         }
     }
 
+    /// Every path through the direct feat prerequisites of `feat_id`, starting
+    /// at `feat_id` and ending at a feat with no feat prerequisites. Paths stop
+    /// early rather than revisit a feat (circular data) or exceed the graph's
+    /// `max_chain_depth`. Empty if the feat has no feat prerequisites.
+    pub fn get_prerequisite_chains(&self, feat_id: u32) -> Vec<Vec<u32>> {
+        let mut chains = Vec::new();
+        if !self.is_built || !self.has_feat_prereqs(feat_id) {
+            return chains;
+        }
+
+        let mut path = vec![feat_id];
+        self.collect_chains(&mut path, &mut chains);
+        chains
+    }
+
+    fn has_feat_prereqs(&self, feat_id: u32) -> bool {
+        self.direct_prerequisites
+            .get(feat_id as usize)
+            .is_some_and(|p| !p.feats.is_empty())
+    }
+
+    fn collect_chains(&self, path: &mut Vec<u32>, chains: &mut Vec<Vec<u32>>) {
+        let Some(&current) = path.last() else {
+            return;
+        };
+        let next: Vec<u32> = self
+            .direct_prerequisites
+            .get(current as usize)
+            .map(|p| {
+                p.feats
+                    .iter()
+                    .copied()
+                    .filter(|feat| !path.contains(feat))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        if next.is_empty() || path.len() >= self.stats.max_chain_depth {
+            chains.push(path.clone());
+            return;
+        }
+
+        for feat in next {
+            path.push(feat);
+            self.collect_chains(path, chains);
+            path.pop();
+        }
+    }
+
     pub fn get_direct_prerequisites(&self, feat_id: u32) -> HashMap<String, serde_json::Value> {
         let mut result = HashMap::new();
         let idx = feat_id as usize;
@@ -251,6 +417,7 @@ impl PrerequisiteGraph {
         if idx < self.direct_prerequisites.len() {
             let prereqs = &self.direct_prerequisites[idx];
             result.insert("feats".to_string(), serde_json::json!(prereqs.feats));
+            result.insert("or_feats".to_string(), serde_json::json!(prereqs.or_feats));
             result.insert(
                 "abilities".to_string(),
                 serde_json::json!(prereqs.abilities),
@@ -264,6 +431,10 @@ impl PrerequisiteGraph {
             );
         } else {
             result.insert("feats".to_string(), serde_json::json!(Vec::<u32>::new()));
+            result.insert(
+                "or_feats".to_string(),
+                serde_json::json!(Vec::<Vec<u32>>::new()),
+            );
             result.insert(
                 "abilities".to_string(),
                 serde_json::json!(HashMap::<String, u32>::new()),
@@ -287,67 +458,57 @@ impl PrerequisiteGraph {
             return (true, Vec::new());
         }
 
-        let mut errors = Vec::new();
+        let char_has_feat = self.char_has_feat(character_feats);
+        let errors = self.feat_errors(feat_id, &char_has_feat, character_data);
 
-        let mut char_has_feat = vec![false; self.feat_requirements.len()];
-        for &feat in character_feats {
-            let idx = feat as usize;
-            if idx < char_has_feat.len() {
-                char_has_feat[idx] = true;
-            }
-        }
+        (errors.is_empty(), errors)
+    }
 
-        let idx = feat_id as usize;
-        if idx < self.feat_requirements.len() {
-            for &req_feat in &self.feat_requirements[idx] {
-                let req_idx = req_feat as usize;
-                if req_idx >= char_has_feat.len() || !char_has_feat[req_idx] {
-                    errors.push(format!("Requires Feat {req_feat}"));
-                }
-            }
+    pub fn validate_batch_fast(
+        &self,
+        feat_ids: Vec<u32>,
+        character_feats: &[u32],
+        character_data: Option<&HashMap<String, serde_json::Value>>,
+    ) -> HashMap<u32, (bool, Vec<String>)> {
+        if !self.is_built {
+            return HashMap::new();
         }
 
-        if let Some(data) = character_data
-            && idx < self.direct_prerequisites.len()
-        {
-            let prereqs = &self.direct_prerequisites[idx];
-
-            for (ability, min_score) in &prereqs.abilities {
-                if let Some(current) = data.get(ability).and_then(serde_json::Value::as_u64)
-                    && (current as u32) < *min_score
-                {
-                    errors.push(format!("Requires {} {}", ability.to_uppercase(), min_score));
-                }
-            }
+        let char_has_feat = self.char_has_feat(character_feats);
 
-            if prereqs.level > 0
-                && let Some(level) = data.get("level").and_then(serde_json::Value::as_u64)
-                && (level as u32) < prereqs.level
-            {
-                errors.push(format!("Requires character level {}", prereqs.level));
-            }
+        let mut results = HashMap::new();
 
-            if prereqs.bab > 0
-                && let Some(bab) = data.get("bab").and_then(serde_json::Value::as_u64)
-                && (bab as u32) < prereqs.bab
-            {
-                errors.push(format!("Requires base attack bonus +{}", prereqs.bab));
-            }
+        for feat_id in feat_ids {
+            let errors = self.feat_errors(feat_id, &char_has_feat, character_data);
+            results.insert(feat_id, (errors.is_empty(), errors));
         }
 
-        (errors.is_empty(), errors)
+        results
     }
 
-    pub fn validate_batch_fast(
+    /// Feat ids the character does not have yet but meets every prerequisite
+    /// for, in ascending order.
+    pub fn available_feats(
         &self,
-        feat_ids: Vec<u32>,
         character_feats: &[u32],
         character_data: Option<&HashMap<String, serde_json::Value>>,
-    ) -> HashMap<u32, (bool, Vec<String>)> {
+    ) -> Vec<u32> {
         if !self.is_built {
-            return HashMap::new();
+            return Vec::new();
         }
 
+        let char_has_feat = self.char_has_feat(character_feats);
+
+        (0..self.feat_requirements.len() as u32)
+            .filter(|&feat_id| !char_has_feat[feat_id as usize])
+            .filter(|&feat_id| {
+                self.feat_errors(feat_id, &char_has_feat, character_data)
+                    .is_empty()
+            })
+            .collect()
+    }
+
+    fn char_has_feat(&self, character_feats: &[u32]) -> Vec<bool> {
         let mut char_has_feat = vec![false; self.feat_requirements.len()];
         for &feat in character_feats {
             let idx = feat as usize;
@@ -355,54 +516,90 @@ impl PrerequisiteGraph {
                 char_has_feat[idx] = true;
             }
         }
+        char_has_feat
+    }
 
-        let mut results = HashMap::new();
+    fn feat_errors(
+        &self,
+        feat_id: u32,
+        char_has_feat: &[bool],
+        character_data: Option<&HashMap<String, serde_json::Value>>,
+    ) -> Vec<String> {
+        let mut errors = Vec::new();
+        let idx = feat_id as usize;
 
-        for feat_id in feat_ids {
-            let mut errors = Vec::new();
-            let idx = feat_id as usize;
-
-            if idx < self.feat_requirements.len() {
-                for &required in &self.feat_requirements[idx] {
-                    let req_idx = required as usize;
-                    if req_idx >= char_has_feat.len() || !char_has_feat[req_idx] {
-                        errors.push(format!("Requires Feat {required}"));
-                    }
+        if idx < self.feat_requirements.len() {
+            for &required in &self.feat_requirements[idx] {
+                let req_idx = required as usize;
+                if req_idx >= char_has_feat.len() || !char_has_feat[req_idx] {
+                    errors.push(format!("Requires Feat {required}"));
                 }
             }
+        }
 
-            if let Some(data) = character_data
-                && idx < self.direct_prerequisites.len()
-            {
-                let prereqs = &self.direct_prerequisites[idx];
-
-                for (ability, min_score) in &prereqs.abilities {
-                    if let Some(current) = data.get(ability).and_then(serde_json::Value::as_u64)
-                        && (current as u32) < *min_score
-                    {
-                        errors.push(format!("Requires {} {}", ability.to_uppercase(), min_score));
-                    }
+        if let Some(prereqs) = self.direct_prerequisites.get(idx) {
+            let owns = |feat: &u32| char_has_feat.get(*feat as usize).copied() == Some(true);
+            for group in &prereqs.or_feats {
+                if !group.iter().any(owns) {
+                    let options: Vec<String> = group.iter().map(u32::to_string).collect();
+                    errors.push(format!("Requires one of Feats {}", options.join(", ")));
                 }
+            }
 
-                if prereqs.level > 0
-                    && let Some(level) = data.get("level").and_then(serde_json::Value::as_u64)
-                    && (level as u32) < prereqs.level
-                {
-                    errors.push(format!("Requires character level {}", prereqs.level));
-                }
+            if let Some(data) = character_data {
+                Self::check_character_requirements(prereqs, data, &mut errors);
+            }
+        }
 
-                if prereqs.bab > 0
-                    && let Some(bab) = data.get("bab").and_then(serde_json::Value::as_u64)
-                    && (bab as u32) < prereqs.bab
-                {
-                    errors.push(format!("Requires base attack bonus +{}", prereqs.bab));
-                }
+        errors
+    }
+
+    /// Non-feat requirements shared by the single and batch validators. A
+    /// requirement is only checked when `data` carries the matching key;
+    /// `spell_level` is the highest spell level the character can cast.
+    fn check_character_requirements(
+        prereqs: &Prerequisites,
+        data: &HashMap<String, serde_json::Value>,
+        errors: &mut Vec<String>,
+    ) {
+        for (ability, min_score) in &prereqs.abilities {
+            if let Some(current) = data.get(ability).and_then(serde_json::Value::as_u64)
+                && (current as u32) < *min_score
+            {
+                errors.push(format!("Requires {} {}", ability.to_uppercase(), min_score));
             }
+        }
 
-            results.insert(feat_id, (errors.is_empty(), errors));
+        if let Some(class) = prereqs.class
+            && let Some(classes) = data.get("classes").and_then(serde_json::Value::as_array)
+            && !classes
+                .iter()
+                .filter_map(serde_json::Value::as_u64)
+                .any(|c| c == u64::from(class))
+        {
+            errors.push(format!("Requires class {class}"));
         }
 
-        results
+        if prereqs.level > 0
+            && let Some(level) = data.get("level").and_then(serde_json::Value::as_u64)
+            && (level as u32) < prereqs.level
+        {
+            errors.push(format!("Requires character level {}", prereqs.level));
+        }
+
+        if prereqs.bab > 0
+            && let Some(bab) = data.get("bab").and_then(serde_json::Value::as_u64)
+            && (bab as u32) < prereqs.bab
+        {
+            errors.push(format!("Requires base attack bonus +{}", prereqs.bab));
+        }
+
+        if prereqs.spell_level > 0
+            && let Some(spell_level) = data.get("spell_level").and_then(serde_json::Value::as_u64)
+            && (spell_level as u32) < prereqs.spell_level
+        {
+            errors.push(format!("Requires spell level {}", prereqs.spell_level));
+        }
     }
 
     pub fn get_statistics(&self) -> HashMap<String, serde_json::Value> {
@@ -18,12 +18,19 @@ pub struct ZipReadResult {
     pub success: bool,
     pub data: Option<Vec<u8>>,
     pub error: Option<String>,
+    /// Archive the data came from (or was requested from, on failure).
+    pub source_archive: Option<String>,
 }
 
 pub struct ZipContentReader {
     open_archives: HashMap<String, ZipArchive<BufReader<File>>>,
     file_indices: HashMap<String, HashMap<String, usize>>,
     basename_indices: HashMap<String, HashMap<String, String>>,
+    /// Archives passed to `open_many`, in priority order.
+    indexed_archives: Vec<String>,
+    /// Lowercase basename -> every (position in `indexed_archives`, entry index)
+    /// holding it, lowest priority first.
+    resource_index: HashMap<String, Vec<(usize, usize)>>,
     files_read: u64,
     bytes_read: u64,
     archives_opened: u64,
@@ -36,6 +43,8 @@ impl ZipContentReader {
             open_archives: HashMap::new(),
             file_indices: HashMap::new(),
             basename_indices: HashMap::new(),
+            indexed_archives: Vec::new(),
+            resource_index: HashMap::new(),
             files_read: 0,
             bytes_read: 0,
             archives_opened: 0,
@@ -60,9 +69,13 @@ impl ZipContentReader {
             .and_then(|indices| indices.get(&internal_path).copied())
             .ok_or_else(|| format!("File not found in ZIP index: {internal_path}"))?;
 
+        self.read_entry(&zip_path, index, &internal_path)
+    }
+
+    fn read_entry(&mut self, zip_path: &str, index: usize, name: &str) -> Result<Vec<u8>, String> {
         let archive = self
             .open_archives
-            .get_mut(&zip_path)
+            .get_mut(zip_path)
             .ok_or_else(|| format!("Failed to access ZIP archive: {zip_path}"))?;
 
         let mut file = archive
@@ -71,13 +84,133 @@ impl ZipContentReader {
 
         let mut contents = Vec::new();
         file.read_to_end(&mut contents)
-            .map_err(|e| format!("Failed to read file {internal_path}: {e}"))?;
+            .map_err(|e| format!("Failed to read file {name}: {e}"))?;
 
         self.files_read += 1;
         self.bytes_read += contents.len() as u64;
         Ok(contents)
     }
 
+    /// Open `zip_paths` and build one index over all of them, keyed by
+    /// lowercase basename, so `read` can resolve a resource without knowing
+    /// which archive holds it. `zip_paths` is in ascending priority: later
+    /// archives (e.g. HAKs) shadow earlier ones, matching NWN2 override order,
+    /// while shadowed copies stay reachable through `read_all_versions`.
+    /// Replaces any previous `open_many` index and returns the number of
+    /// distinct resources indexed.
+    pub fn open_many(&mut self, zip_paths: &[String]) -> Result<usize, String> {
+        self.indexed_archives.clear();
+        self.resource_index.clear();
+
+        for zip_path in zip_paths {
+            if !self.open_archives.contains_key(zip_path) {
+                self.open_archive(zip_path)?;
+            }
+
+            let archive_idx = self.indexed_archives.len();
+            self.indexed_archives.push(zip_path.clone());
+
+            let (Some(basenames), Some(indices)) = (
+                self.basename_indices.get(zip_path),
+                self.file_indices.get(zip_path),
+            ) else {
+                continue;
+            };
+            for (basename, internal_path) in basenames {
+                if let Some(&entry_idx) = indices.get(internal_path) {
+                    self.resource_index
+                        .entry(basename.clone())
+                        .or_default()
+                        .push((archive_idx, entry_idx));
+                }
+            }
+        }
+
+        Ok(self.resource_index.len())
+    }
+
+    /// Read a resource by basename from the archives indexed by `open_many`,
+    /// taking the highest-priority copy.
+    pub fn read(&mut self, resource_name: &str) -> Result<Vec<u8>, String> {
+        let name = resource_name.to_lowercase();
+        let (archive_idx, entry_idx) = self
+            .resource_index
+            .get(&name)
+            .and_then(|versions| versions.last().copied())
+            .ok_or_else(|| format!("Resource not found in indexed archives: {resource_name}"))?;
+
+        self.read_indexed(archive_idx, entry_idx, &name)
+    }
+
+    /// Like `read`, but reports which archive provided the resource.
+    pub fn read_with_source(&mut self, resource_name: &str) -> ZipReadResult {
+        let source_archive = self.source_of(resource_name).map(str::to_string);
+        let (data, error) = match self.read(resource_name) {
+            Ok(data) => (Some(data), None),
+            Err(e) => (None, Some(e)),
+        };
+        ZipReadResult {
+            request_id: resource_name.to_string(),
+            success: data.is_some(),
+            data,
+            error,
+            source_archive,
+        }
+    }
+
+    /// Archive that `read` resolves `resource_name` to.
+    pub fn source_of(&self, resource_name: &str) -> Option<&str> {
+        let versions = self.resource_index.get(&resource_name.to_lowercase())?;
+        let &(archive_idx, _) = versions.last()?;
+        Some(self.indexed_archives[archive_idx].as_str())
+    }
+
+    /// Every indexed copy of `resource_name`, highest priority first. Useful
+    /// for debugging which archive shadows which.
+    pub fn read_all_versions(&mut self, resource_name: &str) -> Vec<ZipReadResult> {
+        let name = resource_name.to_lowercase();
+        let versions = self.resource_index.get(&name).cloned().unwrap_or_default();
+
+        versions
+            .into_iter()
+            .rev()
+            .map(|(archive_idx, entry_idx)| {
+                let source_archive = Some(self.indexed_archives[archive_idx].clone());
+                match self.read_indexed(archive_idx, entry_idx, &name) {
+                    Ok(data) => ZipReadResult {
+                        request_id: resource_name.to_string(),
+                        success: true,
+                        data: Some(data),
+                        error: None,
+                        source_archive,
+                    },
+                    Err(e) => ZipReadResult {
+                        request_id: resource_name.to_string(),
+                        success: false,
+                        data: None,
+                        error: Some(e),
+                        source_archive,
+                    },
+                }
+            })
+            .collect()
+    }
+
+    fn read_indexed(
+        &mut self,
+        archive_idx: usize,
+        entry_idx: usize,
+        name: &str,
+    ) -> Result<Vec<u8>, String> {
+        let zip_path = self.indexed_archives[archive_idx].clone();
+        if self.open_archives.contains_key(&zip_path) {
+            self.cache_hits += 1;
+        } else {
+            self.open_archive(&zip_path)?;
+        }
+        self.read_entry(&zip_path, entry_idx, name)
+    }
+
     pub fn read_multiple_files(&mut self, requests: Vec<ZipReadRequest>) -> Vec<ZipReadResult> {
         let mut results = Vec::new();
 
@@ -99,6 +232,7 @@ impl ZipContentReader {
                         success: false,
                         data: None,
                         error: Some(format!("Failed to open ZIP: {e}")),
+                        source_archive: Some(zip_path.clone()),
                     });
                 }
                 continue;
@@ -118,6 +252,7 @@ impl ZipContentReader {
                                         success: true,
                                         data: Some(contents),
                                         error: None,
+                                        source_archive: Some(zip_path.clone()),
                                     });
                                 }
                                 Err(e) => {
@@ -126,6 +261,7 @@ impl ZipContentReader {
                                         success: false,
                                         data: None,
                                         error: Some(e.to_string()),
+                                        source_archive: Some(zip_path.clone()),
                                     });
                                 }
                             }
@@ -136,6 +272,7 @@ impl ZipContentReader {
                                 success: false,
                                 data: None,
                                 error: Some(format!("File not found: {e}")),
+                                source_archive: Some(zip_path.clone()),
                             });
                         }
                     }
@@ -159,12 +296,14 @@ impl ZipContentReader {
                         success: true,
                         data: Some(data),
                         error: None,
+                        source_archive: Some(req.zip_path.clone()),
                     },
                     Err(e) => ZipReadResult {
                         request_id: req.request_id.clone(),
                         success: false,
                         data: None,
                         error: Some(e),
+                        source_archive: Some(req.zip_path.clone()),
                     },
                 },
             )
@@ -38,6 +38,32 @@ pub fn load_test_gff(name: &str) -> Vec<u8> {
     })
 }
 
+/// A minimal TLK V3.0 (language 0) holding `strings` as StrRefs 0.., each
+/// with the text-present flag set and no sound.
+#[allow(dead_code)]
+pub fn build_tlk(strings: &[&str]) -> Vec<u8> {
+    let data_offset = 20 + 40 * strings.len() as u32;
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"TLK V3.0");
+    bytes.extend_from_slice(&0u32.to_le_bytes());
+    bytes.extend_from_slice(&(strings.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&data_offset.to_le_bytes());
+
+    let mut offset = 0u32;
+    for s in strings {
+        bytes.extend_from_slice(&1u32.to_le_bytes());
+        bytes.extend_from_slice(&[0u8; 24]);
+        bytes.extend_from_slice(&offset.to_le_bytes());
+        bytes.extend_from_slice(&(s.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+        offset += s.len() as u32;
+    }
+    for s in strings {
+        bytes.extend_from_slice(s.as_bytes());
+    }
+    bytes
+}
+
 #[allow(dead_code)]
 pub async fn create_test_context() -> TestContext {
     let temp = TempDir::new().unwrap();
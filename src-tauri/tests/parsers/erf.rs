@@ -1,9 +1,11 @@
 use std::path::PathBuf;
 
+use super::super::common::build_tlk;
 use app_lib::parsers::erf::{
-    ErfBuilder, ErfParser, ErfType, ErfVersion, extension_to_resource_type,
-    resource_type_to_extension,
+    ErfBuilder, ErfError, ErfIntegrityIssue, ErfParser, ErfType, ErfVersion, ErfWarning,
+    erf_description, extension_to_resource_type, resource_type_name, resource_type_to_extension,
 };
+use app_lib::parsers::tlk::TLKParser;
 
 fn get_nwn2_data_path() -> Option<PathBuf> {
     let paths = app_lib::config::NWN2Paths::new();
@@ -120,6 +122,39 @@ fn test_unknown_resource_type() {
     assert_eq!(ext, "unk", "Unknown resource type should return 'unk'");
 }
 
+#[test]
+fn test_resource_type_name() {
+    assert_eq!(resource_type_name(2017), "2DA");
+    assert_eq!(resource_type_name(2009), "NSS");
+    assert_eq!(resource_type_name(2015), "BIC");
+    assert_eq!(resource_type_name(2033), "DDS");
+    assert_eq!(resource_type_name(3005), "MDB");
+    assert_eq!(resource_type_name(9999), "unknown");
+}
+
+#[test]
+fn test_list_resources_detailed() {
+    let mut parser = ErfBuilder::new(ErfType::HAK)
+        .version(ErfVersion::V11)
+        .build();
+    parser
+        .add_resource("classes", 2017, b"2DA V2.0".to_vec())
+        .unwrap();
+    parser.add_resource("odd", 9999, vec![1, 2, 3]).unwrap();
+
+    let mut detailed = parser.list_resources_detailed(None);
+    detailed.sort_by(|a, b| a.name.cmp(&b.name));
+    assert_eq!(detailed.len(), 2);
+    assert_eq!(detailed[0].resource_type, 2017);
+    assert_eq!(detailed[0].type_name, "2DA");
+    assert_eq!(detailed[0].size, 8);
+    assert_eq!(detailed[1].type_name, "unknown");
+
+    let only_2da = parser.list_resources_detailed(Some(2017));
+    assert_eq!(only_2da.len(), 1);
+    assert_eq!(only_2da[0].name, detailed[0].name);
+}
+
 // =============================================================================
 // ARCHIVE CREATION TESTS
 // =============================================================================
@@ -193,6 +228,31 @@ fn test_add_multiple_resources() {
     assert_eq!(resources.len(), 3, "Should have 3 resources");
 }
 
+#[test]
+fn test_add_resource_case_collision() {
+    let mut parser = ErfBuilder::new(ErfType::HAK).build();
+    parser
+        .add_resource("Icon.tga", 3, b"first".to_vec())
+        .unwrap();
+
+    match parser.add_resource("ICON.TGA", 3, b"second".to_vec()) {
+        Err(ErfError::DuplicateResource { name }) => assert_eq!(name, "icon.tga"),
+        other => panic!("Expected DuplicateResource, got {other:?}"),
+    }
+    assert_eq!(parser.resources.len(), 1);
+    assert_eq!(parser.extract_resource("icon.tga").unwrap(), b"first");
+
+    let builder = ErfBuilder::new(ErfType::HAK).add_resource("Icon.tga", b"first".to_vec());
+    assert!(builder.has_resource("ICON.TGA"));
+    assert!(!builder.has_resource("icon.dds"));
+    assert!(matches!(
+        builder
+            .add_resource("ICON.TGA", b"second".to_vec())
+            .try_build(),
+        Err(ErfError::DuplicateResource { .. })
+    ));
+}
+
 #[test]
 fn test_add_large_resource() {
     let mut parser = ErfBuilder::new(ErfType::ERF)
@@ -214,6 +274,55 @@ fn test_add_large_resource() {
 // ROUND-TRIP TESTS
 // =============================================================================
 
+#[test]
+fn test_padded_signatures_accepted() {
+    let bytes = ErfBuilder::new(ErfType::HAK)
+        .add_resource("padded.2da", b"2DA V2.0".to_vec())
+        .build()
+        .to_bytes()
+        .unwrap();
+
+    for sig in [b"HAK\0", b"HAK "] {
+        let mut padded = bytes.clone();
+        padded[..4].copy_from_slice(sig);
+        let mut parser = ErfParser::new();
+        parser.parse_from_bytes(&padded).expect("padded signature");
+        assert_eq!(parser.erf_type, Some(ErfType::HAK));
+        assert!(parser.warnings().is_empty());
+    }
+    assert_eq!(ErfType::from_signature(b"MOD\0"), Some(ErfType::MOD));
+    assert_eq!(ErfType::from_signature(b"HA\0\0"), None);
+}
+
+#[test]
+fn test_unknown_signature_lenient_mode() {
+    let mut bytes = ErfBuilder::new(ErfType::ERF)
+        .add_resource("inside.2da", b"2DA V2.0".to_vec())
+        .build()
+        .to_bytes()
+        .unwrap();
+    bytes[..4].copy_from_slice(b"PWC ");
+
+    let mut strict = ErfParser::new();
+    assert!(matches!(
+        strict.parse_from_bytes(&bytes),
+        Err(ErfError::InvalidSignature { .. })
+    ));
+
+    let mut lenient = ErfParser::new().with_lenient_signature(true);
+    lenient
+        .parse_from_bytes(&bytes)
+        .expect("lenient parse should succeed");
+    assert_eq!(lenient.erf_type, None);
+    assert_eq!(
+        lenient.warnings(),
+        &[ErfWarning::UnknownSignature {
+            found: "PWC ".to_string()
+        }]
+    );
+    assert_eq!(lenient.extract_resource("inside.2da").unwrap(), b"2DA V2.0");
+}
+
 #[test]
 fn test_erf_round_trip_basic() {
     let mut parser = ErfBuilder::new(ErfType::ERF)
@@ -268,6 +377,56 @@ fn test_erf_round_trip_v11() {
     assert_eq!(parser2.get_statistics().total_resources, 1);
 }
 
+#[test]
+fn test_convert_version_v10_to_v11() {
+    let mut parser = ErfBuilder::new(ErfType::HAK)
+        .version(ErfVersion::V10)
+        .build();
+    parser
+        .add_resource("legacy_icon", 3, b"TGA data".to_vec())
+        .unwrap();
+    let mut legacy = ErfParser::new();
+    legacy
+        .parse_from_bytes(&parser.to_bytes().unwrap())
+        .expect("Failed to parse V1.0");
+
+    legacy
+        .convert_version(ErfVersion::V11)
+        .expect("Failed to convert");
+    let bytes = legacy.to_bytes().expect("Failed to serialize V1.1");
+    assert_eq!(&bytes[4..8], b"V1.1");
+
+    let mut converted = ErfParser::new();
+    converted
+        .parse_from_bytes(&bytes)
+        .expect("Failed to re-parse V1.1");
+    assert_eq!(converted.version, Some(ErfVersion::V11));
+    assert_eq!(
+        converted.extract_resource("legacy_icon.tga").unwrap(),
+        b"TGA data"
+    );
+    assert!(converted.validate().unwrap().is_empty());
+}
+
+#[test]
+fn test_convert_version_rejects_long_names() {
+    let mut parser = ErfBuilder::new(ErfType::HAK)
+        .version(ErfVersion::V11)
+        .build();
+    parser
+        .add_resource("a_name_longer_than_sixteen", 2017, b"x".to_vec())
+        .unwrap();
+
+    match parser.convert_version(ErfVersion::V10) {
+        Err(ErfError::ResourceNameTooLong { name, max }) => {
+            assert_eq!(name, "a_name_longer_than_sixteen");
+            assert_eq!(max, 16);
+        }
+        other => panic!("Expected ResourceNameTooLong, got {other:?}"),
+    }
+    assert_eq!(parser.version, Some(ErfVersion::V11));
+}
+
 #[test]
 fn test_hak_round_trip() {
     let mut parser = ErfBuilder::new(ErfType::HAK)
@@ -308,6 +467,109 @@ fn test_mod_round_trip() {
     assert_eq!(parser2.get_statistics().total_resources, 1);
 }
 
+#[test]
+fn test_append_to_file_without_preloading() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let path = temp_dir.path().join("append.hak");
+    ErfBuilder::new(ErfType::HAK)
+        .version(ErfVersion::V11)
+        .add_resource("existing.2da", b"Existing table".to_vec())
+        .add_resource("changed.2da", b"Old text".to_vec())
+        .build()
+        .write(&path)
+        .unwrap();
+
+    let mut parser = ErfParser::new();
+    parser.read(&path).unwrap();
+    parser
+        .add_resource("added", 2017, b"Added table".to_vec())
+        .unwrap();
+    parser
+        .update_resource("changed.2da", b"New, longer text".to_vec())
+        .unwrap();
+    assert!(parser.resources["existing.2da"].data.is_none());
+
+    let bytes = parser
+        .to_bytes()
+        .expect("untouched resources come from the mmap");
+
+    let mut parser2 = ErfParser::new();
+    parser2.parse_from_bytes(&bytes).unwrap();
+    assert_eq!(
+        parser2.extract_resource("existing.2da").unwrap(),
+        b"Existing table"
+    );
+    assert_eq!(
+        parser2.extract_resource("changed.2da").unwrap(),
+        b"New, longer text"
+    );
+    assert_eq!(
+        parser2.extract_resource("added.2da").unwrap(),
+        b"Added table"
+    );
+
+    // Saving back over the mapped source leaves the parser readable
+    parser.save(&path).unwrap();
+    assert!(parser.resources["existing.2da"].data.is_none());
+    assert_eq!(
+        parser.extract_resource("existing.2da").unwrap(),
+        b"Existing table"
+    );
+    assert_eq!(
+        parser.extract_resource("changed.2da").unwrap(),
+        b"New, longer text"
+    );
+
+    let mut reread = ErfParser::new();
+    reread.read(&path).unwrap();
+    assert_eq!(
+        reread.extract_resource("added.2da").unwrap(),
+        b"Added table"
+    );
+    assert!(!temp_dir.path().join("append.hak.tmp").exists());
+}
+
+#[test]
+fn test_write_to_another_path_keeps_reading_the_source() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let source = temp_dir.path().join("source.hak");
+    let copy = temp_dir.path().join("copy.hak");
+    ErfBuilder::new(ErfType::HAK)
+        .add_resource("existing.2da", b"Existing table".to_vec())
+        .build()
+        .write(&source)
+        .unwrap();
+    let source_bytes = std::fs::read(&source).unwrap();
+
+    let mut parser = ErfParser::new();
+    parser.read(&source).unwrap();
+    parser
+        .add_resource("added", 2017, b"Added table".to_vec())
+        .unwrap();
+    parser.save(&copy).unwrap();
+    let mut written = ErfParser::new();
+    written.read(&copy).unwrap();
+    assert!(written.contains("added.2da"));
+    drop(written);
+
+    // The copy gets the edit, the source is untouched, and the parser is
+    // still backed by the source rather than the copy
+    std::fs::write(&copy, b"clobbered").unwrap();
+    assert_eq!(std::fs::read(&source).unwrap(), source_bytes);
+    assert_eq!(
+        parser.metadata.as_ref().unwrap().file_path,
+        source.to_string_lossy()
+    );
+    assert_eq!(
+        parser.extract_resource("existing.2da").unwrap(),
+        b"Existing table"
+    );
+    assert_eq!(
+        parser.extract_resource("added.2da").unwrap(),
+        b"Added table"
+    );
+}
+
 // =============================================================================
 // LIST RESOURCES TESTS
 // =============================================================================
@@ -332,6 +594,64 @@ fn test_list_resources_filter_by_type() {
     assert_eq!(only_bic.len(), 1);
 }
 
+#[test]
+fn test_list_by_extension_matches_type_filter() {
+    let mut parser = ErfBuilder::new(ErfType::HAK)
+        .version(ErfVersion::V11)
+        .build();
+    parser
+        .add_resource("classes", 2017, b"2DA V2.0".to_vec())
+        .unwrap();
+    parser
+        .add_resource("feat", 2017, b"2DA V2.0".to_vec())
+        .unwrap();
+    parser
+        .add_resource("sword", 2025, b"UTI V3.2".to_vec())
+        .unwrap();
+
+    let mut by_ext = parser.list_by_extension("2da");
+    by_ext.sort();
+    let mut by_type: Vec<String> = parser
+        .list_resources(Some(2017))
+        .into_iter()
+        .map(|(name, _, _)| name)
+        .collect();
+    by_type.sort();
+    assert_eq!(by_ext.len(), 2);
+    assert_eq!(by_ext, by_type);
+
+    assert_eq!(parser.list_by_extension(".UTI").len(), 1);
+    assert!(parser.list_by_extension("xyz").is_empty());
+}
+
+#[test]
+fn test_resource_probe_without_extraction() {
+    let mut parser = ErfBuilder::new(ErfType::MOD)
+        .version(ErfVersion::V11)
+        .build();
+    parser
+        .add_resource("module.ifo", 2014, b"IFO V3.2".to_vec())
+        .unwrap();
+    parser
+        .add_resource("classes", 2017, b"2DA V2.0".to_vec())
+        .unwrap();
+
+    let bytes = parser.to_bytes().unwrap();
+    let mut parser2 = ErfParser::new();
+    parser2.parse_from_bytes(&bytes).unwrap();
+
+    assert!(parser2.contains("module.ifo"));
+    assert!(parser2.contains("CLASSES.2DA"));
+    assert!(!parser2.contains("feat.2da"));
+
+    assert_eq!(parser2.resource_type_of("Module.IFO"), Some(2014));
+    assert_eq!(parser2.resource_type_of("classes.2da"), Some(2017));
+    assert_eq!(parser2.resource_type_of("feat.2da"), None);
+
+    assert_eq!(parser2.resource_size("classes.2da"), Some(8));
+    assert_eq!(parser2.resource_size("feat.2da"), None);
+}
+
 // =============================================================================
 // EXTRACT RESOURCE TESTS
 // =============================================================================
@@ -351,6 +671,39 @@ fn test_extract_existing_resource() {
     assert_eq!(extracted.unwrap(), b"My Content");
 }
 
+#[test]
+fn test_resource_slice_does_not_cache() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let path = temp_dir.path().join("slice.hak");
+    ErfBuilder::new(ErfType::HAK)
+        .add_resource("classes.2da", b"2DA V2.0 classes".to_vec())
+        .add_resource("feat.2da", b"2DA V2.0 feat".to_vec())
+        .build()
+        .write(&path)
+        .unwrap();
+
+    let mut parser = ErfParser::new();
+    parser.read(&path).unwrap();
+
+    let slice = parser.resource_slice("CLASSES.2da").unwrap().to_vec();
+    assert!(parser.resources["classes.2da"].data.is_none());
+    assert_eq!(slice, parser.extract_resource("classes.2da").unwrap());
+    assert!(matches!(
+        parser.resource_slice("missing.2da"),
+        Err(ErfError::ResourceNotFound { .. })
+    ));
+
+    let mut from_bytes = ErfParser::new();
+    from_bytes
+        .parse_from_bytes(&std::fs::read(&path).unwrap())
+        .unwrap();
+    assert_eq!(
+        from_bytes.resource_slice("feat.2da").unwrap(),
+        b"2DA V2.0 feat"
+    );
+    assert!(from_bytes.resources["feat.2da"].data.is_none());
+}
+
 #[test]
 fn test_extract_nonexistent_resource() {
     let mut parser = ErfBuilder::new(ErfType::ERF)
@@ -364,6 +717,36 @@ fn test_extract_nonexistent_resource() {
     );
 }
 
+#[test]
+fn test_parallel_extraction_matches_sequential() {
+    let mut builder = ErfBuilder::new(ErfType::HAK).version(ErfVersion::V11);
+    for i in 0..32u8 {
+        builder = builder.add_resource(&format!("table{i}.2da"), vec![i; usize::from(i) * 7]);
+    }
+    let bytes = builder.build().to_bytes().unwrap();
+
+    let mut parser = ErfParser::new();
+    parser.parse_from_bytes(&bytes).unwrap();
+    let names: Vec<String> = parser
+        .list_resources(Some(2017))
+        .into_iter()
+        .map(|(name, _, _)| name)
+        .collect();
+
+    let parallel = parser.extract_many(&names).unwrap();
+    assert!(parser.resources.values().all(|r| r.data.is_none()));
+
+    for (name, data) in &parallel {
+        assert_eq!(data, &parser.extract_resource(name).unwrap());
+    }
+    assert_eq!(parallel.len(), 32);
+
+    assert!(matches!(
+        parser.extract_many(&["missing.2da".to_string()]),
+        Err(ErfError::ResourceNotFound { .. })
+    ));
+}
+
 // =============================================================================
 // BINARY DATA PRESERVATION
 // =============================================================================
@@ -464,6 +847,63 @@ fn test_statistics_after_add() {
     assert_eq!(resources_after.len(), 1, "Should have 1 resource");
 }
 
+// =============================================================================
+// DESCRIPTION STRREF TESTS
+// =============================================================================
+
+#[test]
+fn test_erf_description_resolves_strref() {
+    let mut tlk = TLKParser::new();
+    tlk.parse_from_bytes(&build_tlk(&["Bad Strref", "Other", "A hak of fine hats"]))
+        .expect("Failed to parse TLK");
+
+    let mut parser = ErfBuilder::new(ErfType::HAK)
+        .version(ErfVersion::V10)
+        .build();
+    parser.header.as_mut().unwrap().description_str_ref = 2;
+
+    let bytes = parser.to_bytes().unwrap();
+    let mut parser2 = ErfParser::new();
+    parser2.parse_from_bytes(&bytes).unwrap();
+
+    assert_eq!(
+        erf_description(&parser2, &mut tlk).as_deref(),
+        Some("A hak of fine hats")
+    );
+
+    parser2.header.as_mut().unwrap().description_str_ref = 0xFFFF_FFFF;
+    assert_eq!(erf_description(&parser2, &mut tlk), None);
+}
+
+#[test]
+fn test_localized_description_round_trip() {
+    let mut parser = ErfBuilder::new(ErfType::MOD)
+        .version(ErfVersion::V11)
+        .add_resource("module.ifo", b"IFO content".to_vec())
+        .build();
+    parser.set_description(vec![
+        (0, "A module of fine hats".to_string()),
+        (2, "Un module de beaux chapeaux".to_string()),
+    ]);
+
+    let bytes = parser.to_bytes().unwrap();
+    let mut parser2 = ErfParser::new();
+    parser2.parse_from_bytes(&bytes).unwrap();
+
+    assert_eq!(parser2.description(), parser.description());
+    let header = parser2.header.as_ref().unwrap();
+    assert_eq!(header.language_count, 2);
+    assert_eq!(header.offset_to_localized_string, 160);
+    assert_eq!(
+        header.offset_to_key_list,
+        160 + header.localized_string_size
+    );
+    assert_eq!(
+        parser2.extract_resource("module.ifo").unwrap(),
+        b"IFO content"
+    );
+}
+
 // =============================================================================
 // EDGE CASES
 // =============================================================================
@@ -517,3 +957,117 @@ fn test_empty_content() {
     let extracted = parser2.extract_resource("empty_file.2da").unwrap();
     assert!(extracted.is_empty());
 }
+
+#[test]
+fn test_zero_length_resource() {
+    let mut parser = ErfBuilder::new(ErfType::ERF)
+        .version(ErfVersion::V10)
+        .build();
+
+    parser
+        .add_resource("empty_file", 2017, vec![])
+        .expect("Should allow empty content");
+    parser
+        .add_resource("full_file", 2017, b"Content".to_vec())
+        .unwrap();
+
+    let bytes = parser.to_bytes().unwrap();
+    let mut parser2 = ErfParser::new();
+    parser2.parse_from_bytes(&bytes).unwrap();
+
+    assert_eq!(parser2.is_empty_resource("empty_file.2da"), Some(true));
+    assert_eq!(parser2.is_empty_resource("full_file.2da"), Some(false));
+    assert_eq!(parser2.is_empty_resource("missing.2da"), None);
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let written = parser2
+        .extract_all_by_type(2017, temp_dir.path())
+        .expect("Failed to extract");
+    assert_eq!(written.len(), 2);
+
+    let empty_path = temp_dir.path().join("empty_file.2da");
+    assert!(empty_path.exists());
+    assert_eq!(std::fs::metadata(&empty_path).unwrap().len(), 0);
+    assert!(
+        parser2
+            .extract_resource("empty_file.2da")
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_hostile_offsets_error_instead_of_panicking() {
+    let mut parser = ErfBuilder::new(ErfType::ERF)
+        .version(ErfVersion::V10)
+        .build();
+    parser
+        .add_resource("victim", 2017, b"Content".to_vec())
+        .unwrap();
+    let bytes = parser.to_bytes().unwrap();
+
+    // Resource list follows the 160-byte header and one 24-byte V1.0 key
+    let mut crafted = bytes.clone();
+    crafted[184..188].copy_from_slice(&(u32::MAX - 1).to_le_bytes());
+    crafted[188..192].copy_from_slice(&10u32.to_le_bytes());
+
+    let mut parser2 = ErfParser::new();
+    parser2.parse_from_bytes(&crafted).unwrap();
+    assert!(matches!(
+        parser2.extract_resource("victim.2da"),
+        Err(ErfError::InvalidOffset { .. })
+    ));
+
+    let mut crafted = bytes;
+    crafted[24..28].copy_from_slice(&(u32::MAX - 8).to_le_bytes());
+    assert!(matches!(
+        ErfParser::new().parse_from_bytes(&crafted),
+        Err(ErfError::InvalidOffset { .. })
+    ));
+}
+
+#[test]
+fn test_validate_reports_overlap_and_duplicates() {
+    let bytes = ErfBuilder::new(ErfType::HAK)
+        .version(ErfVersion::V10)
+        .add_resource("first.2da", b"First table".to_vec())
+        .add_resource("second.2da", b"Second table".to_vec())
+        .build()
+        .to_bytes()
+        .unwrap();
+
+    let mut clean = ErfParser::new();
+    clean.parse_from_bytes(&bytes).unwrap();
+    assert!(clean.validate().unwrap().is_empty());
+
+    // Two 24-byte V1.0 keys follow the header, then the resource list;
+    // point the second entry at the first entry's data
+    let mut overlapping = bytes.clone();
+    let first_offset: [u8; 4] = bytes[208..212].try_into().unwrap();
+    overlapping[216..220].copy_from_slice(&first_offset);
+
+    let mut parser = ErfParser::new();
+    parser.parse_from_bytes(&overlapping).unwrap();
+    assert_eq!(
+        parser.validate().unwrap(),
+        vec![ErfIntegrityIssue::OverlappingResources {
+            first: "first.2da".to_string(),
+            second: "second.2da".to_string(),
+        }]
+    );
+
+    // Rename the second key to collide with the first
+    let mut duplicated = bytes.clone();
+    duplicated[184..200].copy_from_slice(&bytes[160..176]);
+
+    let mut parser = ErfParser::new();
+    parser.parse_from_bytes(&duplicated).unwrap();
+    let issues = parser.validate().unwrap();
+    assert!(issues.contains(&ErfIntegrityIssue::DuplicateName {
+        name: "first.2da".to_string()
+    }));
+    assert!(issues.contains(&ErfIntegrityIssue::EntryCountMismatch {
+        header: 2,
+        actual: 1
+    }));
+}
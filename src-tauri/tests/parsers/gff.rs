@@ -1,7 +1,13 @@
-use super::super::common::load_test_gff;
+use super::super::common::{build_tlk, load_test_gff};
+use app_lib::parsers::gff::load_multiple_files;
 use app_lib::parsers::gff::parser::GffParser;
-use app_lib::parsers::gff::types::GffValue;
+use app_lib::parsers::gff::types::{GffValue, LocalizedString, LocalizedSubstring};
 use app_lib::parsers::gff::writer::GffWriter;
+use app_lib::parsers::gff::{GffEditor, GffError};
+use app_lib::parsers::tlk::TLKParser;
+use indexmap::IndexMap;
+use std::borrow::Cow;
+use std::collections::HashSet;
 
 // =============================================================================
 // CORE PARSING TESTS
@@ -299,6 +305,67 @@ async fn test_gff_read_field_by_label() {
     }
 }
 
+#[test]
+fn test_gff_walk_emits_nested_and_list_paths() {
+    let mut equip: IndexMap<String, GffValue<'static>> = IndexMap::new();
+    equip.insert("Tag".into(), GffValue::String(Cow::Borrowed("sword")));
+    let mut item_a: IndexMap<String, GffValue<'static>> = IndexMap::new();
+    item_a.insert("Stack".into(), GffValue::Word(1));
+    let mut item_b: IndexMap<String, GffValue<'static>> = IndexMap::new();
+    item_b.insert("Stack".into(), GffValue::Word(5));
+    item_b.insert("Equip".into(), GffValue::StructOwned(Box::new(equip)));
+
+    let mut root: IndexMap<String, GffValue<'static>> = IndexMap::new();
+    root.insert("Str".into(), GffValue::Byte(14));
+    root.insert("ItemList".into(), GffValue::ListOwned(vec![item_a, item_b]));
+
+    let parser = reparse(root);
+
+    let paths: Vec<String> = parser.walk().map(|(path, _)| path).collect();
+    let set: HashSet<&str> = paths.iter().map(String::as_str).collect();
+
+    for expected in [
+        "Str",
+        "ItemList",
+        "ItemList/0",
+        "ItemList/0/Stack",
+        "ItemList/1/Stack",
+        "ItemList/1/Equip",
+        "ItemList/1/Equip/Tag",
+    ] {
+        assert!(set.contains(expected), "missing path {expected}");
+    }
+    assert_eq!(paths.len(), set.len());
+
+    for (path, value) in parser.walk() {
+        if path == "ItemList/1/Equip/Tag" {
+            assert!(matches!(value, GffValue::String(ref s) if s == "sword"));
+        }
+    }
+}
+
+#[test]
+fn test_gff_walk_stops_on_cycles() {
+    let child = || {
+        let mut map: IndexMap<String, GffValue<'static>> = IndexMap::new();
+        map.insert("X".into(), GffValue::StructOwned(Box::default()));
+        map.insert("Y".into(), GffValue::StructOwned(Box::default()));
+        map
+    };
+    let mut root: IndexMap<String, GffValue<'static>> = IndexMap::new();
+    root.insert("A".into(), GffValue::StructOwned(Box::new(child())));
+    root.insert("B".into(), GffValue::ListOwned(vec![child()]));
+    // Point every struct field and list entry at struct 1, so struct 1
+    // contains itself and the list element is struct 1 again
+    let mut bytes = make_cyclic_gff(write_bic(root), 1);
+    let list_offset = read_u32(&bytes, 48) as usize;
+    bytes[list_offset + 4..list_offset + 8].copy_from_slice(&1u32.to_le_bytes());
+    let parser = GffParser::from_bytes(bytes).expect("Failed to parse GFF");
+
+    let paths: Vec<String> = parser.walk().map(|(path, _)| path).collect();
+    assert_eq!(paths, vec!["A", "A/X", "A/Y", "B", "B/0"]);
+}
+
 // =============================================================================
 // ROUND-TRIP TESTS
 // =============================================================================
@@ -386,6 +453,680 @@ async fn test_gff_round_trip_preserves_data() {
     assert_eq!(original_str, round_trip_str, "Str should be preserved");
 }
 
+#[tokio::test]
+async fn test_gff_value_tree_rewrite_is_byte_stable() {
+    let original = load_test_gff("occidiooctavon/occidiooctavon1.bic");
+    let parser = GffParser::from_bytes(original.clone()).expect("Failed to parse GFF");
+
+    let rewritten = GffWriter::rewrite(&parser).expect("rewrite");
+    assert_eq!(rewritten, original);
+    let reparsed = GffParser::from_bytes(rewritten.clone()).expect("Failed to re-parse GFF");
+    assert_eq!(
+        reparsed.to_json_string().expect("rewritten json"),
+        parser.to_json_string().expect("original json")
+    );
+    assert_eq!(
+        reparsed.get_struct_id(0).unwrap(),
+        parser.get_struct_id(0).unwrap()
+    );
+
+    let rewritten_again = GffWriter::rewrite(&reparsed).expect("second rewrite");
+    assert_eq!(rewritten_again, rewritten);
+}
+
+#[test]
+fn test_gff_rewrite_keeps_original_layout() {
+    let original = interleaved_gff();
+    let parser = GffParser::from_bytes(original.clone()).expect("Failed to parse GFF");
+    assert!(matches!(
+        parser.get_value("Child/Note"),
+        Ok(GffValue::String(ref s)) if s == "hi"
+    ));
+
+    assert_ne!(write_bic(parser.to_value_tree().unwrap()), original);
+    assert_eq!(GffWriter::rewrite(&parser).expect("rewrite"), original);
+}
+
+/// A file laid out the way the game writes one rather than `GffWriter`: the
+/// child struct's fields sit between the root's, and its field indices come
+/// first.
+fn interleaved_gff() -> Vec<u8> {
+    let labels = ["Tag", "Child", "Str", "Note", "Name"];
+    let structs: [[u32; 3]; 2] = [[0xFFFF_FFFF, 8, 3], [7, 0, 2]];
+    let fields: [[u32; 3]; 5] = [[10, 0, 0], [14, 1, 1], [0, 2, 14], [10, 3, 9], [11, 4, 15]];
+    let field_data = [
+        &5u32.to_le_bytes()[..],
+        b"sword",
+        &2u32.to_le_bytes(),
+        b"hi",
+        &[3],
+        b"abc",
+    ]
+    .concat();
+    let field_indices: [u32; 5] = [2, 3, 0, 1, 4];
+
+    let mut bytes = b"BIC V3.2".to_vec();
+    let mut offset = 56;
+    for (size, count) in [
+        (structs.len() * 12, structs.len()),
+        (fields.len() * 12, fields.len()),
+        (labels.len() * 16, labels.len()),
+        (field_data.len(), field_data.len()),
+        (field_indices.len() * 4, field_indices.len() * 4),
+        (0, 0),
+    ] {
+        bytes.extend((offset as u32).to_le_bytes());
+        bytes.extend((count as u32).to_le_bytes());
+        offset += size;
+    }
+    for value in structs.iter().chain(&fields).flatten() {
+        bytes.extend(value.to_le_bytes());
+    }
+    for label in labels {
+        let mut entry = [0u8; 16];
+        entry[..label.len()].copy_from_slice(label.as_bytes());
+        bytes.extend(entry);
+    }
+    bytes.extend(field_data);
+    for index in field_indices {
+        bytes.extend(index.to_le_bytes());
+    }
+    bytes
+}
+
+#[test]
+fn test_gff_value_tree_edit_preserves_struct_ids() {
+    let mut property: IndexMap<String, GffValue<'static>> = IndexMap::new();
+    property.insert("__struct_id__".into(), GffValue::Dword(0));
+    property.insert("PropertyName".into(), GffValue::Word(15));
+    let mut item: IndexMap<String, GffValue<'static>> = IndexMap::new();
+    item.insert("__struct_id__".into(), GffValue::Dword(0x10));
+    item.insert("Tag".into(), GffValue::String(Cow::Borrowed("sword")));
+    item.insert("Cost".into(), GffValue::Dword64(1500));
+    item.insert("PropertiesList".into(), GffValue::ListOwned(vec![property]));
+    let mut combat: IndexMap<String, GffValue<'static>> = IndexMap::new();
+    combat.insert("__struct_id__".into(), GffValue::Dword(51882));
+    combat.insert("ArmorClass".into(), GffValue::Short(-2));
+
+    let mut root: IndexMap<String, GffValue<'static>> = IndexMap::new();
+    root.insert("Str".into(), GffValue::Byte(14));
+    root.insert("Experience".into(), GffValue::Dword(2500));
+    root.insert("ChallengeRating".into(), GffValue::Float(1.5));
+    root.insert(
+        "Conversation".into(),
+        GffValue::ResRef(Cow::Borrowed("c_npc")),
+    );
+    root.insert("CombatInfo".into(), GffValue::StructOwned(Box::new(combat)));
+    root.insert("ItemList".into(), GffValue::ListOwned(vec![item]));
+
+    let original = GffWriter::new("BIC ", "V3.2")
+        .write_with_struct_id(root, 0xFFFF_FFFF)
+        .expect("write");
+    let parser = GffParser::from_bytes(original.clone()).expect("Failed to parse GFF");
+
+    let rewritten = GffWriter::rewrite(&parser).expect("rewrite");
+    assert_eq!(rewritten, original);
+
+    let mut tree = parser.to_value_tree().expect("value tree");
+    tree.insert("Str".into(), GffValue::Byte(18));
+    let parser2 = reparse(tree);
+
+    assert!(matches!(parser2.get_value("Str"), Ok(GffValue::Byte(18))));
+    assert!(matches!(
+        parser2.get_value("CombatInfo"),
+        Ok(GffValue::Struct(ref s)) if s.struct_id == 51882
+    ));
+    assert!(matches!(
+        parser2.get_value("ItemList/0"),
+        Ok(GffValue::Struct(ref s)) if s.struct_id == 0x10
+    ));
+    assert!(matches!(
+        parser2.get_value("ItemList/0/Cost"),
+        Ok(GffValue::Dword64(1500))
+    ));
+    assert_eq!(parser2.get_struct_id(0).unwrap(), 0xFFFF_FFFF);
+}
+
+#[test]
+fn test_gff_value_tree_fails_on_unreadable_or_cyclic_structs() {
+    let parser = edit_target_gff();
+    let original = GffWriter::rewrite(&parser).expect("rewrite");
+    let struct_offset = read_u32(&original, 8) as usize;
+
+    // CombatInfo (struct 1) names a field entry past the field array
+    let mut broken = original.clone();
+    broken[struct_offset + 16..struct_offset + 20].copy_from_slice(&999u32.to_le_bytes());
+    let parser = GffParser::from_bytes(broken).expect("Failed to parse GFF");
+    assert!(parser.read_struct_fields(0).is_ok());
+    assert!(matches!(
+        parser.to_value_tree(),
+        Err(GffError::InvalidFieldIndex(999))
+    ));
+    assert!(GffWriter::rewrite(&parser).is_err());
+    assert!(parser.to_json_string().is_err());
+
+    // The CombatInfo field points back at the root
+    let parser = GffParser::from_bytes(make_cyclic_gff(original, 0)).expect("Failed to parse GFF");
+    assert!(matches!(
+        parser.to_value_tree(),
+        Err(GffError::StructCycle(0))
+    ));
+}
+
+fn edit_target_gff() -> std::sync::Arc<GffParser> {
+    let mut combat: IndexMap<String, GffValue<'static>> = IndexMap::new();
+    combat.insert("ArmorClass".into(), GffValue::Short(-2));
+    let mut item: IndexMap<String, GffValue<'static>> = IndexMap::new();
+    item.insert("StackSize".into(), GffValue::Word(1));
+
+    let mut root: IndexMap<String, GffValue<'static>> = IndexMap::new();
+    root.insert("Str".into(), GffValue::Byte(14));
+    root.insert("CombatInfo".into(), GffValue::StructOwned(Box::new(combat)));
+    root.insert("ItemList".into(), GffValue::ListOwned(vec![item]));
+
+    reparse(root)
+}
+
+fn write_bic(tree: IndexMap<String, GffValue<'static>>) -> Vec<u8> {
+    GffWriter::new("BIC ", "V3.2").write(tree).expect("write")
+}
+
+fn reparse(tree: IndexMap<String, GffValue<'static>>) -> std::sync::Arc<GffParser> {
+    GffParser::from_bytes(write_bic(tree)).expect("Failed to re-parse GFF")
+}
+
+/// Repoint every struct field in a written file at struct `target`.
+fn make_cyclic_gff(mut bytes: Vec<u8>, target: u32) -> Vec<u8> {
+    let field_offset = read_u32(&bytes, 16) as usize;
+    for i in 0..read_u32(&bytes, 20) as usize {
+        let field = field_offset + i * 12;
+        if read_u32(&bytes, field) == 14 {
+            bytes[field + 8..field + 12].copy_from_slice(&target.to_le_bytes());
+        }
+    }
+    bytes
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+#[test]
+fn test_gff_set_value_nested_struct_and_list_paths() {
+    let parser = edit_target_gff();
+
+    let edited = reparse(parser.set_value("Str", GffValue::Byte(18), false).unwrap());
+    assert!(matches!(edited.get_value("Str"), Ok(GffValue::Byte(18))));
+
+    let edited = reparse(
+        parser
+            .set_value("CombatInfo/ArmorClass", GffValue::Short(4), false)
+            .unwrap(),
+    );
+    assert!(matches!(
+        edited.get_value("CombatInfo/ArmorClass"),
+        Ok(GffValue::Short(4))
+    ));
+    assert!(matches!(edited.get_value("Str"), Ok(GffValue::Byte(14))));
+
+    let edited = reparse(
+        parser
+            .set_value("ItemList/0/StackSize", GffValue::Word(10), false)
+            .unwrap(),
+    );
+    assert!(matches!(
+        edited.get_value("ItemList/0/StackSize"),
+        Ok(GffValue::Word(10))
+    ));
+}
+
+#[test]
+fn test_gff_get_fields_matches_get_value() {
+    let mut combat: IndexMap<String, GffValue<'static>> = IndexMap::new();
+    combat.insert("ArmorClass".into(), GffValue::Short(-2));
+    combat.insert("BaseAttackBonus".into(), GffValue::Byte(6));
+    let mut root: IndexMap<String, GffValue<'static>> = IndexMap::new();
+    for (label, score) in [("Str", 14), ("Dex", 12), ("Con", 16), ("Int", 10)] {
+        root.insert(label.into(), GffValue::Byte(score));
+    }
+    root.insert("CombatInfo".into(), GffValue::StructOwned(Box::new(combat)));
+    let parser = reparse(root);
+
+    let paths = [
+        "Str",
+        "Dex",
+        "Con",
+        "Int",
+        "CombatInfo/ArmorClass",
+        "CombatInfo/BaseAttackBonus",
+        "CombatInfo/Missing",
+        "Wis",
+        "Str/Nested",
+    ];
+    let batch = parser.get_fields(&paths);
+
+    assert_eq!(batch.len(), paths.len());
+    for (path, value) in paths.iter().zip(&batch) {
+        let single = parser.get_value(path).ok();
+        assert_eq!(format!("{value:?}"), format!("{single:?}"), "{path}");
+    }
+    assert!(matches!(batch[2], Some(GffValue::Byte(16))));
+    assert!(matches!(batch[4], Some(GffValue::Short(-2))));
+    assert!(batch[6..].iter().all(Option::is_none));
+}
+
+#[test]
+fn test_gff_to_json_string() {
+    let mut item: IndexMap<String, GffValue<'static>> = IndexMap::new();
+    item.insert("__struct_id__".into(), GffValue::Dword(7));
+    item.insert("Tag".into(), GffValue::String(Cow::Borrowed("sword")));
+    let mut root: IndexMap<String, GffValue<'static>> = IndexMap::new();
+    root.insert("Str".into(), GffValue::Byte(14));
+    root.insert(
+        "FirstName".into(),
+        GffValue::LocString(LocalizedString {
+            string_ref: -1,
+            substrings: vec![LocalizedSubstring {
+                string: Cow::Borrowed("Aldanon"),
+                language: 0,
+                gender: 0,
+            }],
+        }),
+    );
+    root.insert("Blob".into(), GffValue::Void(Cow::Borrowed(&[1, 2, 255])));
+    root.insert("ItemList".into(), GffValue::ListOwned(vec![item]));
+    let parser = reparse(root);
+
+    let json: serde_json::Value =
+        serde_json::from_str(&parser.to_json_string().expect("to_json_string")).unwrap();
+
+    assert_eq!(json["Str"], 14);
+    assert_eq!(json["FirstName"]["string_ref"], -1);
+    assert_eq!(json["FirstName"]["substrings"][0]["string"], "Aldanon");
+    assert_eq!(json["FirstName"]["substrings"][0]["language"], 0);
+    assert_eq!(json["Blob"], serde_json::json!([1, 2, 255]));
+    assert_eq!(json["ItemList"][0]["Tag"], "sword");
+    assert_eq!(json["ItemList"][0]["__struct_id__"], 7);
+    assert!(json.get("__struct_id__").is_some());
+}
+
+#[test]
+fn test_gff_editor_preserves_unrelated_bytes() {
+    let parser = edit_target_gff();
+    let original = GffWriter::rewrite(&parser).expect("rewrite");
+
+    let mut editor = GffEditor::new(parser.clone());
+    editor.set("CombatInfo/ArmorClass", GffValue::Short(5));
+    let tree = editor.build().expect("build");
+    // Only the edited path is read; the item list stays lazy
+    assert!(matches!(
+        tree.get("CombatInfo"),
+        Some(GffValue::StructOwned(_))
+    ));
+    assert!(matches!(tree.get("ItemList"), Some(GffValue::List(_))));
+    let edited = write_bic(tree);
+
+    // Only the inline data of the edited field may differ.
+    assert_eq!(edited.len(), original.len());
+    let changed: Vec<usize> = (0..edited.len())
+        .filter(|&i| edited[i] != original[i])
+        .collect();
+    assert!(!changed.is_empty());
+    assert!(changed.last().unwrap() - changed[0] < 4, "{changed:?}");
+
+    let reparsed = GffParser::from_bytes(edited).expect("Failed to re-parse GFF");
+    assert!(matches!(
+        reparsed.get_value("CombatInfo/ArmorClass"),
+        Ok(GffValue::Short(5))
+    ));
+    assert!(matches!(reparsed.get_value("Str"), Ok(GffValue::Byte(14))));
+
+    let mut editor = GffEditor::new(parser.clone());
+    editor.set("Str", GffValue::Int(20));
+    assert!(matches!(editor.build(), Err(GffError::TypeMismatch(_))));
+    let tree = GffEditor::new(parser.clone())
+        .coerce(true)
+        .set("Str", GffValue::Int(20))
+        .build()
+        .expect("coerced build");
+    assert!(matches!(tree.get("Str"), Some(GffValue::Byte(20))));
+
+    let tree = GffEditor::new(parser)
+        .set("ItemList/0/StackSize", GffValue::Word(3))
+        .build()
+        .expect("list element build");
+    assert!(matches!(tree.get("CombatInfo"), Some(GffValue::Struct(_))));
+    let reparsed = reparse(tree);
+    assert!(matches!(
+        reparsed.get_value("ItemList/0/StackSize"),
+        Ok(GffValue::Word(3))
+    ));
+    assert!(matches!(
+        reparsed.get_value("CombatInfo/ArmorClass"),
+        Ok(GffValue::Short(-2))
+    ));
+}
+
+#[test]
+fn test_gff_set_value_rejects_bad_paths_and_type_changes() {
+    let parser = edit_target_gff();
+
+    for path in [
+        "Missing",
+        "ItemList/3/StackSize",
+        "Str/Inner",
+        "CombatInfo/Nope",
+    ] {
+        assert!(
+            matches!(
+                parser.set_value(path, GffValue::Byte(1), false),
+                Err(GffError::FieldNotFound(_))
+            ),
+            "{path} should not resolve"
+        );
+    }
+
+    assert!(matches!(
+        parser.set_value("Str", GffValue::Int(20), false),
+        Err(GffError::TypeMismatch(_))
+    ));
+    assert!(matches!(
+        parser.set_value("Str", GffValue::Int(300), true),
+        Err(GffError::ValueOutOfRange(_))
+    ));
+
+    let edited = reparse(parser.set_value("Str", GffValue::Int(20), true).unwrap());
+    assert!(matches!(edited.get_value("Str"), Ok(GffValue::Byte(20))));
+}
+
+#[test]
+fn test_gff_list_len_and_struct_labels() {
+    let parser = edit_target_gff();
+
+    assert_eq!(
+        parser.struct_labels("").unwrap(),
+        vec!["Str", "CombatInfo", "ItemList"]
+    );
+    assert_eq!(
+        parser.struct_labels("CombatInfo").unwrap(),
+        vec!["ArmorClass"]
+    );
+    assert_eq!(
+        parser.struct_labels("ItemList/0").unwrap(),
+        vec!["StackSize"]
+    );
+    assert_eq!(parser.list_len("ItemList").unwrap(), 1);
+
+    assert!(matches!(
+        parser.list_len("Str"),
+        Err(GffError::FieldNotFound(_))
+    ));
+    assert!(matches!(
+        parser.struct_labels("ItemList"),
+        Err(GffError::FieldNotFound(_))
+    ));
+    assert!(matches!(
+        parser.struct_labels("ItemList/1"),
+        Err(GffError::FieldNotFound(_))
+    ));
+}
+
+#[test]
+fn test_gff_unknown_field_type_strict_and_lenient() {
+    let mut root: IndexMap<String, GffValue<'static>> = IndexMap::new();
+    root.insert("Str".into(), GffValue::Byte(14));
+    root.insert("Vendor".into(), GffValue::Dword(7));
+    root.insert("Dex".into(), GffValue::Byte(12));
+    let mut bytes = write_bic(root);
+
+    // Retype the second field entry (12 bytes each) to an unknown id
+    let field_offset = u32::from_le_bytes(bytes[16..20].try_into().unwrap()) as usize;
+    bytes[field_offset + 12..field_offset + 16].copy_from_slice(&99u32.to_le_bytes());
+
+    let parser = GffParser::from_bytes(bytes).expect("Failed to parse GFF");
+    assert!(!parser.skips_unknown_fields());
+    assert!(matches!(
+        parser.read_struct_fields(0),
+        Err(GffError::UnsupportedFieldType(99))
+    ));
+
+    parser.set_skip_unknown_fields(true);
+    let root = parser.read_struct_fields(0).expect("lenient read");
+    assert!(matches!(root.get("Str"), Some(GffValue::Byte(14))));
+    assert!(matches!(root.get("Vendor"), Some(GffValue::Void(v)) if v.is_empty()));
+    assert!(matches!(root.get("Dex"), Some(GffValue::Byte(12))));
+
+    parser.read_struct_fields(0).expect("second lenient read");
+    let warnings = parser.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].label, "Vendor");
+    assert_eq!(warnings[0].field_type, 99);
+}
+
+#[test]
+fn test_gff_find_structs_by_id_breadth_first() {
+    let struct_with_id = |id: u32, label: &str| {
+        let mut map: IndexMap<String, GffValue<'static>> = IndexMap::new();
+        map.insert("__struct_id__".into(), GffValue::Dword(id));
+        map.insert(label.into(), GffValue::Byte(1));
+        map
+    };
+    let mut container = struct_with_id(3, "Tag");
+    container.insert(
+        "Inner".into(),
+        GffValue::StructOwned(Box::new(struct_with_id(7, "Stack"))),
+    );
+
+    let mut root: IndexMap<String, GffValue<'static>> = IndexMap::new();
+    root.insert("Str".into(), GffValue::Byte(14));
+    root.insert(
+        "Equip".into(),
+        GffValue::StructOwned(Box::new(struct_with_id(7, "Slot"))),
+    );
+    root.insert(
+        "ItemList".into(),
+        GffValue::ListOwned(vec![struct_with_id(7, "Stack"), container]),
+    );
+
+    let parser = reparse(root);
+
+    assert_eq!(
+        parser.find_structs_by_id(7),
+        vec!["Equip", "ItemList/0", "ItemList/1/Inner"]
+    );
+    assert_eq!(
+        parser.find_structs_by_id_within(7, 1),
+        vec!["Equip", "ItemList/0"]
+    );
+    assert_eq!(parser.find_structs_by_id(3), vec!["ItemList/1"]);
+    assert_eq!(parser.find_structs_by_id(0xFFFF_FFFF), vec![""]);
+    assert!(parser.find_structs_by_id(42).is_empty());
+}
+
+#[test]
+fn test_gff_find_structs_by_id_stops_on_cycles() {
+    let child = || {
+        let mut map: IndexMap<String, GffValue<'static>> = IndexMap::new();
+        map.insert("__struct_id__".into(), GffValue::Dword(5));
+        map.insert("X".into(), GffValue::StructOwned(Box::default()));
+        map.insert("Y".into(), GffValue::StructOwned(Box::default()));
+        map
+    };
+    let mut root: IndexMap<String, GffValue<'static>> = IndexMap::new();
+    root.insert("A".into(), GffValue::StructOwned(Box::new(child())));
+    root.insert("B".into(), GffValue::StructOwned(Box::new(child())));
+    // Point every struct field at struct 1, so each struct references it
+    // twice and struct 1 references itself
+    let parser =
+        GffParser::from_bytes(make_cyclic_gff(write_bic(root), 1)).expect("Failed to parse GFF");
+
+    assert_eq!(parser.find_structs_by_id(5), vec!["A"]);
+    assert_eq!(parser.find_structs_by_id_within(5, usize::MAX), vec!["A"]);
+    assert_eq!(parser.find_structs_by_id(0xFFFF_FFFF), vec![""]);
+}
+
+#[test]
+fn test_gff_locstring_language_match_and_strref_fallback() {
+    let substring = |text: &'static str, language: u32, gender: u32| LocalizedSubstring {
+        string: Cow::Borrowed(text),
+        language,
+        gender,
+    };
+    let mut root: IndexMap<String, GffValue<'static>> = IndexMap::new();
+    root.insert(
+        "LocalizedName".into(),
+        GffValue::LocString(LocalizedString {
+            string_ref: 1,
+            substrings: vec![
+                substring("Longsword", 0, 0),
+                substring("Epee longue", 2, 0),
+                substring("Epee longue (f)", 2, 1),
+            ],
+        }),
+    );
+    root.insert(
+        "Description".into(),
+        GffValue::LocString(LocalizedString {
+            string_ref: -1,
+            substrings: vec![substring("A blade", 0, 0)],
+        }),
+    );
+    root.insert("Cost".into(), GffValue::Dword(15));
+
+    let bytes = GffWriter::new("UTI ", "V3.2").write(root).expect("write");
+    let parser = GffParser::from_bytes(bytes).expect("Failed to parse GFF");
+    let mut tlk = TLKParser::new();
+    tlk.parse_from_bytes(&build_tlk(&["Bad Strref", "Langes Schwert"]))
+        .expect("Failed to parse TLK");
+
+    let name = parser.get_locstring("LocalizedName").unwrap();
+    assert_eq!(name.string_ref, 1);
+    assert_eq!(name.substrings.len(), 3);
+    assert!(matches!(
+        parser.get_locstring("Cost"),
+        Err(GffError::TypeMismatch(_))
+    ));
+
+    let text = |path, language, gender, tlk: &mut TLKParser| {
+        parser.get_locstring_for(path, language, gender, tlk)
+    };
+    assert_eq!(
+        text("LocalizedName", 2, 1, &mut tlk).as_deref(),
+        Some("Epee longue (f)")
+    );
+    // Missing gender falls back to the same language
+    assert_eq!(
+        text("LocalizedName", 0, 1, &mut tlk).as_deref(),
+        Some("Longsword")
+    );
+    // Missing language falls back to the TLK entry
+    assert_eq!(
+        text("LocalizedName", 1, 0, &mut tlk).as_deref(),
+        Some("Langes Schwert")
+    );
+    // No substring and no StrRef
+    assert_eq!(text("Description", 1, 0, &mut tlk), None);
+    assert_eq!(text("Missing", 0, 0, &mut tlk), None);
+}
+
+#[test]
+fn test_gff_string_encoding_windows_1252_and_utf8() {
+    // "Ã©" encodes to Windows-1252 bytes C3 A9, the UTF-8 sequence for "é".
+    let mut root: IndexMap<String, GffValue<'static>> = IndexMap::new();
+    root.insert(
+        "Tag".into(),
+        GffValue::String(Cow::Borrowed("caf\u{c3}\u{a9}")),
+    );
+    root.insert(
+        "FirstName".into(),
+        GffValue::LocString(LocalizedString {
+            string_ref: -1,
+            substrings: vec![LocalizedSubstring {
+                string: Cow::Borrowed("\u{c3}\u{a9}l\u{c3}\u{a9}"),
+                language: 0,
+                gender: 0,
+            }],
+        }),
+    );
+    let bytes = write_bic(root);
+
+    let decoded = |parser: &std::sync::Arc<GffParser>| {
+        let tag = match parser.get_value("Tag") {
+            Ok(GffValue::String(s)) => s.into_owned(),
+            other => panic!("expected String, got {other:?}"),
+        };
+        let name = parser.get_locstring("FirstName").expect("locstring");
+        (tag, name.substrings[0].string.to_string())
+    };
+
+    let default = GffParser::from_bytes(bytes.clone()).expect("Failed to parse GFF");
+    assert_eq!(default.encoding(), encoding_rs::WINDOWS_1252);
+    assert_eq!(
+        decoded(&default),
+        (
+            "caf\u{c3}\u{a9}".to_string(),
+            "\u{c3}\u{a9}l\u{c3}\u{a9}".to_string()
+        )
+    );
+
+    let utf8 = GffParser::from_bytes_with_encoding(bytes, encoding_rs::UTF_8)
+        .expect("Failed to parse GFF");
+    assert_eq!(decoded(&utf8), ("café".to_string(), "élé".to_string()));
+}
+
+#[test]
+fn test_gff_header_rejects_sections_past_end_of_file() {
+    let mut root: IndexMap<String, GffValue<'static>> = IndexMap::new();
+    root.insert("Str".into(), GffValue::Byte(14));
+    let bytes = write_bic(root);
+
+    let mut huge_structs = bytes.clone();
+    huge_structs[12..16].copy_from_slice(&0x4000_0000u32.to_le_bytes());
+    match GffParser::from_bytes(huge_structs) {
+        Err(GffError::InvalidHeader(msg)) => assert!(msg.contains("Struct array"), "{msg}"),
+        other => panic!("expected InvalidHeader, got {other:?}"),
+    }
+
+    let mut truncated = bytes.clone();
+    truncated.truncate(bytes.len() - 1);
+    assert!(matches!(
+        GffParser::from_bytes(truncated),
+        Err(GffError::InvalidHeader(_))
+    ));
+
+    assert!(GffParser::from_bytes(bytes).is_ok());
+}
+
+#[test]
+fn test_gff_load_multiple_files_reports_per_file() {
+    let dir = tempfile::TempDir::new().expect("temp dir");
+    let mut paths = Vec::new();
+    for (name, strength) in [("a.bic", 12u8), ("b.ros", 16u8)] {
+        let mut root: IndexMap<String, GffValue<'static>> = IndexMap::new();
+        root.insert("Str".into(), GffValue::Byte(strength));
+        let bytes = write_bic(root);
+        let path = dir.path().join(name);
+        std::fs::write(&path, bytes).expect("write file");
+        paths.push(path);
+    }
+    let broken = dir.path().join("broken.bic");
+    std::fs::write(&broken, b"not a gff").expect("write file");
+    paths.push(broken);
+
+    let result = load_multiple_files(&paths, None);
+    assert_eq!(result.loaded.len(), 2);
+    assert_eq!(result.errors.len(), 1);
+    assert!(result.errors.contains_key(&paths[2]));
+
+    let strength = |i: usize| result.loaded[&paths[i]].get_value("Str").unwrap();
+    assert!(matches!(strength(0), GffValue::Byte(12)));
+    assert!(matches!(strength(1), GffValue::Byte(16)));
+
+    let sequential = load_multiple_files(&paths, Some(1));
+    assert_eq!(sequential.loaded.len(), 2);
+    assert!(sequential.errors.contains_key(&paths[2]));
+}
+
 // =============================================================================
 // MULTIPLE CHARACTER FILES
 // =============================================================================
@@ -1,4 +1,5 @@
 use super::super::common::load_test_gff;
+use app_lib::parsers::gff::GffError;
 use app_lib::parsers::gff::parser::GffParser;
 use app_lib::parsers::gff::types::{GffValue, LocalizedString, LocalizedSubstring};
 use app_lib::parsers::gff::writer::GffWriter;
@@ -442,3 +443,60 @@ async fn test_file_verify_bytes_on_disk() {
         disk_bytes.len()
     );
 }
+
+// =============================================================================
+// RESREF NORMALIZATION
+// =============================================================================
+
+fn write_resref(mut writer: GffWriter, resref: &str) -> Result<Vec<u8>, GffError> {
+    let mut root = indexmap::IndexMap::new();
+    root.insert(
+        "Conversation".to_string(),
+        GffValue::ResRef(Cow::Owned(resref.to_string())),
+    );
+    writer.write(root)
+}
+
+#[test]
+fn test_resref_lowercased_and_truncated_on_write() {
+    let long = "NW_Conversation_With_A_Very_Long_Name";
+    let writer = GffWriter::new("UTC ", "V3.2").normalize_resrefs(true);
+    let bytes = write_resref(writer, long).expect("Write");
+    let parser = GffParser::from_bytes(bytes).expect("Parse");
+
+    match parser.get_value("Conversation") {
+        Ok(GffValue::ResRef(resref)) => {
+            assert_eq!(resref.len(), 32);
+            assert_eq!(resref, long[..32].to_lowercase());
+        }
+        other => panic!("Conversation should be ResRef, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_resref_case_preserved_by_default() {
+    let bytes = write_resref(GffWriter::new("UTC ", "V3.2"), "NW_Guard01").expect("Write");
+    let parser = GffParser::from_bytes(bytes.clone()).expect("Parse");
+    assert!(matches!(
+        parser.get_value("Conversation"),
+        Ok(GffValue::ResRef(ref r)) if r == "NW_Guard01"
+    ));
+
+    assert_eq!(GffWriter::rewrite(&parser).expect("Rewrite"), bytes);
+}
+
+#[test]
+fn test_resref_non_ascii_rejected_when_strict() {
+    let strict = GffWriter::new("UTC ", "V3.2").strict_resrefs(true);
+    assert!(matches!(
+        write_resref(strict, "épée_01"),
+        Err(GffError::ValueOutOfRange(_))
+    ));
+
+    let lenient = write_resref(GffWriter::new("UTC ", "V3.2"), "épée_01").expect("Write");
+    let parser = GffParser::from_bytes(lenient).expect("Parse");
+    assert!(matches!(
+        parser.get_value("Conversation"),
+        Ok(GffValue::ResRef(ref r)) if r == "épée_01"
+    ));
+}
@@ -1,5 +1,9 @@
 use super::super::common::create_test_context;
-use app_lib::parsers::tda::TDAParser;
+use app_lib::parsers::tda::{
+    ControlCharMode, ParserOptions, SecurityLimits, TDAError, TDAParser, TDAWarning, TdaColumnType,
+    load_multiple_files, load_multiple_files_with_timeout,
+};
+use app_lib::utils::CacheValidation;
 
 // =============================================================================
 // BASIC 2DA PARSING TESTS
@@ -373,6 +377,66 @@ Label       Value       Optional
     println!("Test verifies null value handling works");
 }
 
+#[test]
+fn test_2da_schema_classes() {
+    let content = r"2DA V2.0
+
+    Label       Name    HitDie  AttackBonusTable    SkillPointBase  PreReqTable
+0   Barbarian   111     12      CLS_ATK_1           4               ****
+1   Bard        112     6       CLS_ATK_2           4               ****
+2   Cleric      113     8       CLS_ATK_2           2               ****
+27  Shadowdancer 2547   8       CLS_ATK_2           6               CLS_PRES_SHADOW
+";
+
+    let mut parser = TDAParser::new();
+    parser.parse_from_string(content).expect("Failed to parse");
+
+    let schema = parser.schema();
+    let names: Vec<&str> = schema.columns.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "Label",
+            "Name",
+            "HitDie",
+            "AttackBonusTable",
+            "SkillPointBase",
+            "PreReqTable"
+        ]
+    );
+
+    let hit_die = &schema.columns[2];
+    assert_eq!(hit_die.column_type, TdaColumnType::Int);
+    assert!(!hit_die.nullable);
+
+    assert_eq!(schema.columns[0].column_type, TdaColumnType::String);
+
+    let prereq = &schema.columns[5];
+    assert_eq!(prereq.column_type, TdaColumnType::String);
+    assert!(prereq.nullable);
+}
+
+#[test]
+fn test_2da_row_by_original_index() {
+    let content = r"2DA V2.0
+
+    Label       Value
+0   first       10
+1   second      20
+3   fourth      40
+";
+
+    let mut parser = TDAParser::new();
+    parser.set_capture_row_indices(true);
+    parser.parse_from_string(content).expect("Failed to parse");
+
+    assert_eq!(parser.row_by_original_index("3"), Some(2));
+    assert_eq!(parser.get_cell_by_name(2, "Label").unwrap(), Some("fourth"));
+    assert_eq!(parser.row_by_original_index("0"), Some(0));
+    assert_eq!(parser.row_by_original_index("2"), None);
+    assert_eq!(parser.original_row_index(2), Some("3"));
+}
+
 // =============================================================================
 // COLUMN ITERATION
 // =============================================================================
@@ -396,6 +460,36 @@ async fn test_2da_column_iteration() {
     }
 }
 
+#[test]
+fn test_2da_get_column() {
+    let content = r"2DA V2.0
+
+    Label       Name        Value
+0   first       100         10
+1   second      ****
+2   third       300         30
+";
+
+    let mut parser = TDAParser::new();
+    parser.parse_from_string(content).expect("Failed to parse");
+
+    assert_eq!(
+        parser.get_column("label").unwrap(),
+        vec![Some("first"), Some("second"), Some("third")]
+    );
+    assert_eq!(
+        parser.get_column("Name").unwrap(),
+        vec![Some("100"), None, Some("300")]
+    );
+    assert_eq!(
+        parser.get_column_by_index(2).unwrap(),
+        vec![Some("10"), None, Some("30")]
+    );
+
+    assert!(parser.get_column("Missing").is_err());
+    assert!(parser.get_column_by_index(3).is_err());
+}
+
 // =============================================================================
 // CACHE/SERIALIZATION TESTS
 // =============================================================================
@@ -421,6 +515,722 @@ Label       Name        Value
     assert_eq!(parser.column_count(), restored.column_count());
 }
 
+#[test]
+fn test_2da_msgpack_uncompressed_round_trip() {
+    let content = r#"2DA V2.0
+
+Label       Name        Value
+0           test1       "Quoted value"
+1           test2       ****
+2           test3
+"#;
+
+    let mut parser = TDAParser::new();
+    parser.parse_from_string(content).expect("Failed to parse");
+
+    let plain = parser.to_msgpack().expect("Failed to serialize");
+    let compressed = parser.to_msgpack_compressed().expect("Failed to compress");
+
+    let from_plain = TDAParser::from_msgpack(&plain).expect("Failed to restore");
+    let from_compressed =
+        TDAParser::from_msgpack_compressed(&compressed).expect("Failed to restore");
+
+    assert_eq!(from_plain.column_names(), from_compressed.column_names());
+    assert_eq!(
+        from_plain.format_version(),
+        from_compressed.format_version()
+    );
+    assert_eq!(from_plain.row_count(), parser.row_count());
+    for row in 0..parser.row_count() {
+        for col in 0..parser.column_count() {
+            assert_eq!(
+                from_plain.get_cell(row, col).unwrap(),
+                from_compressed.get_cell(row, col).unwrap()
+            );
+            assert_eq!(
+                from_plain.get_cell(row, col).unwrap(),
+                parser.get_cell(row, col).unwrap()
+            );
+        }
+    }
+
+    assert!(TDAParser::from_msgpack(&compressed).is_err());
+}
+
+#[test]
+fn test_2da_set_format_version() {
+    let content = r"2DA V1.0
+
+Label       Value
+0           10
+";
+
+    let mut parser = TDAParser::new();
+    parser.parse_from_string(content).expect("Failed to parse");
+    assert_eq!(parser.format_version(), "2DA V1.0");
+
+    parser
+        .set_format_version("2DA V2.0")
+        .expect("V2.0 should be accepted");
+
+    let restored =
+        TDAParser::from_msgpack_compressed(&parser.to_msgpack_compressed().unwrap()).unwrap();
+    assert_eq!(restored.format_version(), "2DA V2.0");
+
+    assert!(parser.set_format_version("2DA V9.9").is_err());
+    assert_eq!(parser.format_version(), "2DA V2.0");
+}
+
+#[test]
+fn test_2da_write_round_trip() {
+    let content = r#"2DAMV1.0
+
+    Label       Name        Description
+0   test1       100         "A quoted description"
+1   test2       ****        Simple
+2   test3       300
+"#;
+
+    let mut parser = TDAParser::new();
+    parser.parse_from_string(content).expect("Failed to parse");
+
+    let written = parser.to_2da_string();
+    assert!(written.starts_with("2DAMV1.0\r\n\r\n"));
+    assert!(written.contains("\"A quoted description\""));
+    assert!(written.lines().all(|l| !l.ends_with(' ')));
+
+    let mut reparsed = TDAParser::new();
+    reparsed
+        .parse_from_string(&written)
+        .expect("Failed to re-parse written 2DA");
+
+    assert_eq!(reparsed.format_version(), "2DAMV1.0");
+    assert_eq!(reparsed.column_names(), parser.column_names());
+    assert_eq!(reparsed.row_count(), 3);
+    for row in 0..parser.row_count() {
+        for col in 0..parser.column_count() {
+            let original = parser.get_cell(row, col).unwrap();
+            let expected = original.filter(|s| !s.is_empty());
+            assert_eq!(reparsed.get_cell(row, col).unwrap(), expected);
+        }
+    }
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let path = temp_dir.path().join("test.2da");
+    parser.write_to_file(&path).expect("Failed to write");
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), written);
+}
+
+#[test]
+fn test_2da_to_csv() {
+    let content = r"2DA V2.0
+
+    Label       Name        Description
+0   test1       100         Placeholder
+1   test2       ****        Simple
+5   test3       300         Gone
+";
+
+    let mut parser = TDAParser::new();
+    parser.parse_from_string(content).expect("Failed to parse");
+    parser
+        .set_cell(0, 2, Some("Say \"hi\", then leave"))
+        .unwrap();
+    parser.set_cell(2, 2, None).unwrap();
+
+    assert_eq!(
+        parser.to_csv(b',', false),
+        "Label,Name,Description\r\n\
+         test1,100,\"Say \"\"hi\"\", then leave\"\r\n\
+         test2,****,Simple\r\n\
+         test3,300,\r\n"
+    );
+    assert_eq!(
+        parser.to_csv(b'\t', true),
+        "\tLabel\tName\tDescription\r\n\
+         0\ttest1\t100\t\"Say \"\"hi\"\", then leave\"\r\n\
+         1\ttest2\t****\tSimple\r\n\
+         2\ttest3\t300\t\r\n"
+    );
+}
+
+#[test]
+fn test_2da_from_csv() {
+    let csv = "Label,Name,Description\r\n\
+               sword,100,\"Sharp, \"\"pointy\"\" end\"\r\n\
+               axe,****,\r\n\
+               \r\n\
+               club,300\n";
+
+    let parser = TDAParser::from_csv(csv, b',').expect("Failed to import CSV");
+    assert_eq!(parser.column_names(), vec!["Label", "Name", "Description"]);
+    assert_eq!(parser.row_count(), 3);
+    assert_eq!(
+        parser.get_cell_by_name(0, "Description").unwrap(),
+        Some("Sharp, \"pointy\" end")
+    );
+    assert_eq!(parser.get_cell_by_name(1, "Name").unwrap(), None);
+    assert_eq!(parser.get_cell_by_name(1, "Description").unwrap(), Some(""));
+    assert_eq!(parser.get_cell_by_name(2, "Label").unwrap(), Some("club"));
+    assert_eq!(parser.get_cell_by_name(2, "Description").unwrap(), None);
+    assert_eq!(
+        parser.metadata().warnings,
+        vec![TDAWarning::RaggedRow {
+            row: 2,
+            got: 2,
+            expected: 3
+        }]
+    );
+
+    // Round trip through the exporter, index column included
+    let reimported = TDAParser::from_csv(&parser.to_csv(b'\t', true), b'\t').unwrap();
+    assert_eq!(reimported.column_names(), parser.column_names());
+    for row in 0..parser.row_count() {
+        for col in 0..parser.column_count() {
+            assert_eq!(
+                reimported.get_cell(row, col).unwrap(),
+                parser.get_cell(row, col).unwrap()
+            );
+        }
+    }
+
+    assert!(TDAParser::from_csv("Label\n\"open", b',').is_err());
+    assert!(matches!(
+        TDAParser::from_csv("Label,label\n", b','),
+        Err(TDAError::DuplicateColumn { .. })
+    ));
+}
+
+#[test]
+fn test_2da_keep_index_column() {
+    let content = "2DA V2.0\n\n\tLABEL\tName\n0\tAttack\t100\n5\tWait\t****\n";
+
+    let mut dropped = TDAParser::new();
+    dropped.parse_from_string(content).expect("Failed to parse");
+    assert_eq!(dropped.column_names(), vec!["LABEL", "Name"]);
+    assert_eq!(
+        dropped.get_cell_by_name(0, "LABEL").unwrap(),
+        Some("Attack")
+    );
+
+    let options = ParserOptions {
+        keep_index_column: true,
+        ..ParserOptions::default()
+    };
+    let mut kept = TDAParser::with_options(SecurityLimits::default(), options);
+    kept.parse_from_string(content).expect("Failed to parse");
+
+    assert_eq!(kept.column_names(), vec!["", "LABEL", "Name"]);
+    assert_eq!(kept.find_column_index("LABEL"), Some(1));
+    assert_eq!(kept.get_cell(1, 0).unwrap(), Some("5"));
+    assert_eq!(kept.get_cell_by_name(0, "LABEL").unwrap(), Some("Attack"));
+    assert_eq!(kept.get_cell_by_name(1, "Name").unwrap(), None);
+
+    // Space-separated headers have no empty token; the index column is still kept
+    let mut spaced = TDAParser::with_options(SecurityLimits::default(), kept.options().clone());
+    spaced
+        .parse_from_string("2DA V2.0\n\n    LABEL   Name\n0   Attack  100\n")
+        .expect("Failed to parse");
+    assert_eq!(spaced.column_names(), vec!["", "LABEL", "Name"]);
+    assert_eq!(spaced.get_cell_by_name(0, "Name").unwrap(), Some("100"));
+
+    let written = kept.to_2da_string();
+    let mut reparsed = TDAParser::new();
+    reparsed
+        .parse_from_string(&written)
+        .expect("Failed to re-parse");
+    assert_eq!(reparsed.column_names(), vec!["LABEL", "Name"]);
+    assert!(written.lines().nth(4).unwrap().starts_with('5'));
+}
+
+#[test]
+fn test_2da_apply_merge() {
+    let base_content = r"2DA V2.0
+
+    Label       Name        Value
+0   first       100         10
+1   second      200         20
+2   third       300         ****
+";
+    let merge_content = r"2DAMV1.0
+
+    Value       Icon
+1   25          ****
+2   ****        ico_third
+4   40          ico_new
+";
+
+    let mut base = TDAParser::new();
+    base.parse_from_string(base_content)
+        .expect("Failed to parse base");
+    let mut merge = TDAParser::new();
+    merge.set_capture_row_indices(true);
+    merge
+        .parse_from_string(merge_content)
+        .expect("Failed to parse merge");
+    assert_eq!(merge.format_version(), "2DAMV1.0");
+
+    base.apply_merge(&merge).expect("Failed to apply merge");
+
+    assert_eq!(base.column_names(), vec!["Label", "Name", "Value", "Icon"]);
+    assert_eq!(base.row_count(), 5);
+    assert_eq!(base.get_cell_by_name(0, "Value").unwrap(), Some("10"));
+    assert_eq!(base.get_cell_by_name(1, "Value").unwrap(), Some("25"));
+    assert_eq!(base.get_cell_by_name(1, "Icon").unwrap(), None);
+    assert_eq!(base.get_cell_by_name(2, "Value").unwrap(), None);
+    assert_eq!(base.get_cell_by_name(2, "Icon").unwrap(), Some("ico_third"));
+    assert_eq!(base.get_cell_by_name(2, "Label").unwrap(), Some("third"));
+    assert_eq!(base.get_cell_by_name(3, "Label").unwrap(), None);
+    assert_eq!(base.get_cell_by_name(4, "Value").unwrap(), Some("40"));
+
+    let mut uncaptured = TDAParser::new();
+    uncaptured.parse_from_string(merge_content).unwrap();
+    assert!(base.apply_merge(&uncaptured).is_err());
+}
+
+#[test]
+fn test_2da_find_all() {
+    let content = r#"2DA V2.0
+
+    Label       Icon            Description
+0   sword       it_sword        "A sword used by it_sword fans"
+1   Sword       IT_SWORD        ****
+2   axe         it_axe          "A sword used by it_sword fans"
+3   empty
+"#;
+
+    let mut parser = TDAParser::new();
+    parser.parse_from_string(content).expect("Failed to parse");
+
+    assert_eq!(parser.find_all("it_sword", false), vec![(0, 1)]);
+    assert_eq!(parser.find_all("it_sword", true), vec![(0, 1), (1, 1)]);
+    assert_eq!(parser.find_all("SWORD", true), vec![(0, 0), (1, 0)]);
+    assert_eq!(
+        parser.find_all("A sword used by it_sword fans", false),
+        vec![(0, 2), (2, 2)]
+    );
+    assert!(parser.find_all("not_present", false).is_empty());
+    assert!(parser.find_all("", false).is_empty());
+    assert!(parser.find_all("", true).is_empty());
+    assert!(parser.find_all("****", false).is_empty());
+}
+
+#[test]
+fn test_2da_utf8_bom() {
+    let mut data = b"\xEF\xBB\xBF".to_vec();
+    data.extend_from_slice(b"2DA V2.0\n\nLabel\tName\n0\tfirst\t100\n");
+
+    let mut parser = TDAParser::new();
+    parser
+        .parse_from_bytes(&data)
+        .expect("BOM should be skipped");
+    assert_eq!(parser.format_version(), "2DA V2.0");
+    assert_eq!(parser.column_names(), vec!["Label", "Name"]);
+    assert_eq!(parser.get_cell_by_name(0, "Label").unwrap(), Some("first"));
+    assert_eq!(parser.metadata().warnings, vec![TDAWarning::ByteOrderMark]);
+}
+
+#[test]
+fn test_2da_control_chars() {
+    let content = "2DA V2.0\n\n\tLabel\tValue\n0\tbro\x00ken\t10\n1\tclean\t20\n";
+
+    let mut kept = TDAParser::new();
+    kept.parse_from_string(content).unwrap();
+    assert_eq!(kept.get_cell(0, 0).unwrap(), Some("bro\x00ken"));
+
+    let strip = ParserOptions {
+        control_chars: ControlCharMode::Strip,
+        ..ParserOptions::default()
+    };
+    let mut stripped = TDAParser::with_options(SecurityLimits::default(), strip);
+    stripped.parse_from_string(content).unwrap();
+    assert_eq!(stripped.get_cell(0, 0).unwrap(), Some("broken"));
+    assert_eq!(stripped.get_cell(0, 1).unwrap(), Some("10"));
+    assert_eq!(
+        stripped.metadata().warnings,
+        vec![TDAWarning::ControlCharsStripped { line: 4, count: 1 }]
+    );
+
+    let reject = ParserOptions {
+        control_chars: ControlCharMode::Reject,
+        ..ParserOptions::default()
+    };
+    let mut rejected = TDAParser::with_options(SecurityLimits::default(), reject);
+    assert!(matches!(
+        rejected.parse_from_string(content),
+        Err(TDAError::MalformedLine { line_number: 4, .. })
+    ));
+}
+
+#[test]
+fn test_2da_set_cell() {
+    let content = "2DA V2.0\n\n\tLabel\tValue\n0\tfirst\t10\n1\tsecond\t20\n";
+    let mut parser = TDAParser::new();
+    parser.parse_from_string(content).unwrap();
+
+    parser.set_cell(0, 1, Some("****")).unwrap();
+    assert_eq!(parser.get_cell(0, 1).unwrap(), None);
+    parser.set_cell(1, 0, Some("renamed label")).unwrap();
+    assert_eq!(parser.get_cell(1, 0).unwrap(), Some("renamed label"));
+    parser.set_cell(1, 1, None).unwrap();
+    assert_eq!(parser.get_cell(1, 1).unwrap(), Some(""));
+
+    assert!(matches!(
+        parser.set_cell(2, 0, Some("x")),
+        Err(TDAError::RowIndexOutOfBounds { index: 2, max: 2 })
+    ));
+    assert!(matches!(
+        parser.set_cell(0, 2, Some("x")),
+        Err(TDAError::ColumnIndexOutOfBounds { index: 2, max: 2 })
+    ));
+
+    let written = parser.to_2da_string();
+    assert!(written.contains("\"renamed label\""));
+}
+
+#[test]
+fn test_2da_append_row() {
+    let content = "2DA V2.0\n\n\tLabel\tValue\tExtra\n0\tfirst\t10\t1\n";
+    let mut parser = TDAParser::with_limits(SecurityLimits {
+        max_rows: 2,
+        ..SecurityLimits::default()
+    });
+    parser.parse_from_string(content).unwrap();
+
+    parser.append_row(&[Some("second")]).unwrap();
+    assert_eq!(parser.row_count(), 2);
+    assert_eq!(parser.get_cell_by_name(1, "Label").unwrap(), Some("second"));
+    assert_eq!(parser.get_cell_by_name(1, "Value").unwrap(), None);
+    assert_eq!(parser.get_cell_by_name(1, "Extra").unwrap(), None);
+
+    assert!(matches!(
+        parser.append_row(&[Some("third")]),
+        Err(TDAError::RowCountExceeded { count: 3, limit: 2 })
+    ));
+    assert!(parser.append_row(&[None, None, None, None]).is_err());
+    assert_eq!(parser.row_count(), 2);
+}
+
+#[test]
+fn test_2da_insert_and_remove_rows() {
+    let content = "2DA V2.0\n\n\tLabel\tValue\n0\tfirst\t10\n1\tsecond\t20\n2\tthird\t30\n";
+    let mut parser = TDAParser::new();
+    parser.parse_from_string(content).unwrap();
+
+    parser
+        .insert_row(1, &[Some("inserted"), Some("15")])
+        .unwrap();
+    assert_eq!(parser.row_count(), 4);
+    let labels: Vec<_> = parser.iter_column(0).collect();
+    assert_eq!(
+        labels,
+        vec![
+            Some("first"),
+            Some("inserted"),
+            Some("second"),
+            Some("third")
+        ]
+    );
+    assert_eq!(parser.get_cell_by_name(2, "Value").unwrap(), Some("20"));
+
+    parser.remove_row(0).unwrap();
+    assert_eq!(parser.row_count(), 3);
+    assert_eq!(
+        parser.get_cell_by_name(0, "Label").unwrap(),
+        Some("inserted")
+    );
+    assert_eq!(parser.get_cell_by_name(2, "Value").unwrap(), Some("30"));
+
+    // Indices were not captured, so rows are renumbered on write
+    let written = parser.to_2da_string();
+    assert!(
+        written
+            .lines()
+            .any(|l| l.starts_with('0') && l.contains("inserted"))
+    );
+    assert!(
+        written
+            .lines()
+            .any(|l| l.starts_with('2') && l.contains("third"))
+    );
+
+    assert!(matches!(
+        parser.remove_row(3),
+        Err(TDAError::RowIndexOutOfBounds { index: 3, max: 3 })
+    ));
+    assert!(matches!(
+        parser.insert_row(4, &[]),
+        Err(TDAError::RowIndexOutOfBounds { index: 4, max: 3 })
+    ));
+    assert_eq!(parser.row_count(), 3);
+}
+
+#[test]
+fn test_2da_rename_column() {
+    let content = "2DA V2.0\n\n\tLabel\tName\n0\tfirst\t100\n";
+    let mut parser = TDAParser::new();
+    parser.parse_from_string(content).unwrap();
+
+    parser.rename_column("name", "DisplayName").unwrap();
+    assert_eq!(parser.column_names(), vec!["Label", "DisplayName"]);
+    assert_eq!(
+        parser.get_cell_by_name(0, "DisplayName").unwrap(),
+        Some("100")
+    );
+    assert!(matches!(
+        parser.get_cell_by_name(0, "Name"),
+        Err(TDAError::ColumnNotFound { .. })
+    ));
+
+    assert!(matches!(
+        parser.rename_column("Name", "Other"),
+        Err(TDAError::ColumnNotFound { .. })
+    ));
+    assert!(matches!(
+        parser.rename_column("DisplayName", "LABEL"),
+        Err(TDAError::DuplicateColumn {
+            first: 0,
+            second: 1,
+            ..
+        })
+    ));
+    parser.rename_column("label", "LABEL").unwrap();
+    assert_eq!(parser.column_names(), vec!["LABEL", "DisplayName"]);
+}
+
+#[test]
+fn test_2da_memory_breakdown_sums_to_usage() {
+    let content = r#"2DA V2.0
+
+    Label       Name        Description
+0   test1       100         "A quoted description"
+1   test2       ****        Simple
+2   test1       300         "Another description"
+"#;
+
+    let mut parser = TDAParser::new();
+    parser.parse_from_string(content).expect("Failed to parse");
+
+    let breakdown = parser.memory_breakdown();
+    assert!(breakdown.interner_bytes > 0);
+    assert!(breakdown.rows_bytes > 0);
+    assert!(breakdown.columns_bytes > 0);
+    assert!(breakdown.column_map_bytes > 0);
+    assert_eq!(breakdown.total(), parser.statistics().memory_usage);
+}
+
+#[test]
+fn test_2da_load_with_cache_checks_content_hash() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let source = temp_dir.path().join("classes.2da");
+    let cache = temp_dir.path().join("classes.2da.cache");
+    std::fs::write(&source, "2DA V2.0\n\n\tLabel\n0\tFighter\n").unwrap();
+    let load = |parser: &mut TDAParser| {
+        parser
+            .load_with_cache_validated(&source, Some(&cache), CacheValidation::ContentHash)
+            .unwrap()
+    };
+
+    let mut parser = TDAParser::new();
+    assert!(!load(&mut parser));
+    assert!(parser.metadata().source_hash.is_some());
+    let mut parser = TDAParser::new();
+    assert!(load(&mut parser));
+
+    std::fs::write(&source, "2DA V2.0\n\n\tLabel\n0\tWizard\n").unwrap();
+    let mut parser = TDAParser::new();
+    assert!(!load(&mut parser));
+    assert_eq!(parser.get_cell_by_name(0, "Label").unwrap(), Some("Wizard"));
+}
+
+#[test]
+fn test_2da_load_with_cache_checks_size_and_mtime() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let source = temp_dir.path().join("classes.2da");
+    let cache = temp_dir.path().join("classes.2da.cache");
+    std::fs::write(&source, "2DA V2.0\n\n\tLabel\n0\tFighter\n").unwrap();
+
+    let mut parser = TDAParser::new();
+    assert!(!parser.load_with_cache(&source, Some(&cache)).unwrap());
+    assert!(parser.metadata().source_stamp.is_some());
+    assert!(parser.metadata().source_hash.is_none());
+    let mut parser = TDAParser::new();
+    assert!(parser.load_with_cache(&source, Some(&cache)).unwrap());
+
+    std::fs::write(&source, "2DA V2.0\n\n\tLabel\n0\tSorcerer\n").unwrap();
+    let mut parser = TDAParser::new();
+    assert!(!parser.load_with_cache(&source, Some(&cache)).unwrap());
+    assert_eq!(
+        parser.get_cell_by_name(0, "Label").unwrap(),
+        Some("Sorcerer")
+    );
+}
+
+#[test]
+fn test_2da_stampless_cache_falls_back_to_mtime() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let source = temp_dir.path().join("classes.2da");
+    let cache = temp_dir.path().join("classes.2da.cache");
+    std::fs::write(&source, "2DA V2.0\n\n\tLabel\n0\tFighter\n").unwrap();
+
+    // A cache from before stamps were recorded
+    let mut parser = TDAParser::new();
+    parser.parse_from_file(&source).unwrap();
+    assert!(parser.metadata().source_stamp.is_none());
+    std::fs::write(&cache, parser.to_msgpack_compressed().unwrap()).unwrap();
+    let mut parser = TDAParser::new();
+    assert!(parser.load_with_cache(&source, Some(&cache)).unwrap());
+
+    std::fs::write(&source, "2DA V2.0\n\n\tLabel\n0\tRanger\n").unwrap();
+    std::fs::File::options()
+        .write(true)
+        .open(&cache)
+        .unwrap()
+        .set_modified(std::time::SystemTime::UNIX_EPOCH)
+        .unwrap();
+
+    let mut parser = TDAParser::new();
+    assert!(!parser.load_with_cache(&source, Some(&cache)).unwrap());
+    assert_eq!(parser.get_cell_by_name(0, "Label").unwrap(), Some("Ranger"));
+}
+
+#[test]
+fn test_2da_load_with_cache_keeps_row_indices() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let source = temp_dir.path().join("gaps.2da");
+    let cache = temp_dir.path().join("gaps.2da.cache");
+    std::fs::write(&source, "2DA V2.0\n\n\tLabel\n0\tzero\n1\tone\n3\tthree\n").unwrap();
+
+    // Without capture the cache holds no indices, so it can't serve a capturing load
+    let mut parser = TDAParser::new();
+    assert!(!parser.load_with_cache(&source, Some(&cache)).unwrap());
+
+    for hit in [false, true] {
+        let mut parser = TDAParser::new();
+        parser.set_capture_row_indices(true);
+        assert_eq!(parser.load_with_cache(&source, Some(&cache)).unwrap(), hit);
+        assert!(parser.captures_row_indices());
+        assert_eq!(parser.row_by_original_index("3"), Some(2));
+        assert_eq!(parser.row_by_original_index("2"), None);
+    }
+
+    let cached = TDAParser::from_msgpack_compressed(&std::fs::read(&cache).unwrap()).unwrap();
+    assert_eq!(cached.original_row_index(2), Some("3"));
+}
+
+#[test]
+fn test_2da_load_multiple_files_single_thread() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let paths: Vec<_> = (0..3)
+        .map(|i| {
+            let path = temp_dir.path().join(format!("table{i}.2da"));
+            std::fs::write(&path, format!("2DA V2.0\n\n\tLabel\n0\trow{i}\n")).unwrap();
+            path
+        })
+        .collect();
+
+    let loaded = load_multiple_files(&paths, None, Some(1)).loaded;
+    assert_eq!(loaded.len(), 3);
+    for (i, path) in paths.iter().enumerate() {
+        let parser = &loaded[path];
+        assert_eq!(
+            parser.get_cell_by_name(0, "Label").unwrap(),
+            Some(format!("row{i}").as_str())
+        );
+    }
+
+    let bounded = load_multiple_files(&paths, None, Some(2)).loaded;
+    assert_eq!(bounded.len(), 3);
+}
+
+#[test]
+fn test_2da_load_multiple_files_keeps_valid_on_error() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let good = temp_dir.path().join("good.2da");
+    let bad = temp_dir.path().join("bad.2da");
+    std::fs::write(&good, "2DA V2.0\n\n\tLabel\n0\tok\n").unwrap();
+    std::fs::write(&bad, "NOT A 2DA FILE\n").unwrap();
+
+    let result = load_multiple_files(&[&good, &bad], None, None);
+
+    assert_eq!(result.loaded.len(), 1);
+    assert_eq!(
+        result.loaded[&good].get_cell_by_name(0, "Label").unwrap(),
+        Some("ok")
+    );
+    assert!(matches!(
+        result.errors.get(&bad),
+        Some(TDAError::InvalidHeader(_))
+    ));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_2da_load_multiple_files_non_utf8_paths() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let first = temp_dir.path().join(OsStr::from_bytes(b"bad\xff.2da"));
+    let second = temp_dir.path().join(OsStr::from_bytes(b"bad\xfe.2da"));
+    assert_eq!(first.to_string_lossy(), second.to_string_lossy());
+    if std::fs::write(&first, "2DA V2.0\n\n\tLabel\n0\tfirst\n").is_err() {
+        // Filesystem refuses non-UTF-8 names
+        return;
+    }
+    std::fs::write(&second, "2DA V2.0\n\n\tLabel\n0\tsecond\n").unwrap();
+
+    let result = load_multiple_files(&[&first, &second], None, Some(1));
+
+    assert_eq!(result.loaded.len(), 2);
+    assert_eq!(
+        result.loaded[&first].get_cell_by_name(0, "Label").unwrap(),
+        Some("first")
+    );
+    assert_eq!(
+        result.loaded[&second].get_cell_by_name(0, "Label").unwrap(),
+        Some("second")
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_2da_load_with_timeout_abandons_hung_file() {
+    use std::time::{Duration, Instant};
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let fast = temp_dir.path().join("fast.2da");
+    std::fs::write(&fast, "2DA V2.0\n\n\tLabel\n0\tfast\n").unwrap();
+    // Opening a FIFO for reading blocks until a writer shows up, like a read
+    // from an unresponsive network mount
+    let hung = temp_dir.path().join("hung.2da");
+    let made = std::process::Command::new("mkfifo").arg(&hung).status();
+    if !made.is_ok_and(|status| status.success()) {
+        println!("mkfifo unavailable, skipping");
+        return;
+    }
+
+    let start = Instant::now();
+    let result =
+        load_multiple_files_with_timeout(&[&fast, &hung], None, Duration::from_millis(200));
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert_eq!(
+        result.loaded[&fast].get_cell_by_name(0, "Label").unwrap(),
+        Some("fast")
+    );
+    assert!(matches!(
+        result.errors.get(&hung),
+        Some(TDAError::Timeout { .. })
+    ));
+
+    // The abandoned load holds its own thread, not a Rayon worker
+    let paths = vec![&fast; 64];
+    assert_eq!(load_multiple_files(&paths, None, None).loaded.len(), 1);
+
+    // Let the abandoned load finish
+    drop(std::fs::OpenOptions::new().write(true).open(&hung));
+}
+
 // =============================================================================
 // ERROR HANDLING
 // =============================================================================
@@ -475,6 +1285,131 @@ Label       Value
     assert!(result.is_err(), "Nonexistent column should error");
 }
 
+#[test]
+fn test_2da_duplicate_column() {
+    let content = r"2DA V2.0
+
+    Label       Name        Label
+0   first       100         again
+";
+
+    let mut parser = TDAParser::new();
+    let err = parser.parse_from_string(content).unwrap_err();
+    assert!(matches!(
+        err,
+        TDAError::DuplicateColumn { ref name, first: 0, second: 2 } if name == "Label"
+    ));
+
+    let options = ParserOptions {
+        rename_duplicate_columns: true,
+        ..ParserOptions::default()
+    };
+    let mut parser = TDAParser::with_options(SecurityLimits::default(), options);
+    parser.parse_from_string(content).expect("Failed to parse");
+
+    assert_eq!(parser.column_names(), vec!["Label", "Name", "Label_2"]);
+    assert_eq!(
+        parser.metadata().warnings,
+        vec![TDAWarning::DuplicateColumn {
+            name: "Label".to_string(),
+            renamed: "Label_2".to_string(),
+        }]
+    );
+    assert_eq!(parser.get_cell_by_name(0, "Label").unwrap(), Some("first"));
+    assert_eq!(
+        parser.get_cell_by_name(0, "label_2").unwrap(),
+        Some("again")
+    );
+}
+
+#[test]
+fn test_2da_parse_warnings() {
+    let mut parser = TDAParser::new();
+    parser
+        .parse_from_string("2DA V2.0\n\nLabel Value\n0 a 1\n1 b\n2 c 3 extra\n")
+        .expect("Failed to parse");
+    assert!(parser.metadata().has_warnings());
+    assert_eq!(
+        parser.metadata().warnings,
+        vec![
+            TDAWarning::RaggedRow {
+                row: 1,
+                got: 1,
+                expected: 2,
+            },
+            TDAWarning::RaggedRow {
+                row: 2,
+                got: 3,
+                expected: 2,
+            },
+        ]
+    );
+
+    parser
+        .parse_from_string("2DAMV1.0\n\nLabel\n0 a\n")
+        .expect("Failed to parse");
+    assert_eq!(parser.metadata().warnings, vec![TDAWarning::MergeFormat]);
+
+    parser
+        .parse_from_string("2DA\tV2.0\n\nLabel\n0 a\n")
+        .expect("Failed to parse");
+    assert_eq!(
+        parser.metadata().warnings,
+        vec![TDAWarning::NonStandardHeader {
+            header: "2DA\tV2.0".to_string(),
+        }]
+    );
+
+    parser
+        .parse_from_string("2DA V2.0\n\nLabel\n0 a\n")
+        .expect("Failed to parse");
+    assert!(!parser.metadata().has_warnings());
+}
+
+#[test]
+fn test_2da_typed_accessors() {
+    let content = r"2DA V2.0
+
+    Label   TIMER   Range   Hostile
+0   Attack  0       1.5     1
+1   Wait    ****    ****    0
+2   Broken  abc     2x      yes
+3   Flags   0x1F    3
+";
+
+    let mut parser = TDAParser::new();
+    parser.parse_from_string(content).expect("Failed to parse");
+
+    assert_eq!(parser.get_int_by_name(0, "TIMER").unwrap(), Some(0));
+    assert_eq!(parser.get_int_by_name(3, "TIMER").unwrap(), Some(31));
+    assert_eq!(parser.get_float_by_name(0, "Range").unwrap(), Some(1.5));
+    assert_eq!(parser.get_float_by_name(3, "Range").unwrap(), Some(3.0));
+    assert_eq!(parser.get_bool_by_name(0, "Hostile").unwrap(), Some(true));
+    assert_eq!(parser.get_bool_by_name(1, "Hostile").unwrap(), Some(false));
+
+    assert_eq!(parser.get_int_by_name(1, "TIMER").unwrap(), None);
+    assert_eq!(parser.get_float_by_name(1, "Range").unwrap(), None);
+    assert_eq!(parser.get_bool_by_name(3, "Hostile").unwrap(), None);
+
+    let err = parser.get_int_by_name(2, "TIMER").unwrap_err();
+    assert!(matches!(
+        err,
+        TDAError::CellTypeMismatch { row: 2, ref col, ref value } if col == "TIMER" && value == "abc"
+    ));
+    assert!(matches!(
+        parser.get_float_by_name(2, "Range"),
+        Err(TDAError::CellTypeMismatch { .. })
+    ));
+    assert!(matches!(
+        parser.get_bool_by_name(2, "Hostile"),
+        Err(TDAError::CellTypeMismatch { .. })
+    ));
+    assert!(matches!(
+        parser.get_int_by_name(0, "Missing"),
+        Err(TDAError::ColumnNotFound { .. })
+    ));
+}
+
 // =============================================================================
 // STATISTICS TESTS
 // =============================================================================
@@ -1,4 +1,9 @@
-use super::super::common::create_test_context;
+use super::super::common::{build_tlk, create_test_context};
+use app_lib::config::NWN2Paths;
+use app_lib::parsers::tlk::{
+    SearchMode, SearchOptions, TLKError, TLKParser, TlkChange, TlkDecodeError, TlkDiff,
+    ValidationReport, load_multiple_files,
+};
 
 // =============================================================================
 // BASIC TLK LOOKUP TESTS
@@ -190,6 +195,536 @@ async fn test_tlk_random_access() {
     }
 }
 
+#[test]
+fn test_tlk_batch_ordered() {
+    let mut tlk = TLKParser::new();
+    tlk.parse_from_bytes(&build_tlk(&["Zero", "One", "Two"]))
+        .expect("Failed to parse TLK");
+
+    let strings = tlk
+        .get_strings_batch_ordered(&[2, 99, 0, 2])
+        .expect("Batch lookup failed");
+    assert_eq!(
+        strings,
+        vec![
+            Some("Two".to_string()),
+            None,
+            Some("Zero".to_string()),
+            Some("Two".to_string()),
+        ]
+    );
+
+    let batch = tlk.get_strings_batch(&[1, 2]).expect("Batch lookup failed");
+    assert_eq!(batch.strings.len(), 2);
+    assert_eq!(batch.metrics.cache_hits + batch.metrics.cache_misses, 2);
+
+    // Push entry 1's size past the end of the string data
+    let mut data = build_tlk(&["Zero", "One", "Two"]);
+    data[20 + 40 + 32..20 + 40 + 36].copy_from_slice(&500u32.to_le_bytes());
+    let mut tlk = TLKParser::new();
+    tlk.parse_from_bytes(&data).expect("Failed to parse TLK");
+    assert_eq!(
+        tlk.get_strings_batch_ordered(&[0, 1, 2]).unwrap(),
+        vec![Some("Zero".to_string()), None, Some("Two".to_string())]
+    );
+}
+
+#[test]
+fn test_tlk_write_round_trip() {
+    let mut original = build_tlk(&["Zero", "One", "Two"]);
+    original[8..12].copy_from_slice(&2u32.to_le_bytes());
+    original[24..30].copy_from_slice(b"vo_one");
+
+    let mut tlk = TLKParser::new();
+    tlk.parse_from_bytes(&original)
+        .expect("Failed to parse TLK");
+    assert_eq!(tlk.to_bytes().expect("Failed to write"), original);
+
+    tlk.set_string(1, "Replaced line").unwrap();
+    tlk.set_string(5, "Appended").unwrap();
+
+    let mut reparsed = TLKParser::new();
+    reparsed
+        .parse_from_bytes(&tlk.to_bytes().unwrap())
+        .expect("Failed to re-parse TLK");
+
+    assert_eq!(reparsed.header.as_ref().unwrap().language_id, 2);
+    assert_eq!(reparsed.string_count(), 6);
+    assert_eq!(reparsed.entries[0].sound_resref.as_deref(), Some("vo_one"));
+    assert_eq!(reparsed.get_string(0).unwrap().as_deref(), Some("Zero"));
+    assert_eq!(
+        reparsed.get_string(1).unwrap().as_deref(),
+        Some("Replaced line")
+    );
+    assert_eq!(reparsed.get_string(2).unwrap().as_deref(), Some("Two"));
+    assert!(!reparsed.entries[3].is_present());
+    assert_eq!(reparsed.get_string(5).unwrap().as_deref(), Some("Appended"));
+    assert_eq!(
+        reparsed.string_data.len(),
+        "ZeroReplaced lineTwoAppended".len()
+    );
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let path = temp_dir.path().join("custom.tlk");
+    tlk.write_to_file(&path).expect("Failed to write");
+    assert_eq!(std::fs::read(&path).unwrap(), tlk.to_bytes().unwrap());
+}
+
+#[test]
+fn test_tlk_tokenized_search_ranks_phrase_first() {
+    let mut tlk = TLKParser::new();
+    tlk.parse_from_bytes(&build_tlk(&[
+        "The ball of fire rolls on",
+        "Cast Fire Ball at the gate.",
+        "Nothing relevant here",
+        "A fire burns",
+    ]))
+    .expect("Failed to parse TLK");
+
+    let options = SearchOptions {
+        mode: SearchMode::Tokenized,
+        ..SearchOptions::default()
+    };
+    let results = tlk.search_strings("fire ball", &options).unwrap();
+    let order: Vec<usize> = results.iter().map(|r| r.str_ref).collect();
+    assert_eq!(order, vec![1, 0, 3]);
+    assert!(results[0].score > results[1].score);
+    assert!(results[1].score > results[2].score);
+
+    // Default substring mode needs the whole query verbatim
+    let results = tlk
+        .search_strings("fire ball", &SearchOptions::default())
+        .unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].str_ref, 1);
+}
+
+#[test]
+fn test_tlk_search_index_skips_decoding() {
+    // Only the first 100 entries are pre-cached on parse
+    let mut strings: Vec<String> = (0..150).map(|i| format!("Line {i}")).collect();
+    strings[120] = "The Dragon sleeps".to_string();
+    let refs: Vec<&str> = strings.iter().map(String::as_str).collect();
+    let data = build_tlk(&refs);
+
+    let mut plain = TLKParser::new();
+    plain.parse_from_bytes(&data).unwrap();
+    let results = plain
+        .search_strings("dragon", &SearchOptions::default())
+        .unwrap();
+    assert_eq!(results[0].str_ref, 120);
+    assert_eq!(plain.string_cache.len(), 150);
+
+    let mut indexed = TLKParser::new();
+    indexed.parse_from_bytes(&data).unwrap();
+    assert!(!indexed.has_search_index());
+    indexed.build_search_index();
+    assert!(indexed.has_search_index());
+    assert_eq!(indexed.string_cache.len(), 100);
+
+    let results = indexed
+        .search_strings("dragon", &SearchOptions::default())
+        .unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].content, "The Dragon sleeps");
+    assert_eq!(indexed.string_cache.len(), 101);
+
+    indexed.set_string(130, "A second DRAGON").unwrap();
+    indexed.set_string(200, "dragon appended").unwrap();
+    let found: Vec<usize> = indexed
+        .search_strings("dragon", &SearchOptions::default())
+        .unwrap()
+        .iter()
+        .map(|r| r.str_ref)
+        .collect();
+    assert_eq!(found.len(), 3);
+    assert!(found.contains(&130) && found.contains(&200));
+
+    indexed.parse_from_bytes(&data).unwrap();
+    assert!(!indexed.has_search_index());
+}
+
+/// French (`language_id` 1) table with "Épée" in Windows-1252 at 1 and
+/// "Zażółć" in Windows-1250 at 2
+fn legacy_tlk() -> Vec<u8> {
+    let latin1: &[u8] = &[0xC9, b'p', 0xE9, b'e'];
+    let polish: &[u8] = &[b'Z', b'a', 0xBF, 0xF3, 0xB3, 0xE6];
+
+    let mut data = build_tlk(&["Plain", "xxxx", "xxxxxx", "Déjà vu"]);
+    let table_end = data.len() - "PlainxxxxxxxxxxDéjà vu".len();
+    data[table_end + 5..table_end + 9].copy_from_slice(latin1);
+    data[table_end + 9..table_end + 15].copy_from_slice(polish);
+    data[8..12].copy_from_slice(&1u32.to_le_bytes());
+    data
+}
+
+#[test]
+fn test_tlk_legacy_encoding() {
+    let mut tlk = TLKParser::new();
+    tlk.parse_from_bytes(&legacy_tlk()).unwrap();
+    assert!(tlk.encoding().is_none());
+    assert_eq!(tlk.get_string(0).unwrap().as_deref(), Some("Plain"));
+    assert_eq!(tlk.get_string(1).unwrap().as_deref(), Some("Épée"));
+    assert_eq!(tlk.get_string(3).unwrap().as_deref(), Some("Déjà vu"));
+
+    tlk.set_encoding(encoding_rs::WINDOWS_1250);
+    assert_eq!(tlk.get_string(2).unwrap().as_deref(), Some("Zażółć"));
+
+    tlk.set_string(4, "Gęś").unwrap();
+    assert_eq!(tlk.entries[4].string_size, 3);
+    assert_eq!(tlk.get_string(4).unwrap().as_deref(), Some("Gęś"));
+}
+
+#[test]
+fn test_tlk_set_string_rejects_unencodable_text() {
+    let mut tlk = TLKParser::new();
+    tlk.parse_from_bytes(&legacy_tlk()).unwrap();
+    tlk.set_encoding(encoding_rs::WINDOWS_1252);
+    let entry = tlk.entries[1].clone();
+    let file_len = tlk.to_bytes().unwrap().len();
+
+    assert!(matches!(
+        tlk.set_string(1, "Épée 剣"),
+        Err(TLKError::UnencodableString { str_ref: 1, .. })
+    ));
+    assert_eq!(tlk.entries[1].data_offset, entry.data_offset);
+    assert_eq!(tlk.entries[1].string_size, entry.string_size);
+    assert_eq!(tlk.get_string(1).unwrap().as_deref(), Some("Épée"));
+    assert_eq!(tlk.to_bytes().unwrap().len(), file_len);
+}
+
+#[test]
+fn test_tlk_legacy_encoding_has_no_decode_errors() {
+    let mut tlk = TLKParser::new();
+    tlk.parse_from_bytes(&legacy_tlk()).unwrap();
+    for str_ref in 0..tlk.string_count() {
+        tlk.get_string(str_ref).unwrap();
+    }
+
+    assert!(tlk.decode_errors().is_empty());
+    assert!(tlk.validate_all().unwrap().decode_failures.is_empty());
+}
+
+#[test]
+fn test_tlk_decode_errors() {
+    let mut data = build_tlk(&["Fine", "Bad xx here", "Also fine"]);
+    let bad_at = data.len() - "xx hereAlso fine".len();
+    data[bad_at..bad_at + 2].copy_from_slice(&[0xFF, 0xFE]);
+
+    // Auto-detect falls back to Windows-1252, which decodes these bytes
+    let mut tlk = TLKParser::new();
+    tlk.parse_from_bytes(&data).unwrap();
+    assert!(tlk.get_string(1).unwrap().is_some());
+    assert!(tlk.decode_errors().is_empty());
+
+    tlk.set_encoding(encoding_rs::UTF_8);
+    assert!(tlk.get_string(1).unwrap().is_some());
+    assert_eq!(
+        tlk.decode_errors(),
+        &[TlkDecodeError {
+            str_ref: 1,
+            offset: "FineBad ".len(),
+        }]
+    );
+    assert!(tlk.get_string(1).unwrap().is_some());
+    assert_eq!(tlk.decode_errors().len(), 1);
+
+    tlk.set_string(1, "Fixed").unwrap();
+    assert!(tlk.decode_errors().is_empty());
+}
+
+#[test]
+fn test_tlk_validate_all() {
+    let mut data = build_tlk(&["Fine", "Bad xx", "Gone", "Past the end"]);
+    let bad_at = data.len() - "xxGonePast the end".len();
+    data[bad_at..bad_at + 2].copy_from_slice(&[0xFF, 0xFE]);
+    // Clear the present flag on entry 2 and push entry 3's size past the end
+    data[20 + 40 * 2..20 + 40 * 2 + 4].copy_from_slice(&0u32.to_le_bytes());
+    data[20 + 40 * 3 + 32..20 + 40 * 3 + 36].copy_from_slice(&500u32.to_le_bytes());
+
+    let mut tlk = TLKParser::new();
+    tlk.parse_from_bytes(&data).unwrap();
+    tlk.set_encoding(encoding_rs::UTF_8);
+    let cached = tlk.string_cache.len();
+
+    let report = tlk.validate_all().unwrap();
+    assert_eq!(report.present, 3);
+    assert_eq!(report.absent, 1);
+    assert_eq!(report.out_of_bounds, vec![3]);
+    assert_eq!(
+        report.decode_failures,
+        vec![TlkDecodeError {
+            str_ref: 1,
+            offset: "FineBad ".len(),
+        }]
+    );
+    assert!(!report.is_valid());
+    assert_eq!(tlk.string_cache.len(), cached);
+
+    let mut clean = TLKParser::new();
+    clean
+        .parse_from_bytes(&build_tlk(&["Zero", "One"]))
+        .unwrap();
+    assert!(clean.validate_all().unwrap().is_valid());
+    assert_eq!(
+        TLKParser::new().validate_all().unwrap(),
+        ValidationReport::default()
+    );
+}
+
+#[test]
+fn test_tlk_validate_all_game_dialog() {
+    let Some(path) = NWN2Paths::new().dialog_tlk().filter(|p| p.exists()) else {
+        println!("dialog.tlk not found, skipping");
+        return;
+    };
+
+    let mut tlk = TLKParser::new();
+    tlk.parse_from_file(&path)
+        .expect("Failed to parse dialog.tlk");
+    let report = tlk.validate_all().unwrap();
+
+    println!(
+        "dialog.tlk: {} present, {} absent, {} out of bounds, {} decode failures",
+        report.present,
+        report.absent,
+        report.out_of_bounds.len(),
+        report.decode_failures.len()
+    );
+    assert_eq!(report.present + report.absent, tlk.string_count());
+    assert!(report.out_of_bounds.is_empty());
+}
+
+#[test]
+fn test_tlk_entry_metadata() {
+    let mut data = build_tlk(&["Hello there", "No voice"]);
+    data[24..33].copy_from_slice(b"vo_hello1");
+
+    let mut tlk = TLKParser::new();
+    tlk.parse_from_bytes(&data).unwrap();
+
+    let entry = tlk.get_entry(0).expect("entry 0 exists");
+    assert_eq!(entry.flags & 0x01, 0x01);
+    assert!(entry.is_present());
+    assert_eq!(entry.string_size, 11);
+    assert_eq!(tlk.get_sound_resref(0), Some("vo_hello1"));
+    assert_eq!(tlk.get_sound_resref(1), None);
+
+    assert!(tlk.get_entry(2).is_none());
+    assert!(tlk.get_sound_resref(usize::MAX).is_none());
+}
+
+#[test]
+fn test_tlk_diff() {
+    let mut base = TLKParser::new();
+    base.parse_from_bytes(&build_tlk(&["Zero", "One", "Two"]))
+        .unwrap();
+    let mut edited = TLKParser::new();
+    edited
+        .parse_from_bytes(&build_tlk(&["Zero", "Uno", "Two"]))
+        .unwrap();
+
+    assert_eq!(
+        base.diff(&mut edited).unwrap(),
+        vec![TlkDiff {
+            str_ref: 1,
+            change: TlkChange::Changed {
+                old: "One".to_string(),
+                new: "Uno".to_string(),
+            },
+        }]
+    );
+
+    edited.set_string(4, "Four").unwrap();
+    let diffs = base.diff(&mut edited).unwrap();
+    assert_eq!(diffs.len(), 3);
+    assert_eq!(
+        diffs[2],
+        TlkDiff {
+            str_ref: 4,
+            change: TlkChange::Added {
+                new: "Four".to_string(),
+            },
+        }
+    );
+
+    let reverse = edited.diff(&mut base).unwrap();
+    assert!(matches!(reverse[2].change, TlkChange::Removed { ref old } if old == "Four"));
+
+    let mut same = TLKParser::new();
+    same.parse_from_bytes(&build_tlk(&["Zero", "One", "Two"]))
+        .unwrap();
+    assert!(base.diff(&mut same).unwrap().is_empty());
+}
+
+#[test]
+fn test_tlk_load_multiple_files_keeps_valid_on_error() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let good = temp_dir.path().join("dialog.tlk");
+    let bad = temp_dir.path().join("broken.tlk");
+    std::fs::write(&good, build_tlk(&["Zero", "One"])).unwrap();
+    std::fs::write(&bad, b"not a tlk").unwrap();
+
+    for max_threads in [None, Some(1)] {
+        let mut result = load_multiple_files(&[&good, &bad], None, max_threads);
+        assert_eq!(result.loaded.len(), 1);
+        assert!(result.errors.contains_key(&bad));
+        let tlk = result.loaded.get_mut(&good).unwrap();
+        assert_eq!(tlk.get_string(1).unwrap().as_deref(), Some("One"));
+    }
+}
+
+#[test]
+fn test_tlk_cache_rebuilds_backdated_source() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let source = temp_dir.path().join("dialog.tlk");
+    let cache = temp_dir.path().join("dialog.tlk.cache");
+
+    std::fs::write(&source, build_tlk(&["Zero", "One"])).unwrap();
+    let mut tlk = TLKParser::new();
+    assert!(!tlk.load_with_cache(&source, Some(&cache)).unwrap());
+    let mut tlk = TLKParser::new();
+    assert!(tlk.load_with_cache(&source, Some(&cache)).unwrap());
+
+    // Rewrite the source but backdate it, as a restore from backup would
+    std::fs::write(&source, build_tlk(&["Zero", "Uno"])).unwrap();
+    std::fs::File::options()
+        .write(true)
+        .open(&source)
+        .unwrap()
+        .set_modified(std::time::SystemTime::UNIX_EPOCH)
+        .unwrap();
+
+    let mut tlk = TLKParser::new();
+    assert!(!tlk.load_with_cache(&source, Some(&cache)).unwrap());
+    assert_eq!(tlk.get_string(1).unwrap().as_deref(), Some("Uno"));
+}
+
+#[test]
+fn test_tlk_cache_records_content_hash() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let source = temp_dir.path().join("dialog.tlk");
+    let cache = temp_dir.path().join("dialog.tlk.cache");
+
+    std::fs::write(&source, build_tlk(&["Zero", "One"])).unwrap();
+    let load = |tlk: &mut TLKParser| tlk.load_with_cache_hashed(&source, &cache).unwrap();
+    let mut tlk = TLKParser::new();
+    assert!(!load(&mut tlk));
+
+    // A backdated rewrite fools an mtime check but not the recorded hash
+    std::fs::write(&source, build_tlk(&["Zero", "Uno"])).unwrap();
+    std::fs::File::options()
+        .write(true)
+        .open(&source)
+        .unwrap()
+        .set_modified(std::time::SystemTime::UNIX_EPOCH)
+        .unwrap();
+
+    let mut tlk = TLKParser::new();
+    assert!(!load(&mut tlk));
+    assert_eq!(tlk.get_string(1).unwrap().as_deref(), Some("Uno"));
+    let mut tlk = TLKParser::new();
+    assert!(load(&mut tlk));
+}
+
+#[test]
+fn test_tlk_utf8_bom() {
+    let mut data = b"\xEF\xBB\xBF".to_vec();
+    data.extend_from_slice(&build_tlk(&["Zero", "One"]));
+
+    let mut tlk = TLKParser::new();
+    tlk.parse_from_bytes(&data).expect("BOM should be skipped");
+    assert_eq!(tlk.get_string(1).unwrap().as_deref(), Some("One"));
+    assert!(tlk.metadata().had_bom);
+    assert!(tlk.metadata().has_warnings);
+
+    tlk.parse_from_bytes(&build_tlk(&["Zero"])).unwrap();
+    assert!(!tlk.metadata().had_bom);
+
+    // Buffered and memory-mapped file loads skip it the same way
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let path = temp_dir.path().join("bom.tlk");
+    std::fs::write(&path, &data).unwrap();
+
+    let mut buffered = TLKParser::new();
+    buffered.parse_from_file(&path).unwrap();
+    let mut mapped = TLKParser::new();
+    mapped.parse_from_file_mmap(&path).unwrap();
+    for tlk in [&mut buffered, &mut mapped] {
+        assert_eq!(tlk.get_string(0).unwrap().as_deref(), Some("Zero"));
+        assert_eq!(tlk.get_string(1).unwrap().as_deref(), Some("One"));
+        assert!(tlk.metadata().had_bom);
+    }
+}
+
+#[test]
+fn test_tlk_clear_then_query() {
+    let mut tlk = TLKParser::new();
+    assert!(!tlk.is_loaded());
+    assert_eq!(tlk.get_string(0).unwrap(), None);
+
+    tlk.parse_from_bytes(&build_tlk(&["Zero", "One"])).unwrap();
+    assert!(tlk.is_loaded());
+    assert_eq!(tlk.get_string(1).unwrap().as_deref(), Some("One"));
+
+    tlk.clear();
+    assert!(!tlk.is_loaded());
+    assert_eq!(tlk.get_string(0).unwrap(), None);
+    assert!(
+        tlk.search_strings("one", &SearchOptions::default())
+            .unwrap()
+            .is_empty()
+    );
+
+    // Reused for a different file, nothing from the first one survives
+    tlk.parse_from_bytes(&build_tlk(&["Other"])).unwrap();
+    assert!(tlk.is_loaded());
+    assert_eq!(tlk.get_string(0).unwrap().as_deref(), Some("Other"));
+    assert_eq!(tlk.get_string(1).unwrap(), None);
+
+    let mut empty = TLKParser::new();
+    empty.parse_from_bytes(&build_tlk(&[])).unwrap();
+    assert!(!empty.is_loaded());
+
+    let mut built = TLKParser::new();
+    built.set_string(0, "Fresh").unwrap();
+    assert!(built.is_loaded());
+    assert_eq!(built.get_string(0).unwrap().as_deref(), Some("Fresh"));
+}
+
+#[test]
+fn test_tlk_mmap_matches_eager() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let path = temp_dir.path().join("dialog.tlk");
+    let strings: Vec<String> = (0..250).map(|i| format!("Line number {i}")).collect();
+    let refs: Vec<&str> = strings.iter().map(String::as_str).collect();
+    std::fs::write(&path, build_tlk(&refs)).unwrap();
+
+    let mut eager = TLKParser::new();
+    eager.parse_from_file(&path).unwrap();
+    let mut lazy = TLKParser::new();
+    lazy.parse_from_file_mmap(&path).unwrap();
+
+    assert!(lazy.is_memory_mapped());
+    assert!(lazy.string_data.is_empty());
+    assert_eq!(lazy.string_count(), eager.string_count());
+    for str_ref in [0, 1, 99, 100, 249, 250] {
+        assert_eq!(
+            lazy.get_string(str_ref).unwrap(),
+            eager.get_string(str_ref).unwrap()
+        );
+    }
+    assert_eq!(lazy.to_bytes().unwrap(), eager.to_bytes().unwrap());
+
+    lazy.set_string(3, "Edited").unwrap();
+    assert!(!lazy.is_memory_mapped());
+    assert_eq!(lazy.get_string(3).unwrap().as_deref(), Some("Edited"));
+    assert_eq!(
+        lazy.get_string(200).unwrap().as_deref(),
+        Some("Line number 200")
+    );
+}
+
 // =============================================================================
 // EDGE CASES
 // =============================================================================
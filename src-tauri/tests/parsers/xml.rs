@@ -1,7 +1,12 @@
+use std::collections::HashMap;
 use std::fmt::Write as _;
 use std::path::PathBuf;
 
-use app_lib::parsers::xml::RustXmlParser;
+use app_lib::parsers::xml::{
+    CompanionDefinition, QuestClassifierConfig, QuestGroupOrder, RustXmlParser, XmlChangeKind,
+    XmlValue, last_write_time_to_datetime,
+};
+use regex::Regex;
 
 fn fixtures_path() -> PathBuf {
     PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
@@ -169,6 +174,67 @@ fn test_quest_groups_structure() {
     }
 }
 
+#[test]
+fn test_quest_config_threshold_and_patterns() {
+    let xml = r"<Globals>
+    <Integers>
+        <Integer><Name>21_TowerState</Name><Value>30</Value></Integer>
+        <Integer><Name>31_SilverSwordStep</Name><Value>2</Value></Integer>
+        <Integer><Name>15_DuncanQuest</Name><Value>1</Value></Integer>
+    </Integers>
+</Globals>";
+
+    let default = RustXmlParser::from_string(xml).expect("parse");
+    let overview = default.get_quest_overview_struct();
+    assert_eq!(overview.completed_count, 0);
+    assert_eq!(overview.active_count, 2);
+
+    let parser = RustXmlParser::from_string(xml)
+        .expect("parse")
+        .with_quest_config(QuestClassifierConfig {
+            completion_threshold: 30,
+            include_patterns: vec![Regex::new(r"(?i).*Step$").unwrap()],
+            exclude_patterns: vec![Regex::new(r"^15_").unwrap()],
+        });
+    let overview = parser.get_quest_overview_struct();
+
+    assert_eq!(overview.completed_count, 1);
+    assert_eq!(overview.active_count, 1);
+    assert_eq!(overview.quest_groups["21"].completed, vec!["21_TowerState"]);
+    assert_eq!(
+        overview.quest_groups["31"].active,
+        vec!["31_SilverSwordStep"]
+    );
+    assert!(!overview.quest_groups.contains_key("15"));
+}
+
+#[test]
+fn test_quest_groups_sorted_by_progress() {
+    let xml = r"<Globals>
+    <Integers>
+        <Integer><Name>10_TowerState</Name><Value>80</Value></Integer>
+        <Integer><Name>20_FortDone</Name><Value>1</Value></Integer>
+        <Integer><Name>20_FortQuest</Name><Value>1</Value></Integer>
+        <Integer><Name>30_CourtState</Name><Value>5</Value></Integer>
+        <Integer><Name>30_TrialQuest</Name><Value>1</Value></Integer>
+        <Integer><Name>30_JuryQuest</Name><Value>1</Value></Integer>
+        <Integer><Name>40_KeepDone</Name><Value>1</Value></Integer>
+    </Integers>
+</Globals>";
+    let parser = RustXmlParser::from_string(xml).expect("parse");
+    let names = |order| -> Vec<String> {
+        parser
+            .get_quest_groups_sorted(order)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect()
+    };
+
+    // 10, 20, and 40 each have one completed var: alphabetical among them
+    assert_eq!(names(QuestGroupOrder::Completed), ["10", "20", "40", "30"]);
+    assert_eq!(names(QuestGroupOrder::Total), ["30", "20", "10", "40"]);
+}
+
 // =============================================================================
 // COMPANION STATUS TESTS
 // =============================================================================
@@ -213,6 +279,47 @@ fn test_motb_companions() {
     }
 }
 
+#[test]
+fn test_custom_companion_definitions_are_explicit() {
+    let xml = r"<Globals>
+    <Integers>
+        <Integer><Name>00_nInfluenceneeshka</Name><Value>10</Value></Integer>
+        <Integer><Name>soz_infBelueth</Name><Value>7</Value></Integer>
+        <Integer><Name>soz_BeluethJoined</Name><Value>1</Value></Integer>
+    </Integers>
+</Globals>";
+
+    let discovered = RustXmlParser::from_string(xml).expect("parse");
+    assert_eq!(
+        discovered.get_companion_status()["belueth"].source,
+        "discovered"
+    );
+
+    let mut custom = HashMap::new();
+    custom.insert(
+        "belueth",
+        CompanionDefinition {
+            name: "Belueth",
+            influence_var: "soz_infBelueth",
+            joined_var: "soz_BeluethJoined",
+            met_var: None,
+            ros_name: None,
+        },
+    );
+    let parser = RustXmlParser::from_string(xml)
+        .expect("parse")
+        .with_companion_definitions(custom);
+    let status = parser.get_companion_status();
+
+    let belueth = &status["belueth"];
+    assert_eq!(belueth.source, "explicit");
+    assert_eq!(belueth.name, "Belueth");
+    assert_eq!(belueth.influence, Some(7));
+    assert_eq!(belueth.recruitment, "recruited");
+    assert_eq!(status["neeshka"].source, "explicit");
+    assert!(parser.companion_definitions().contains_key("khelgar"));
+}
+
 // =============================================================================
 // FULL SUMMARY TESTS
 // =============================================================================
@@ -238,6 +345,53 @@ fn test_full_summary() {
     println!("  Raw data: {:?}", summary.raw_data_counts);
 }
 
+#[test]
+fn test_last_saved_unix_seconds_and_filetime() {
+    let unix = RustXmlParser::from_string(
+        r"<Globals><Integers>
+        <Integer><Name>LastWriteTime</Name><Value>1700000000</Value></Integer>
+    </Integers></Globals>",
+    )
+    .expect("parse");
+    assert_eq!(
+        unix.get_general_info()["last_saved"].as_deref(),
+        Some("2023-11-14T22:13:20+00:00")
+    );
+
+    let filetime = RustXmlParser::from_string(
+        r"<Globals><Strings>
+        <String><Name>LastWriteTime</Name><Value>133000000000000000</Value></String>
+    </Strings></Globals>",
+    )
+    .expect("parse");
+    assert_eq!(
+        filetime.get_general_info()["last_saved"].as_deref(),
+        Some("2022-06-18T04:26:40+00:00")
+    );
+
+    let filetime_int = RustXmlParser::from_string(
+        r"<Globals><Integers>
+        <Integer><Name>LastWriteTime</Name><Value>133444736000000000</Value></Integer>
+    </Integers></Globals>",
+    )
+    .expect("FILETIME-sized integer should parse");
+    assert_eq!(
+        filetime_int.get_general_info()["last_saved"].as_deref(),
+        Some("2023-11-14T22:13:20+00:00")
+    );
+    let reparsed = RustXmlParser::from_string(&filetime_int.to_xml_string().unwrap()).unwrap();
+    assert_eq!(
+        reparsed.data.integers["LastWriteTime"],
+        133_444_736_000_000_000
+    );
+
+    let same_instant = last_write_time_to_datetime(1_700_000_000).unwrap();
+    assert_eq!(
+        last_write_time_to_datetime(133_444_736_000_000_000),
+        Some(same_instant)
+    );
+}
+
 // =============================================================================
 // ROUND-TRIP TESTS
 // =============================================================================
@@ -606,3 +760,102 @@ fn test_cheat_flags_stripped_on_write() {
         "Only Cheater/ShowCheatsWarning may be stripped; every other boolean must survive"
     );
 }
+
+#[test]
+fn test_xml_setters_round_trip() {
+    let xml = r"<Globals>
+    <Integers>
+        <Integer><Name>00_nInfluenceneeshka</Name><Value>10</Value></Integer>
+        <Integer><Name>00_nUnchanged</Name><Value>3</Value></Integer>
+    </Integers>
+</Globals>";
+
+    let mut parser = RustXmlParser::from_string(xml).expect("parse");
+    parser.set_integer("00_nInfluenceneeshka", 42);
+    parser.set_integer("21_TowerState", 100);
+    parser.set_string("00_sLastArea", "West Harbor");
+    parser.set_float("00_fDifficulty", 1.25);
+    parser.set_vector("00_vCampPos", [1.5, 2.0, -3.0]);
+
+    let written = parser.to_xml_string().expect("serialize");
+    let reparsed = RustXmlParser::from_string(&written).expect("reparse");
+
+    assert_eq!(reparsed.data.integers["00_nInfluenceneeshka"], 42);
+    assert_eq!(reparsed.data.integers["00_nUnchanged"], 3);
+    assert_eq!(reparsed.data.integers["21_TowerState"], 100);
+    assert_eq!(
+        reparsed.data.integers.get_index_of("00_nInfluenceneeshka"),
+        Some(0),
+        "edited entries keep their position"
+    );
+    assert_eq!(reparsed.data.strings["00_sLastArea"], "West Harbor");
+    assert!((reparsed.data.floats["00_fDifficulty"] - 1.25).abs() < f32::EPSILON);
+    let camp = reparsed.data.vectors["00_vCampPos"];
+    assert!(
+        camp.iter()
+            .zip([1.5, 2.0, -3.0])
+            .all(|(a, b)| (a - b).abs() < f32::EPSILON),
+        "{camp:?}"
+    );
+}
+
+// =============================================================================
+// DIFF TESTS
+// =============================================================================
+
+#[test]
+fn test_xml_diff_reports_changes() {
+    let before = r"<Globals>
+    <Integers>
+        <Integer><Name>00_nInfluenceneeshka</Name><Value>10</Value></Integer>
+        <Integer><Name>00_nUnchanged</Name><Value>3</Value></Integer>
+    </Integers>
+    <Strings>
+        <String><Name>00_sLastArea</Name><Value>Highcliff</Value></String>
+    </Strings>
+    <Vectors>
+        <Vector><Name>00_vCampPos</Name><X>1.5</X><Y>2.0</Y><Z>0.0</Z></Vector>
+    </Vectors>
+</Globals>";
+    let after = r"<Globals>
+    <Integers>
+        <Integer><Name>00_nInfluenceneeshka</Name><Value>25</Value></Integer>
+        <Integer><Name>00_nUnchanged</Name><Value>3</Value></Integer>
+    </Integers>
+    <Strings>
+        <String><Name>00_sLastArea</Name><Value>Highcliff</Value></String>
+        <String><Name>00_sQuestNote</Name><Value>Met Duncan</Value></String>
+    </Strings>
+</Globals>";
+
+    let old = RustXmlParser::from_string(before).expect("parse before");
+    let new = RustXmlParser::from_string(after).expect("parse after");
+    assert_eq!(old.data.vectors.len(), 1);
+
+    let diff = old.diff(&new);
+    assert_eq!(diff.changes.len(), 3, "{diff:?}");
+
+    let find = |name: &str| {
+        diff.changes
+            .iter()
+            .find(|c| c.name == name)
+            .unwrap_or_else(|| panic!("missing diff for {name}"))
+    };
+
+    let influence = find("00_nInfluenceneeshka");
+    assert_eq!(influence.kind, XmlChangeKind::Changed);
+    assert_eq!(influence.old, Some(XmlValue::Integer(10)));
+    assert_eq!(influence.new, Some(XmlValue::Integer(25)));
+
+    let note = find("00_sQuestNote");
+    assert_eq!(note.kind, XmlChangeKind::Added);
+    assert_eq!(note.old, None);
+    assert_eq!(note.new, Some(XmlValue::String("Met Duncan".to_string())));
+
+    let camp = find("00_vCampPos");
+    assert_eq!(camp.kind, XmlChangeKind::Removed);
+    assert_eq!(camp.old, Some(XmlValue::Vector([1.5, 2.0, 0.0])));
+    assert_eq!(camp.new, None);
+
+    assert!(old.diff(&old).is_empty());
+}
@@ -0,0 +1,87 @@
+//! Allocation counting for `TDAParser::iter_rows` against the game's
+//! actions.2da.
+//!
+//! This is its own test binary because the counting `#[global_allocator]`
+//! replaces the allocator for every test in the binary; keeping it here means
+//! no other test runs on it. Counts are per-thread, so the harness's other
+//! threads don't leak into the measurement. Skips when the NWN2 data folder
+//! isn't found.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use app_lib::config::NWN2Paths;
+use app_lib::parsers::tda::TDAParser;
+use app_lib::utils::ZipContentReader;
+
+thread_local! {
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if COUNTING.with(Cell::get) {
+            ALLOCATIONS.with(|n| n.set(n.get() + 1));
+        }
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Allocations made on this thread while running `f`.
+fn count_allocations<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    ALLOCATIONS.with(|n| n.set(0));
+    COUNTING.with(|c| c.set(true));
+    let result = f();
+    COUNTING.with(|c| c.set(false));
+    (result, ALLOCATIONS.with(Cell::get))
+}
+
+fn load_actions_2da() -> Option<TDAParser> {
+    let zip = NWN2Paths::new().data()?.join("2da.zip");
+    if !zip.exists() {
+        return None;
+    }
+
+    let mut reader = ZipContentReader::new();
+    reader
+        .open_many(&[zip.to_string_lossy().into_owned()])
+        .expect("Failed to open 2da.zip");
+    let bytes = reader.read("actions.2da").ok()?;
+
+    let mut parser = TDAParser::new();
+    parser
+        .parse_from_bytes(&bytes)
+        .expect("Failed to parse actions.2da");
+    Some(parser)
+}
+
+#[test]
+fn test_iter_rows_does_not_allocate_per_row() {
+    let Some(parser) = load_actions_2da() else {
+        println!("actions.2da not found, skipping");
+        return;
+    };
+    assert!(parser.row_count() >= 17);
+    let label = parser.column_names()[0].to_string();
+
+    let (labelled, allocations) = count_allocations(|| {
+        parser
+            .iter_rows()
+            .filter(|row| row.get(&label).is_some())
+            .count()
+    });
+
+    assert!(labelled > 0);
+    assert_eq!(allocations, 0);
+    assert_eq!(count_allocations(|| "x".to_string()).1, 1);
+}
@@ -0,0 +1,25 @@
+use app_lib::utils::{content_hash, content_hash_bytes};
+
+#[test]
+fn test_content_hash_tracks_file_content() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let path = temp_dir.path().join("classes.2da");
+
+    std::fs::write(&path, "2DA V2.0\n\n\tLabel\n0\tFighter\n").unwrap();
+    let first = content_hash(&path).unwrap();
+    assert_eq!(first, content_hash(&path).unwrap());
+    assert_eq!(
+        first,
+        content_hash_bytes(b"2DA V2.0\n\n\tLabel\n0\tFighter\n")
+    );
+
+    std::fs::write(&path, "2DA V2.0\n\n\tLabel\n0\tWizard\n").unwrap();
+    assert_ne!(first, content_hash(&path).unwrap());
+
+    // Larger than one read chunk
+    let large = vec![7u8; 200 * 1024];
+    std::fs::write(&path, &large).unwrap();
+    assert_eq!(content_hash(&path).unwrap(), content_hash_bytes(&large));
+
+    assert!(content_hash(&temp_dir.path().join("missing.2da")).is_err());
+}
@@ -1,3 +1,4 @@
+mod content_hash;
 mod path_discovery;
 mod precompiled_cache;
 mod prerequisite_graph;
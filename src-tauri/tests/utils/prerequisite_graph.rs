@@ -1,6 +1,7 @@
 use super::super::common::create_test_context;
 
 use app_lib::utils::PrerequisiteGraph;
+use std::collections::HashMap;
 
 fn cell_value(
     table: &app_lib::loaders::types::LoadedTable,
@@ -411,3 +412,174 @@ async fn test_epic_feat_prerequisites() {
         }
     }
 }
+
+fn feat_row(fields: &[(&str, i64)]) -> HashMap<String, serde_json::Value> {
+    fields
+        .iter()
+        .map(|(key, value)| ((*key).to_string(), serde_json::json!(value)))
+        .collect()
+}
+
+#[test]
+fn test_prerequisite_chains_follow_each_path() {
+    // 0 <- 1 <- 2, and 3 requires both 2 and 0
+    let feat_data = vec![
+        feat_row(&[]),
+        feat_row(&[("prereqfeat1", 0)]),
+        feat_row(&[("prereqfeat1", 1)]),
+        feat_row(&[("prereqfeat1", 2), ("prereqfeat2", 0)]),
+    ];
+    let mut graph = PrerequisiteGraph::new();
+    graph.build_from_data(&feat_data).unwrap();
+
+    assert_eq!(graph.get_prerequisite_chains(2), vec![vec![2, 1, 0]]);
+    assert_eq!(
+        graph.get_prerequisite_chains(3),
+        vec![vec![3, 2, 1, 0], vec![3, 0]]
+    );
+    assert!(graph.get_prerequisite_chains(0).is_empty());
+}
+
+#[test]
+fn test_circular_dependencies_report_cycle_members() {
+    // 1 <-> 2 form a cycle; 3 leads into it
+    let feat_data = vec![
+        feat_row(&[]),
+        feat_row(&[("prereqfeat1", 2)]),
+        feat_row(&[("prereqfeat1", 1)]),
+        feat_row(&[("prereqfeat1", 2)]),
+    ];
+    let mut graph = PrerequisiteGraph::new();
+    graph.build_from_data(&feat_data).unwrap();
+
+    assert_eq!(graph.get_circular_dependencies(), vec![vec![1, 2, 1]]);
+
+    let count = graph.get_statistics()["circular_dependencies_count"]
+        .as_u64()
+        .unwrap();
+    assert_eq!(count, 3);
+}
+
+#[test]
+fn test_class_prerequisite_requires_class_in_list() {
+    let feat_data = vec![feat_row(&[]), feat_row(&[("prereqclass", 4)])];
+    let mut graph = PrerequisiteGraph::new();
+    graph.build_from_data(&feat_data).unwrap();
+
+    let mut fighter_rogue = HashMap::new();
+    fighter_rogue.insert("classes".to_string(), serde_json::json!([4, 8]));
+    assert!(
+        graph
+            .validate_feat_prerequisites_fast(1, &[], Some(&fighter_rogue))
+            .0
+    );
+
+    let mut wizard = HashMap::new();
+    wizard.insert("classes".to_string(), serde_json::json!([10]));
+    let (valid, errors) = graph.validate_feat_prerequisites_fast(1, &[], Some(&wizard));
+    assert!(!valid);
+    assert_eq!(errors, vec!["Requires class 4".to_string()]);
+
+    let batch = graph.validate_batch_fast(vec![0, 1], &[], Some(&wizard));
+    assert!(batch[&0].0);
+    assert_eq!(batch[&1], (false, vec!["Requires class 4".to_string()]));
+}
+
+#[test]
+fn test_spell_level_prerequisite_fails_for_non_caster() {
+    // Metamagic-style feat needing 1st level spells
+    let feat_data = vec![feat_row(&[("minspelllvl", 1)])];
+    let mut graph = PrerequisiteGraph::new();
+    graph.build_from_data(&feat_data).unwrap();
+
+    let mut fighter = HashMap::new();
+    fighter.insert("spell_level".to_string(), serde_json::json!(0));
+    let expected = (false, vec!["Requires spell level 1".to_string()]);
+    assert_eq!(
+        graph.validate_feat_prerequisites_fast(0, &[], Some(&fighter)),
+        expected
+    );
+    assert_eq!(
+        graph.validate_batch_fast(vec![0], &[], Some(&fighter))[&0],
+        expected
+    );
+
+    let mut wizard = HashMap::new();
+    wizard.insert("spell_level".to_string(), serde_json::json!(3));
+    assert!(
+        graph
+            .validate_feat_prerequisites_fast(0, &[], Some(&wizard))
+            .0
+    );
+}
+
+#[test]
+fn test_available_feats_excludes_owned_and_includes_newly_eligible() {
+    // 0 <- 1 <- 2, and 3 needs STR 13
+    let feat_data = vec![
+        feat_row(&[]),
+        feat_row(&[("prereqfeat1", 0)]),
+        feat_row(&[("prereqfeat1", 1)]),
+        feat_row(&[("minstr", 13)]),
+    ];
+    let mut graph = PrerequisiteGraph::new();
+    graph.build_from_data(&feat_data).unwrap();
+
+    let mut data = HashMap::new();
+    data.insert("strength".to_string(), serde_json::json!(10));
+    assert_eq!(graph.available_feats(&[0], Some(&data)), vec![1]);
+
+    data.insert("strength".to_string(), serde_json::json!(14));
+    assert_eq!(graph.available_feats(&[0, 1], Some(&data)), vec![2, 3]);
+}
+
+#[test]
+fn test_or_prerequisites_pass_with_any_option() {
+    let feat_data = vec![
+        feat_row(&[]),
+        feat_row(&[]),
+        feat_row(&[("orreqfeat0", 0), ("orreqfeat1", 1)]),
+    ];
+    let mut graph = PrerequisiteGraph::new();
+    graph.build_from_data(&feat_data).unwrap();
+
+    assert_eq!(
+        graph.validate_feat_prerequisites_fast(2, &[1], None),
+        (true, vec![])
+    );
+    assert!(graph.validate_batch_fast(vec![2], &[1], None)[&2].0);
+
+    let (valid, errors) = graph.validate_feat_prerequisites_fast(2, &[], None);
+    assert!(!valid);
+    assert_eq!(errors, vec!["Requires one of Feats 0, 1".to_string()]);
+}
+
+#[test]
+fn test_graph_bytes_round_trip_preserves_validation() {
+    let feat_data = vec![
+        feat_row(&[]),
+        feat_row(&[("prereqfeat1", 0), ("minstr", 13)]),
+        feat_row(&[("prereqfeat1", 1), ("orreqfeat0", 3)]),
+        feat_row(&[("minlevel", 4)]),
+    ];
+    let mut graph = PrerequisiteGraph::new();
+    graph.build_from_data(&feat_data).unwrap();
+
+    let restored = PrerequisiteGraph::from_bytes(&graph.to_bytes().unwrap()).unwrap();
+
+    let mut data = HashMap::new();
+    data.insert("strength".to_string(), serde_json::json!(12));
+    data.insert("level".to_string(), serde_json::json!(2));
+    let feats: Vec<u32> = (0..4).collect();
+    for owned in [vec![], vec![0], vec![0, 1, 3]] {
+        assert_eq!(
+            restored.validate_batch_fast(feats.clone(), &owned, Some(&data)),
+            graph.validate_batch_fast(feats.clone(), &owned, Some(&data))
+        );
+    }
+    assert_eq!(
+        restored.get_statistics()["max_chain_depth"],
+        graph.get_statistics()["max_chain_depth"]
+    );
+    assert!(PrerequisiteGraph::new().to_bytes().is_err());
+}
@@ -339,3 +339,77 @@ fn test_read_from_enhanced_data() {
         }
     }
 }
+
+fn write_test_zip(path: &std::path::Path, entries: &[(&str, &[u8])]) {
+    use std::io::Write;
+
+    let file = std::fs::File::create(path).expect("create zip");
+    let mut writer = zip::ZipWriter::new(file);
+    for (name, data) in entries {
+        writer
+            .start_file(*name, zip::write::SimpleFileOptions::default())
+            .expect("start zip entry");
+        writer.write_all(data).expect("write zip entry");
+    }
+    writer.finish().expect("finish zip");
+}
+
+#[test]
+fn test_open_many_resolves_resource_in_second_archive() {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let first = temp.path().join("2da.zip");
+    let second = temp.path().join("2da_x1.zip");
+    write_test_zip(&first, &[("2DA/classes.2da", b"2DA V2.0 classes")]);
+    write_test_zip(&second, &[("2DA_X1/Feat.2da", b"2DA V2.0 feat")]);
+
+    let mut reader = ZipContentReader::new();
+    let indexed = reader
+        .open_many(&[
+            first.to_string_lossy().to_string(),
+            second.to_string_lossy().to_string(),
+        ])
+        .expect("open_many");
+    assert_eq!(indexed, 2);
+
+    assert_eq!(
+        reader.read("feat.2da").expect("read feat"),
+        b"2DA V2.0 feat"
+    );
+    assert_eq!(
+        reader.read("CLASSES.2DA").expect("read classes"),
+        b"2DA V2.0 classes"
+    );
+    assert!(reader.read("spells.2da").is_err());
+}
+
+#[test]
+fn test_open_many_prefers_higher_priority_archive() {
+    let temp = tempfile::tempdir().expect("tempdir");
+    let base = temp.path().join("2da.zip");
+    let hak = temp.path().join("custom_hak.zip");
+    write_test_zip(&base, &[("2DA/classes.2da", b"base classes")]);
+    write_test_zip(&hak, &[("classes.2da", b"hak classes")]);
+
+    let base_path = base.to_string_lossy().to_string();
+    let hak_path = hak.to_string_lossy().to_string();
+    let mut reader = ZipContentReader::new();
+    assert_eq!(
+        reader
+            .open_many(&[base_path.clone(), hak_path.clone()])
+            .expect("open_many"),
+        1
+    );
+
+    let result = reader.read_with_source("classes.2da");
+    assert!(result.success);
+    assert_eq!(result.data.as_deref(), Some(&b"hak classes"[..]));
+    assert_eq!(result.source_archive.as_deref(), Some(hak_path.as_str()));
+
+    let versions = reader.read_all_versions("classes.2da");
+    let sources: Vec<_> = versions
+        .iter()
+        .map(|v| v.source_archive.clone().unwrap())
+        .collect();
+    assert_eq!(sources, vec![hak_path, base_path]);
+    assert_eq!(versions[1].data.as_deref(), Some(&b"base classes"[..]));
+}